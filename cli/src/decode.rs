@@ -11,17 +11,19 @@ use voter_stake_registry::state::*;
 // accounts and then converted to JSON.
 //
 
+// The locked amounts of deposit entries with tranches depend on their
+// VestingSchedule account and are left empty.
 #[derive(Serialize)]
 struct DisplayDepositEntry {
     allow_clawback: bool,
     mint_index: u8,
-    unlocked_now: u64,
-    locked_now: u64,
-    locked_1y: u64,
-    locked_2y: u64,
-    locked_3y: u64,
-    locked_4y: u64,
-    locked_5y: u64,
+    unlocked_now: Option<u64>,
+    locked_now: Option<u64>,
+    locked_1y: Option<u64>,
+    locked_2y: Option<u64>,
+    locked_3y: Option<u64>,
+    locked_4y: Option<u64>,
+    locked_5y: Option<u64>,
 }
 
 #[derive(Serialize)]
@@ -31,10 +33,28 @@ struct DisplayVoter {
     deposit_entries: Vec<DisplayDepositEntry>,
 }
 
+#[derive(Serialize)]
+struct DisplayTranche {
+    unlock_ts: i64,
+    amount: u64,
+}
+
+#[derive(Serialize)]
+struct DisplayVestingSchedule {
+    voter: String,
+    deposit_entry_index: u8,
+    tranches: Vec<DisplayTranche>,
+}
+
 /// Decode a Voter account and print its JSON to stdout
 fn decode_voter(data: &[u8]) -> Result<()> {
-    let mut data = data;
-    let voter: Voter = anchor_lang::AccountDeserialize::try_deserialize(&mut data)?;
+    let mut voter_data = data;
+    let voter: Voter = anchor_lang::AccountDeserialize::try_deserialize(&mut voter_data)?;
+    // Voters grown with grow_voter have further deposit entries after the Voter struct
+    let extra_deposits: Vec<DepositEntry> = data[8 + std::mem::size_of::<Voter>()..]
+        .chunks_exact(std::mem::size_of::<DepositEntry>())
+        .map(bytemuck::pod_read_unaligned)
+        .collect();
     let now_ts = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)?
         .as_secs() as i64;
@@ -45,17 +65,22 @@ fn decode_voter(data: &[u8]) -> Result<()> {
         deposit_entries: voter
             .deposits
             .iter()
+            .chain(extra_deposits.iter())
             .filter(|d| d.is_used)
-            .map(|d| DisplayDepositEntry {
-                allow_clawback: d.allow_clawback,
-                mint_index: d.voting_mint_config_idx,
-                unlocked_now: d.amount_unlocked(now_ts),
-                locked_now: d.amount_locked(now_ts),
-                locked_1y: d.amount_locked(now_ts + year),
-                locked_2y: d.amount_locked(now_ts + 2 * year),
-                locked_3y: d.amount_locked(now_ts + 3 * year),
-                locked_4y: d.amount_locked(now_ts + 4 * year),
-                locked_5y: d.amount_locked(now_ts + 5 * year),
+            .map(|d| {
+                let known = d.lockup.kind != LockupKind::Tranches;
+                let locked = |ts| known.then(|| d.amount_locked(ts, None));
+                DisplayDepositEntry {
+                    allow_clawback: d.allow_clawback,
+                    mint_index: d.voting_mint_config_idx,
+                    unlocked_now: known.then(|| d.amount_unlocked(now_ts, None)),
+                    locked_now: locked(now_ts),
+                    locked_1y: locked(now_ts + year),
+                    locked_2y: locked(now_ts + 2 * year),
+                    locked_3y: locked(now_ts + 3 * year),
+                    locked_4y: locked(now_ts + 4 * year),
+                    locked_5y: locked(now_ts + 5 * year),
+                }
             })
             .collect(),
     };
@@ -63,10 +88,36 @@ fn decode_voter(data: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Decode a VestingSchedule account and print its JSON to stdout
+fn decode_vesting_schedule(data: &[u8]) -> Result<()> {
+    let mut schedule_data = data;
+    let schedule: VestingSchedule =
+        anchor_lang::AccountDeserialize::try_deserialize(&mut schedule_data)?;
+    let ser = DisplayVestingSchedule {
+        voter: schedule.voter.to_string(),
+        deposit_entry_index: schedule.deposit_entry_index,
+        tranches: schedule
+            .tranches()
+            .iter()
+            .map(|t| DisplayTranche {
+                unlock_ts: t.unlock_ts,
+                amount: t.amount,
+            })
+            .collect(),
+    };
+    println!("{}", serde_json::to_string(&ser)?);
+    Ok(())
+}
+
+type AccountDecoder = fn(&[u8]) -> Result<()>;
+
 // Read a sequence of base64 encoded accounts from stdin
 // and write their decoded versions back out as JSON.
 pub fn decode_account() -> Result<()> {
-    let account_types = HashMap::from([(Voter::discriminator(), &decode_voter)]);
+    let account_types = HashMap::from([
+        (Voter::discriminator(), decode_voter as AccountDecoder),
+        (VestingSchedule::discriminator(), decode_vesting_schedule),
+    ]);
 
     for line in io::stdin().lock().lines() {
         let data = base64::decode(line?)?;
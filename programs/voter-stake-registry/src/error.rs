@@ -122,4 +122,127 @@ pub enum VsrError {
     // 6039 / 0x1797
     #[msg("")]
     MintConfigNotUsed,
+    // 6040 / 0x1798
+    #[msg("")]
+    InvalidTokenProgram,
+    // 6041 / 0x1799
+    #[msg("")]
+    TooManyVotingMints,
+    // 6042 / 0x179a
+    #[msg("")]
+    TooManyDepositEntries,
+    // 6043 / 0x179b
+    #[msg("")]
+    InvalidDelegate,
+    // 6044 / 0x179c
+    #[msg("")]
+    MissingDelegateAccount,
+    // 6045 / 0x179d
+    #[msg("")]
+    MissingVestingSchedule,
+    // 6046 / 0x179e
+    #[msg("")]
+    InvalidVestingSchedule,
+    // 6047 / 0x179f
+    #[msg("")]
+    EarlyUnlockDisabled,
+    // 6048 / 0x17a0
+    #[msg("")]
+    InvalidEarlyUnlockPenalty,
+    // 6049 / 0x17a1
+    #[msg("")]
+    InvalidEarlyUnlockTreasury,
+    // 6050 / 0x17a2
+    #[msg("")]
+    MissingVoterReward,
+    // 6051 / 0x17a3
+    #[msg("")]
+    InvalidRewardDuration,
+    // 6052 / 0x17a4
+    #[msg("")]
+    VoterHasRewardPositions,
+    // 6053 / 0x17a5
+    #[msg("")]
+    UnclaimedRewards,
+    // 6054 / 0x17a6
+    #[msg("")]
+    MissingWeightCheckpoints,
+    // 6055 / 0x17a7
+    #[msg("")]
+    WeightCheckpointNotFound,
+    // 6056 / 0x17a8
+    #[msg("")]
+    VoterHasWeightCheckpoints,
+    // 6057 / 0x17a9
+    #[msg("")]
+    RegistrarPaused,
+    // 6058 / 0x17aa
+    #[msg("")]
+    DepositBelowMinimum,
+    // 6059 / 0x17ab
+    #[msg("")]
+    MintDepositLimitExceeded,
+    // 6060 / 0x17ac
+    #[msg("")]
+    VoterDepositLimitExceeded,
+    // 6061 / 0x17ad
+    #[msg("")]
+    LockupKindNotAllowed,
+    // 6062 / 0x17ae
+    #[msg("")]
+    LockupDurationOutOfBounds,
+    // 6063 / 0x17af
+    #[msg("")]
+    VotingMintRetired,
+    // 6064 / 0x17b0
+    #[msg("")]
+    VotingMintNotRetired,
+    // 6065 / 0x17b1
+    #[msg("")]
+    VotingMintHasDepositEntries,
+    // 6066 / 0x17b2
+    #[msg("")]
+    IncompleteRegistrarStats,
+    // 6067 / 0x17b3
+    #[msg("")]
+    MissingVotingMintRate,
+    // 6068 / 0x17b4
+    #[msg("")]
+    VotingMintRateTooHigh,
+    // 6069 / 0x17b5
+    #[msg("")]
+    MissingPredecessorVoterWeightRecord,
+    // 6070 / 0x17b6
+    #[msg("")]
+    InvalidPredecessorVoterWeightRecord,
+    // 6071 / 0x17b7
+    #[msg("")]
+    VoterHasVestingSchedules,
+    // 6072 / 0x17b8
+    #[msg("")]
+    IncompleteVaultTransfer,
+    // 6073 / 0x17b9
+    #[msg("")]
+    InsufficientDepositedTokens,
+    // 6074 / 0x17ba
+    #[msg("")]
+    SameDepositEntry,
+    // 6075 / 0x17bb
+    #[msg("")]
+    MustChangeWholeDepositEntry,
+    // 6076 / 0x17bc
+    #[msg("")]
+    MissingRegistrarStats,
+    // 6077 / 0x17bd
+    #[msg("")]
+    HistoricalVoterWeightEnabled,
+    // 6078 / 0x17be
+    #[msg("")]
+    HistoricalVoterWeightDisabled,
+    // 6079 / 0x17bf
+    #[msg("")]
+    VoterHasDelegators,
+    // 6080 / 0x17c0
+    #[msg("")]
+    VoterHasDelegate,
 }
@@ -41,3 +41,26 @@ pub struct DepositEntryInfo {
     /// Information about locking, if any
     pub locking: Option<LockingInfo>,
 }
+
+#[event]
+#[derive(Debug)]
+pub struct VotingMintStatsInfo {
+    pub voting_mint_config_index: u8,
+    pub mint: Pubkey,
+    /// Tokens deposited in all deposit entries of the mint
+    pub deposited_native: u64,
+    /// Sum of the initially locked amounts of all deposit entries of the mint
+    pub initially_locked_native: u64,
+    /// Number of deposit entries of the mint in use
+    pub deposit_entries: u64,
+}
+
+#[event]
+#[derive(Debug)]
+pub struct RegistrarPausedChanged {
+    pub registrar: Pubkey,
+    /// True if the registrar is now paused
+    pub paused: bool,
+    /// The realm authority or pause guardian that made the change
+    pub authority: Pubkey,
+}
@@ -0,0 +1,347 @@
+use crate::error::*;
+use crate::instructions::init_vesting_schedule;
+use crate::state::*;
+use crate::token_interface::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as tx_instructions;
+use anchor_spl::associated_token::AssociatedToken;
+use std::convert::TryFrom;
+use std::mem::size_of;
+
+// Remaining accounts must be, for each vault of the voter, the accounts
+// [deposit_mint, vault, new_vault, token_program] where new_vault is the
+// associated token account of new_voter and token_program the one owning
+// deposit_mint. The vaults must be writable, and so must the mints with
+// transfer fees, which get the fees withheld in the old vaults.
+//
+// They must be followed by the accounts [vesting_schedule,
+// new_vesting_schedule] for each used Tranches deposit entry, in the order of
+// the entries. new_vesting_schedule is the address for new_voter and the same
+// deposit entry index. Both must be writable.
+#[derive(Accounts)]
+pub struct AcceptVoterAuthority<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority,
+        constraint = voter.load()?.pending_authority == new_voter_authority.key() @ VsrError::InvalidAuthority,
+        close = payer,
+    )]
+    pub voter: AccountLoader<'info, Voter>,
+
+    /// CHECK: The current authority of voter, it agreed with propose_voter_authority
+    pub voter_authority: UncheckedAccount<'info>,
+
+    /// The token_owner_record for the voter_authority. This is needed
+    /// to forbid the transfer while the voter is engaged with a vote or
+    /// has an open proposal.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is voter_authority
+    pub token_owner_record: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        close = payer,
+    )]
+    pub voter_weight_record: Box<Account<'info, VoterWeightRecord>>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), new_voter_authority.key().as_ref()],
+        bump,
+        payer = payer,
+        space = voter.as_ref().data_len(),
+    )]
+    pub new_voter: AccountLoader<'info, Voter>,
+    pub new_voter_authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), new_voter_authority.key().as_ref()],
+        bump,
+        payer = payer,
+        space = size_of::<VoterWeightRecord>(),
+    )]
+    pub new_voter_weight_record: Box<Account<'info, VoterWeightRecord>>,
+
+    /// Pays for the new accounts and receives the rent of the closed ones
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Address constraint is set
+    #[account(address = tx_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Completes a transfer of the voter to new_voter_authority, see
+/// propose_voter_authority.
+///
+/// The deposit entries, with their lockups and clawback settings, and the
+/// delegate move to a new voter and voter weight record for the new
+/// authority. The tokens of all vaults move to the new voter's vaults and
+/// the old accounts are closed. So are the vesting schedules of Tranches
+/// deposits, whose tranches move to new schedules of the new voter.
+///
+/// With spl-token-2022 mints that charge transfer fees, the new vault gets
+/// less than the old one held. The deposit entries of the mint shrink by the
+/// fee, each in proportion to its deposited amount.
+///
+/// Voters with reward positions, weight checkpoints or delegators can't be
+/// transferred, and neither can voters engaged in a vote. Delegators must
+/// revoke their delegation with set_delegate first: their withdraws and
+/// delegate changes need the voter weight record of the old authority.
+pub fn accept_voter_authority<'key, 'accounts, 'remaining, 'info>(
+    ctx: Context<'key, 'accounts, 'remaining, 'info, AcceptVoterAuthority<'info>>,
+) -> Result<()> {
+    // Forbid transferring voters from CPI, like creating them.
+    {
+        let ixns = ctx.accounts.instructions.to_account_info();
+        let current_index = tx_instructions::load_current_index_checked(&ixns)? as usize;
+        let current_ixn = tx_instructions::load_instruction_at_checked(current_index, &ixns)?;
+        require_keys_eq!(
+            current_ixn.program_id,
+            *ctx.program_id,
+            VsrError::ForbiddenCpi
+        );
+    }
+
+    let registrar = &ctx.accounts.registrar.load_full()?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+
+    {
+        let mut voter = ctx.accounts.voter.load_full_mut()?;
+        require_eq!(voter.reward_positions, 0, VsrError::VoterHasRewardPositions);
+        require!(
+            !voter.has_weight_checkpoints,
+            VsrError::VoterHasWeightCheckpoints
+        );
+        require_eq!(voter.delegators, 0, VsrError::VoterHasDelegators);
+
+        // Governance may forbid the transfer, like withdraws, for example
+        // when engaged in a vote.
+        let token_owner_record = voter.load_token_owner_record(
+            &ctx.accounts.token_owner_record.to_account_info(),
+            registrar,
+        )?;
+        token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+        // Move the tokens of each vault.
+        let voter_seeds = voter_seeds!(voter);
+        let voter_key = ctx.accounts.voter.key();
+        let new_voter_key = ctx.accounts.new_voter.key();
+        let tranches_entries: Vec<u8> = voter
+            .deposits()
+            .enumerate()
+            .filter(|(_, d)| d.is_used && d.lockup.kind == LockupKind::Tranches)
+            .map(|(index, _)| index as u8)
+            .collect();
+        require_gte!(
+            ctx.remaining_accounts.len(),
+            2 * tranches_entries.len(),
+            VsrError::MissingVestingSchedule
+        );
+        let (vault_accounts, schedule_accounts) = ctx
+            .remaining_accounts
+            .split_at(ctx.remaining_accounts.len() - 2 * tranches_entries.len());
+        let mut moved_mints = Vec::new();
+        for accounts in vault_accounts.chunks(4) {
+            require_eq!(accounts.len(), 4, VsrError::IncompleteVaultTransfer);
+            let (mint, vault, new_vault, token_program) =
+                (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+            require!(
+                is_token_program(&token_program.key()),
+                VsrError::InvalidTokenProgram
+            );
+            require_keys_eq!(
+                *mint.owner,
+                token_program.key(),
+                VsrError::InvalidTokenProgram
+            );
+            require_keys_eq!(
+                vault.key(),
+                vault_address(&voter_key, &mint.key(), &token_program.key()),
+                VsrError::IncompleteVaultTransfer
+            );
+            require_keys_eq!(
+                new_vault.key(),
+                vault_address(&new_voter_key, &mint.key(), &token_program.key()),
+                VsrError::IncompleteVaultTransfer
+            );
+
+            create_vault_if_needed(
+                &ctx.accounts.payer.to_account_info(),
+                new_vault,
+                &ctx.accounts.new_voter.to_account_info(),
+                mint,
+                &ctx.accounts.system_program.to_account_info(),
+                token_program,
+                &ctx.accounts.associated_token_program.to_account_info(),
+            )?;
+
+            let amount = unpack_token_account(vault)?.amount;
+            let new_vault_before = unpack_token_account(new_vault)?.amount;
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    TransferChecked {
+                        from: vault.clone(),
+                        mint: mint.clone(),
+                        to: new_vault.clone(),
+                        authority: ctx.accounts.voter.to_account_info(),
+                    },
+                    &[voter_seeds],
+                ),
+                amount,
+            )?;
+            harvest_withheld_fees(token_program, mint, vault)?;
+            close_account(CpiContext::new_with_signer(
+                token_program.clone(),
+                CloseAccount {
+                    account: vault.clone(),
+                    destination: ctx.accounts.payer.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+                &[voter_seeds],
+            ))?;
+
+            let received = unpack_token_account(new_vault)?
+                .amount
+                .checked_sub(new_vault_before)
+                .unwrap();
+            if let Ok(mint_idx) = registrar.voting_mint_config_index(mint.key()) {
+                moved_mints.push((mint_idx, received));
+            }
+        }
+
+        // Every vault backing deposits must have moved.
+        for d in voter.deposits().filter(|d| d.is_used) {
+            require!(
+                d.amount_deposited_native == 0
+                    || moved_mints
+                        .iter()
+                        .any(|(mint_idx, _)| *mint_idx == d.voting_mint_config_idx as usize),
+                VsrError::IncompleteVaultTransfer
+            );
+        }
+
+        // Move the tranches of each vesting schedule to a new one for the
+        // same deposit entry of new_voter.
+        let mut new_schedules = Vec::new();
+        for (index, accounts) in tranches_entries.iter().zip(schedule_accounts.chunks(2)) {
+            let schedule = AccountLoader::<VestingSchedule>::try_from(&accounts[0])?;
+            {
+                let schedule = schedule.load()?;
+                require_keys_eq!(schedule.voter, voter_key, VsrError::InvalidVestingSchedule);
+                require_eq!(
+                    schedule.deposit_entry_index,
+                    *index,
+                    VsrError::InvalidVestingSchedule
+                );
+                let mut new_schedule = init_vesting_schedule(
+                    &accounts[1],
+                    &new_voter_key,
+                    *index,
+                    ctx.accounts.payer.as_ref(),
+                    ctx.accounts.system_program.as_ref(),
+                )?;
+                new_schedule.tranches_len = schedule.tranches_len;
+                new_schedule.tranches = schedule.tranches;
+                new_schedules.push((*index, new_schedule));
+            }
+            schedule.close(ctx.accounts.payer.to_account_info())?;
+        }
+
+        // Take the transfer fees out of the deposit entries of their mint.
+        let curr_ts = registrar.clock_unix_timestamp();
+        for (mint_idx, received) in moved_mints {
+            let mint_stats_before = voter.mint_stats(mint_idx);
+            let deposited = mint_stats_before.deposited_native;
+            let fee = deposited.saturating_sub(received);
+            if fee == 0 {
+                continue;
+            }
+            let mut fee_left = fee;
+            for index in 0..voter.deposits_len() {
+                let d = voter.deposit_mut(index as u8)?;
+                if !d.is_used || d.voting_mint_config_idx as usize != mint_idx {
+                    continue;
+                }
+                // Rounding up makes sure the shares cover the whole fee.
+                let share = u64::try_from(
+                    (fee as u128)
+                        .checked_mul(d.amount_deposited_native as u128)
+                        .unwrap()
+                        .checked_add(deposited as u128 - 1)
+                        .unwrap()
+                        / deposited as u128,
+                )
+                .unwrap()
+                .min(fee_left);
+                let schedule = new_schedules
+                    .iter_mut()
+                    .find(|(schedule_index, _)| *schedule_index as usize == index)
+                    .map(|(_, schedule)| &mut **schedule);
+                d.remove_proportionally(share, curr_ts, schedule)?;
+                fee_left -= share;
+            }
+            require_eq!(fee_left, 0, VsrError::InternalProgramError);
+
+            update_registrar_stats(
+                registrar,
+                &ctx.accounts.registrar_stats,
+                mint_idx,
+                &mint_stats_before,
+                &voter.mint_stats(mint_idx),
+            )?;
+        }
+    }
+
+    // Copy the voter, including the deposit entries added with grow_voter.
+    {
+        let data = ctx.accounts.voter.as_ref().try_borrow_data()?;
+        let mut new_data = ctx.accounts.new_voter.as_ref().try_borrow_mut_data()?;
+        new_data[8..].copy_from_slice(&data[8..]);
+    }
+
+    let new_voter_authority = ctx.accounts.new_voter_authority.key();
+    let new_voter = &mut ctx.accounts.new_voter.load_init()?;
+    new_voter.voter_authority = new_voter_authority;
+    new_voter.voter_bump = *ctx.bumps.get("new_voter").unwrap();
+    new_voter.voter_weight_record_bump = *ctx.bumps.get("new_voter_weight_record").unwrap();
+    new_voter.pending_authority = Pubkey::default();
+
+    let new_record = &mut ctx.accounts.new_voter_weight_record;
+    new_record.account_discriminator =
+        spl_governance_addin_api::voter_weight::VoterWeightRecord::ACCOUNT_DISCRIMINATOR;
+    new_record.realm = registrar.realm;
+    new_record.governing_token_mint = registrar.realm_governing_token_mint;
+    new_record.governing_token_owner = new_voter_authority;
+
+    msg!(
+        "Voter {} transferred to {}",
+        ctx.accounts.voter_authority.key(),
+        new_voter_authority
+    );
+
+    Ok(())
+}
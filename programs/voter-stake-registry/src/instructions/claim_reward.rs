@@ -0,0 +1,101 @@
+use crate::error::*;
+use crate::state::*;
+use crate::token_interface::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClaimReward<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    #[account(mut, has_one = registrar, has_one = reward_mint)]
+    pub distribution: AccountLoader<'info, RewardDistribution>,
+
+    #[account(mut, has_one = voter, has_one = distribution)]
+    pub voter_reward: AccountLoader<'info, VoterReward>,
+
+    /// CHECK: Address constraint is set, the associated token account of distribution
+    #[account(
+        mut,
+        address = vault_address(&distribution.key(), &reward_mint.key(), &token_program.key()),
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the token program, must be a token account for reward_mint
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub reward_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
+}
+
+impl<'info> ClaimReward<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.destination.to_account_info(),
+            authority: self.distribution.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+/// Transfers the rewards a voter accrued in a distribution to `destination`.
+///
+/// Also updates the position to the voter's current locked vote weight. The
+/// rewards since the last update are paid at no more than that weight, see
+/// RewardDistribution::settle_position().
+///
+/// The VestingSchedule accounts of the voter's deposits with LockupKind::Tranches
+/// must be passed as remaining accounts.
+pub fn claim_reward(ctx: Context<ClaimReward>) -> Result<()> {
+    let amount = {
+        let registrar = &ctx
+            .accounts
+            .registrar
+            .load_full_with_rates(ctx.remaining_accounts)?;
+        require!(!registrar.paused, VsrError::RegistrarPaused);
+        let voter = &ctx.accounts.voter.load_full()?;
+        let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
+        let weight = reward_weight(voter, registrar, &schedules)?;
+
+        let distribution = &mut ctx.accounts.distribution.load_mut()?;
+        let position = &mut ctx.accounts.voter_reward.load_mut()?;
+        distribution.settle_position(position, registrar.clock_unix_timestamp(), weight);
+        let amount = position.rewards_owed;
+        position.rewards_owed = 0;
+        amount
+    };
+
+    {
+        // Transfer the rewards.
+        let distribution = &ctx.accounts.distribution.load()?;
+        let distribution_seeds = reward_distribution_seeds!(distribution);
+        transfer_checked(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[distribution_seeds]),
+            amount,
+        )?;
+    }
+
+    msg!("Claimed reward amount {}", amount);
+
+    Ok(())
+}
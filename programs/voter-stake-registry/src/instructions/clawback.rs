@@ -1,8 +1,7 @@
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token;
-use anchor_spl::token::{Token, TokenAccount};
 
 #[derive(Accounts)]
 pub struct Clawback<'info> {
@@ -10,6 +9,11 @@ pub struct Clawback<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
     pub realm_authority: Signer<'info>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     // checking the PDA address it just an extra precaution,
     // the other constraints must be exhaustive
     #[account(
@@ -19,24 +23,37 @@ pub struct Clawback<'info> {
         has_one = registrar)]
     pub voter: AccountLoader<'info, Voter>,
 
+    /// CHECK: Address constraint is set, the associated token account of voter
     #[account(
         mut,
-        associated_token::authority = voter,
-        associated_token::mint = destination.mint,
+        address = vault_address(&voter.key(), &deposit_mint.key(), &token_program.key()),
     )]
-    pub vault: Box<Account<'info, TokenAccount>>,
+    pub vault: UncheckedAccount<'info>,
 
+    /// CHECK: The token program checks that the destination has the right mint
     #[account(mut)]
-    pub destination: Box<Account<'info, TokenAccount>>,
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub deposit_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// The vesting schedule of the deposit entry, required if it has
+    /// LockupKind::Tranches.
+    #[account(mut, has_one = voter)]
+    pub vesting_schedule: Option<AccountLoader<'info, VestingSchedule>>,
 }
 
 impl<'info> Clawback<'info> {
-    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
         let program = self.token_program.to_account_info();
-        let accounts = token::Transfer {
+        let accounts = TransferChecked {
             from: self.vault.to_account_info(),
+            mint: self.deposit_mint.to_account_info(),
             to: self.destination.to_account_info(),
             authority: self.voter.to_account_info(),
         };
@@ -47,55 +64,147 @@ impl<'info> Clawback<'info> {
 /// Claws back locked tokens from a deposit entry.
 ///
 /// `deposit_entry_index`: The index of the deposit entry to claw back tokens on.
+/// `amount`: The amount of locked tokens to claw back, or None for all of them.
 ///
 /// The deposit entry must have been created with `allow_clawback=true`.
 ///
-/// The instruction will always reclaim all locked tokens, while leaving tokens
-/// that have already vested in place.
-pub fn clawback(ctx: Context<Clawback>, deposit_entry_index: u8) -> Result<()> {
-    let locked_amount = {
+/// Tokens that have already vested are always left in place. When all locked
+/// tokens are reclaimed, the lockup ends and clawback is disabled on the entry.
+/// When only part of them is reclaimed, the remaining locked tokens keep
+/// vesting on the original schedule, with each period vesting proportionally
+/// less, and the entry stays clawback-enabled.
+///
+/// For deposits with LockupKind::Tranches, the tokens are taken from the
+/// tranches that unlock last.
+///
+/// The voter's VoterReward accounts and their RewardDistributions can be
+/// passed as remaining accounts to stop rewards accruing on the clawed back
+/// tokens. Unlike other instructions, clawback does not require all of them,
+/// so grantees can't block it. Others can be updated with update_voter_rewards.
+///
+/// The voter's VoterWeightCheckpoints account must be passed as a remaining
+/// account if it has one, see record_weight_checkpoint().
+pub fn clawback(
+    ctx: Context<Clawback>,
+    deposit_entry_index: u8,
+    amount: Option<u64>,
+) -> Result<()> {
+    let clawback_amount = {
         // Load the accounts.
-        let registrar = &ctx.accounts.registrar.load()?;
-        let voter = &mut ctx.accounts.voter.load_mut()?;
+        let registrar = &ctx.accounts.registrar.load_full()?;
+        let voter = &mut ctx.accounts.voter.load_full_mut()?;
 
         // Note: don't assert if token_owner_record is engaged in active proposals
         // since this way a grantee could block clawback
 
         // Get the deposit being clawed back from.
+        let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+        let mint_stats_before = voter.mint_stats(mint_idx);
         let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
+        require_eq!(
+            mint_idx,
+            deposit_entry.voting_mint_config_idx as usize,
+            VsrError::InvalidMint
+        );
         require!(
             deposit_entry.allow_clawback,
             VsrError::ClawbackNotAllowedOnDeposit
         );
 
+        // After resolving vesting, amount_initially_locked_native is exactly the
+        // locked amount and the lockup only covers the remaining periods.
+        // Tranches can't be resolved, their schedule is adjusted below instead.
         let curr_ts = registrar.clock_unix_timestamp();
-        let locked_amount = deposit_entry.amount_locked(curr_ts);
+        let mut schedule = None;
+        let locked_amount = if deposit_entry.lockup.kind == LockupKind::Tranches {
+            let vesting_schedule = ctx
+                .accounts
+                .vesting_schedule
+                .as_ref()
+                .ok_or_else(|| error!(VsrError::MissingVestingSchedule))?
+                .load_mut()?;
+            require_eq!(
+                vesting_schedule.deposit_entry_index,
+                deposit_entry_index,
+                VsrError::InvalidVestingSchedule
+            );
+            let locked_amount = deposit_entry.amount_locked(curr_ts, Some(&vesting_schedule));
+            schedule = Some(vesting_schedule);
+            locked_amount
+        } else {
+            deposit_entry.resolve_vesting(curr_ts)?;
+            deposit_entry.amount_initially_locked_native
+        };
+        let clawback_amount = amount.unwrap_or(locked_amount);
+        require_gte!(
+            locked_amount,
+            clawback_amount,
+            VsrError::InsufficientLockedTokens
+        );
+
+        // The locked tokens are in the tranches that unlock last.
+        if let Some(schedule) = schedule.as_mut() {
+            schedule.remove_from_end(clawback_amount)?;
+        }
 
         // Update deposit book keeping.
         require_gte!(
             deposit_entry.amount_deposited_native,
-            locked_amount,
+            clawback_amount,
             VsrError::InternalProgramError
         );
-        deposit_entry.amount_deposited_native -= locked_amount;
+        deposit_entry.amount_deposited_native -= clawback_amount;
+        deposit_entry.amount_initially_locked_native -= clawback_amount;
 
         // Now that all locked funds are withdrawn, end the lockup
-        let curr_ts = registrar.clock_unix_timestamp();
-        deposit_entry.amount_initially_locked_native = 0;
-        deposit_entry.lockup = Lockup::new_from_periods(LockupKind::None, curr_ts, curr_ts, 0)?;
-        deposit_entry.allow_clawback = false;
-        locked_amount
+        if clawback_amount == locked_amount {
+            deposit_entry.lockup = Lockup::new_from_periods(LockupKind::None, curr_ts, curr_ts, 0)?;
+            deposit_entry.allow_clawback = false;
+        }
+
+        // Update the registrar's deposit totals.
+        update_registrar_stats(
+            registrar,
+            &ctx.accounts.registrar_stats,
+            mint_idx,
+            &mint_stats_before,
+            &voter.mint_stats(mint_idx),
+        )?;
+
+        clawback_amount
     };
 
     {
         // Transfer the tokens to withdraw.
         let voter = &mut ctx.accounts.voter.load()?;
         let voter_seeds = voter_seeds!(voter);
-        token::transfer(
+        transfer_checked(
             ctx.accounts.transfer_ctx().with_signer(&[voter_seeds]),
-            locked_amount,
+            clawback_amount,
         )?;
     }
 
+    // Rewards accrue at the reduced locked vote weight from now on.
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = &ctx.accounts.voter.load_full()?;
+    update_reward_positions(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+        false,
+    )?;
+
+    // Snapshots of the voter's weight must not include the clawed back tokens.
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
     Ok(())
 }
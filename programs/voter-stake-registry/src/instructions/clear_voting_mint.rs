@@ -0,0 +1,42 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClearVotingMint<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: AccountLoader<'info, RegistrarStats>,
+}
+
+/// Clears the slot of a retired voting mint, so it can be configured again.
+///
+/// No deposit entry may use the voting mint anymore. That is known from the
+/// deposit entry count in RegistrarStats, so it only works for registrars
+/// that have one, see create_registrar_stats.
+pub fn clear_voting_mint(ctx: Context<ClearVotingMint>, idx: u16) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_full_mut()?;
+    let registrar_stats = &mut ctx.accounts.registrar_stats.load_mut()?;
+    let idx = idx as usize;
+    require_gt!(
+        registrar.voting_mints_len(),
+        idx,
+        VsrError::OutOfBoundsVotingMintConfigIndex
+    );
+    require!(
+        registrar.voting_mint(idx).retired,
+        VsrError::VotingMintNotRetired
+    );
+    require_eq!(
+        registrar_stats.voting_mints[idx].deposit_entries,
+        0,
+        VsrError::VotingMintHasDepositEntries
+    );
+
+    *registrar.voting_mint_mut(idx) = VotingMintConfig::default();
+    registrar_stats.voting_mints[idx] = VotingMintStats::default();
+    Ok(())
+}
@@ -4,15 +4,31 @@ use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct CloseDepositEntry<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     // checking the PDA address it just an extra precaution,
     // the other constraints must be exhaustive
     #[account(
         mut,
-        seeds = [voter.load()?.registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
         bump = voter.load()?.voter_bump,
-        has_one = voter_authority)]
+        has_one = voter_authority,
+        has_one = registrar)]
     pub voter: AccountLoader<'info, Voter>,
+
+    #[account(mut)]
     pub voter_authority: Signer<'info>,
+
+    /// The vesting schedule of the deposit entry, required if it has
+    /// LockupKind::Tranches. It is closed and its rent goes to the voter
+    /// authority.
+    #[account(mut, has_one = voter, close = voter_authority)]
+    pub vesting_schedule: Option<AccountLoader<'info, VestingSchedule>>,
 }
 
 /// Close an empty deposit entry, allowing it to be reused in the future.
@@ -21,8 +37,17 @@ pub struct CloseDepositEntry<'info> {
 ///
 /// If the deposit entry has `allow_clawback` set, it can only be closed once
 /// the lockup period has expired.
+///
+/// The vesting schedule of a deposit entry with LockupKind::Tranches is
+/// closed with it.
 pub fn close_deposit_entry(ctx: Context<CloseDepositEntry>, deposit_entry_index: u8) -> Result<()> {
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx.accounts.registrar.load()?;
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let mint_idx = voter
+        .active_deposit_mut(deposit_entry_index)?
+        .voting_mint_config_idx as usize;
+    let mint_stats_before = voter.mint_stats(mint_idx);
+
     let d = voter.active_deposit_mut(deposit_entry_index)?;
     require_eq!(d.amount_deposited_native, 0, VsrError::VotingTokenNonZero);
 
@@ -36,8 +61,29 @@ pub fn close_deposit_entry(ctx: Context<CloseDepositEntry>, deposit_entry_index:
         );
     }
 
+    match &ctx.accounts.vesting_schedule {
+        Some(vesting_schedule) => require_eq!(
+            vesting_schedule.load()?.deposit_entry_index,
+            deposit_entry_index,
+            VsrError::InvalidVestingSchedule
+        ),
+        None => require!(
+            d.lockup.kind != LockupKind::Tranches,
+            VsrError::MissingVestingSchedule
+        ),
+    }
+
     *d = DepositEntry::default();
     d.is_used = false;
 
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
     Ok(())
 }
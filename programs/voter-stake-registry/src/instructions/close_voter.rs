@@ -2,13 +2,15 @@ use std::ops::DerefMut;
 
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::{self, CloseAccount};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, CloseAccount, Token, TokenAccount};
 use bytemuck::bytes_of_mut;
 
 // Remaining accounts must be all the token token accounts owned by voter, he wants to close,
 // they should be writable so that they can be closed and sol required for rent
-// can then be sent back to the sol_destination
+// can then be sent back to the sol_destination.
+// They must also include the vesting schedules of the voter's deposit entries with
+// LockupKind::Tranches, whose rent goes to the voter_authority.
 #[derive(Accounts)]
 pub struct CloseVoter<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
@@ -20,35 +22,99 @@ pub struct CloseVoter<'info> {
         seeds = [voter.load()?.registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
         bump = voter.load()?.voter_bump,
         has_one = voter_authority,
+        has_one = registrar,
         close = sol_destination
     )]
     pub voter: AccountLoader<'info, Voter>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
+    #[account(mut)]
     pub voter_authority: Signer<'info>,
 
     #[account(mut)]
     /// CHECK: Destination may be any address.
     pub sol_destination: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = token_interface::is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
 }
 
 /// Closes the voter account (Optionally, also token vaults, as part of remaining_accounts),
-/// allowing one to retrieve rent exemption SOL.
-/// Only accounts with no remaining deposits can be closed.
+/// allowing one to retrieve rent exemption SOL. The vesting schedules of the voter
+/// are closed too.
+/// Only accounts with no remaining deposits, no reward positions and no weight
+/// checkpoints can be closed. Neither can voters that delegated or have
+/// delegators, see set_delegate.
+///
+/// Vaults of spl-token-2022 mints with transfer fees can only be closed once
+/// the fees withheld in them were harvested to the mint.
 pub fn close_voter<'key, 'accounts, 'remaining, 'info>(
     ctx: Context<'key, 'accounts, 'remaining, 'info, CloseVoter<'info>>,
 ) -> Result<()> {
     {
-        let voter = ctx.accounts.voter.load()?;
-        let amount = voter.deposits.iter().fold(0u64, |sum, d| {
+        let voter = ctx.accounts.voter.load_full()?;
+        let amount = voter.deposits().fold(0u64, |sum, d| {
             sum.checked_add(d.amount_deposited_native).unwrap()
         });
         require_eq!(amount, 0, VsrError::VotingTokenNonZero);
+        require_eq!(voter.reward_positions, 0, VsrError::VoterHasRewardPositions);
+        require!(
+            !voter.has_weight_checkpoints,
+            VsrError::VoterHasWeightCheckpoints
+        );
+        require!(!voter.has_delegate(), VsrError::VoterHasDelegate);
+        require_eq!(voter.delegators, 0, VsrError::VoterHasDelegators);
 
+        // The voter's deposit entries no longer count towards the registrar's totals.
+        let mut mint_indexes: Vec<usize> = voter
+            .deposits()
+            .filter(|d| d.is_used)
+            .map(|d| d.voting_mint_config_idx as usize)
+            .collect();
+        mint_indexes.sort_unstable();
+        mint_indexes.dedup();
+        let registrar = &ctx.accounts.registrar.load()?;
+        for mint_idx in mint_indexes {
+            update_registrar_stats(
+                registrar,
+                &ctx.accounts.registrar_stats,
+                mint_idx,
+                &voter.mint_stats(mint_idx),
+                &VotingMintStats::default(),
+            )?;
+        }
+
+        let mut closed_schedules = Vec::new();
         let voter_seeds = voter_seeds!(voter);
         for account in &mut ctx.remaining_accounts.iter() {
-            let token = Account::<TokenAccount>::try_from(&account.clone()).unwrap();
+            if VestingSchedules::is_vesting_schedule(account) {
+                let vesting_schedule = AccountLoader::<VestingSchedule>::try_from(account)?;
+                let deposit_entry_index = {
+                    let schedule = vesting_schedule.load()?;
+                    require_keys_eq!(
+                        schedule.voter,
+                        ctx.accounts.voter.key(),
+                        VsrError::InvalidVestingSchedule
+                    );
+                    schedule.deposit_entry_index
+                };
+                vesting_schedule.close(ctx.accounts.voter_authority.to_account_info())?;
+                closed_schedules.push(deposit_entry_index);
+                continue;
+            }
+
+            // Vaults of one token program can only be closed by that program.
+            require_keys_eq!(
+                *account.owner,
+                ctx.accounts.token_program.key(),
+                VsrError::InvalidTokenProgram
+            );
+            let token = token_interface::unpack_token_account(account)?;
             require_keys_eq!(
                 token.owner,
                 ctx.accounts.voter.key(),
@@ -62,7 +128,7 @@ pub fn close_voter<'key, 'accounts, 'remaining, 'info>(
                 authority: ctx.accounts.voter.to_account_info(),
             };
             let cpi_program = ctx.accounts.token_program.to_account_info();
-            token::close_account(CpiContext::new_with_signer(
+            token_interface::close_account(CpiContext::new_with_signer(
                 cpi_program,
                 cpi_accounts,
                 &[voter_seeds],
@@ -70,6 +136,15 @@ pub fn close_voter<'key, 'accounts, 'remaining, 'info>(
 
             account.exit(ctx.program_id)?;
         }
+
+        for (index, d) in voter.deposits().enumerate() {
+            if d.is_used && d.lockup.kind == LockupKind::Tranches {
+                require!(
+                    closed_schedules.contains(&(index as u8)),
+                    VsrError::MissingVestingSchedule
+                );
+            }
+        }
     }
 
     // zero out voter account to prevent reinit attacks
@@ -0,0 +1,52 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseVoterReward<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    #[account(mut, has_one = registrar)]
+    pub distribution: AccountLoader<'info, RewardDistribution>,
+
+    #[account(
+        mut,
+        has_one = voter,
+        has_one = distribution,
+        close = sol_destination,
+    )]
+    pub voter_reward: AccountLoader<'info, VoterReward>,
+
+    #[account(mut)]
+    /// CHECK: Destination may be any address.
+    pub sol_destination: UncheckedAccount<'info>,
+}
+
+/// Ends a voter's participation in a reward distribution, reclaiming rent.
+///
+/// All accrued rewards must have been claimed, see claim_reward.
+pub fn close_voter_reward(ctx: Context<CloseVoterReward>) -> Result<()> {
+    let registrar = &ctx.accounts.registrar.load()?;
+    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let distribution = &mut ctx.accounts.distribution.load_mut()?;
+    let position = &mut ctx.accounts.voter_reward.load_mut()?;
+
+    // Remove the position's weight from the distribution.
+    distribution.update_position(position, registrar.clock_unix_timestamp(), 0);
+    require_eq!(position.rewards_owed, 0, VsrError::UnclaimedRewards);
+
+    voter.reward_positions = voter.reward_positions.checked_sub(1).unwrap();
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseVoterWeightCheckpoints<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = voter,
+        close = sol_destination,
+    )]
+    pub checkpoints: AccountLoader<'info, VoterWeightCheckpoints>,
+
+    #[account(mut)]
+    /// CHECK: Destination may be any address.
+    pub sol_destination: UncheckedAccount<'info>,
+}
+
+/// Stops recording the history of a voter's vote weight, reclaiming rent.
+///
+/// The recorded history is lost: voter weight records for past times can't
+/// be written anymore, even if checkpoints are created again.
+pub fn close_voter_weight_checkpoints(ctx: Context<CloseVoterWeightCheckpoints>) -> Result<()> {
+    let voter = &mut ctx.accounts.voter.load_mut()?;
+    voter.has_weight_checkpoints = false;
+    Ok(())
+}
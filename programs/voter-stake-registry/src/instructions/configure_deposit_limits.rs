@@ -0,0 +1,54 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ConfigureDepositLimits<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Sets the deposit limits of a configured voting mint.
+///
+/// * `idx`: index of the voting mint
+/// * `min_deposit_native`: minimum number of native tokens a deposit, grant
+///   or internal transfer must add to a deposit entry
+/// * `max_deposited_native`: maximum number of native tokens deposited by
+///   all voters together
+/// * `max_voter_deposited_native`: maximum number of native tokens deposited
+///   by a single voter
+///
+/// Zero disables a limit. Limits are only checked when tokens are added, so
+/// lowering a maximum below the current deposits doesn't affect them.
+///
+/// The total for max_deposited_native comes from the registrar's
+/// RegistrarStats, so it can only be set for registrars that have one.
+pub fn configure_deposit_limits(
+    ctx: Context<ConfigureDepositLimits>,
+    idx: u16,
+    min_deposit_native: u64,
+    max_deposited_native: u64,
+    max_voter_deposited_native: u64,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_full_mut()?;
+    let idx = idx as usize;
+    require_gt!(
+        registrar.voting_mints_len(),
+        idx,
+        VsrError::OutOfBoundsVotingMintConfigIndex
+    );
+    if max_deposited_native > 0 {
+        require!(
+            registrar.has_registrar_stats,
+            VsrError::MissingRegistrarStats
+        );
+    }
+    let mint_config = registrar.voting_mint_mut(idx);
+    require!(mint_config.in_use(), VsrError::MintConfigNotUsed);
+
+    mint_config.min_deposit_native = min_deposit_native;
+    mint_config.max_deposited_native = max_deposited_native;
+    mint_config.max_voter_deposited_native = max_voter_deposited_native;
+    Ok(())
+}
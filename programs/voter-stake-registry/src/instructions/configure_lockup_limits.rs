@@ -0,0 +1,54 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ConfigureLockupLimits<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Sets which lockups deposits of a configured voting mint may have.
+///
+/// * `idx`: index of the voting mint
+/// * `allowed_lockup_kinds`: bitmask with bit `1 << kind` set for each
+///   allowed LockupKind, zero to allow all kinds
+/// * `min_lockup_secs`: minimum number of seconds left on a lockup; doesn't
+///   apply to LockupKind::None
+/// * `max_lockup_secs`: maximum number of seconds left on a lockup, zero for
+///   no limit
+///
+/// The limits are checked when a lockup is chosen: by create_deposit_entry,
+/// grant and reset_lockup, and for the target of internal_transfer_locked.
+/// Existing deposits are not affected.
+pub fn configure_lockup_limits(
+    ctx: Context<ConfigureLockupLimits>,
+    idx: u16,
+    allowed_lockup_kinds: u32,
+    min_lockup_secs: u64,
+    max_lockup_secs: u64,
+) -> Result<()> {
+    if max_lockup_secs > 0 {
+        require_gte!(
+            max_lockup_secs,
+            min_lockup_secs,
+            VsrError::LockupDurationOutOfBounds
+        );
+    }
+
+    let registrar = &mut ctx.accounts.registrar.load_full_mut()?;
+    let idx = idx as usize;
+    require_gt!(
+        registrar.voting_mints_len(),
+        idx,
+        VsrError::OutOfBoundsVotingMintConfigIndex
+    );
+    let mint_config = registrar.voting_mint_mut(idx);
+    require!(mint_config.in_use(), VsrError::MintConfigNotUsed);
+
+    mint_config.allowed_lockup_kinds = allowed_lockup_kinds;
+    mint_config.min_lockup_secs = min_lockup_secs;
+    mint_config.max_lockup_secs = max_lockup_secs;
+    Ok(())
+}
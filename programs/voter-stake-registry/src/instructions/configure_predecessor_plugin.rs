@@ -0,0 +1,30 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ConfigurePredecessorPlugin<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Chains the registrar after another voter weight plugin.
+///
+/// * `predecessor_program_id`: the plugin program, for example an NFT or
+///   civic pass plugin. Pass the default pubkey to remove it.
+/// * `predecessor_weight_mode`: whether the predecessor's weight is added
+///   to or multiplied with the registrar's own
+///
+/// update_voter_weight_record then requires the predecessor's
+/// VoterWeightRecord of the voter as a remaining account. The max vote
+/// weight is not affected.
+pub fn configure_predecessor_plugin(
+    ctx: Context<ConfigurePredecessorPlugin>,
+    predecessor_program_id: Pubkey,
+    predecessor_weight_mode: PredecessorWeightMode,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    registrar.predecessor_program_id = predecessor_program_id;
+    registrar.predecessor_weight_mode = predecessor_weight_mode;
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
 
 // Remaining accounts must be all the token mints that have registered
-// as voting mints, including the newly registered one.
+// as voting mints, including the newly registered one, and the
+// VotingMintRate accounts of priced voting mints.
 #[derive(Accounts)]
 pub struct ConfigureVotingMint<'info> {
     #[account(mut, has_one = realm_authority)]
@@ -12,7 +13,9 @@ pub struct ConfigureVotingMint<'info> {
     pub realm_authority: Signer<'info>,
 
     /// Tokens of this mint will produce vote weight
-    pub mint: Account<'info, Mint>,
+    ///
+    /// CHECK: May be an spl-token or spl-token-2022 mint, unpacked in the instruction
+    pub mint: UncheckedAccount<'info>,
     // This instruction expects that all voting mint addresses, including a
     // newly registered one, are passed in ctx.remainingAccounts.
 }
@@ -27,9 +30,17 @@ pub struct ConfigureVotingMint<'info> {
 /// * `max_extra_lockup_vote_weight_scaled_factor`: max extra weight for lockups, in 1/1e9 units
 /// * `lockup_saturation_secs`: lockup duration at which the full vote weight
 ///   bonus is given to locked up deposits
+/// * `early_unlock_penalty_scaled_factor`: max share of locked tokens forfeited
+///   by early_unlock, in 1/1e9 units, zero to disable early unlocks
 ///
 /// This instruction can be called several times for the same mint and index to
-/// change the voting mint configuration.
+/// change the voting mint configuration. The deposit and lockup limits are
+/// kept, they are changed with configure_deposit_limits and
+/// configure_lockup_limits. So is the pricing set up with
+/// configure_voting_mint_rate. Retired mints can't be changed.
+///
+/// Only the first four indexes are available initially, use grow_registrar to
+/// add more voting mint slots.
 ///
 /// The vote weight for `amount` of native tokens will be
 /// ```
@@ -40,7 +51,8 @@ pub struct ConfigureVotingMint<'info> {
 /// ```
 /// where lockup_duration_factor is a value between 0 and 1, depending on how long
 /// the amount is locked up. It is 1 when the lockup duration is greater or equal
-/// lockup_saturation_secs.
+/// lockup_saturation_secs. For priced voting mints, both factors are further
+/// scaled by the current rate, see configure_voting_mint_rate.
 ///
 /// Warning: Choose values that ensure that the vote weight will not overflow the
 /// u64 limit! There is a check based on the supply of all configured mints, but
@@ -69,6 +81,7 @@ pub struct ConfigureVotingMint<'info> {
 ///    * B with digit_shift=0, baseline_vote_weight_scaled_factor=1e9, max_extra_lockup_vote_weight_scaled_factor=1e9
 /// to not lose precision on B tokens.
 ///
+#[allow(clippy::too_many_arguments)]
 pub fn configure_voting_mint(
     ctx: Context<ConfigureVotingMint>,
     idx: u16,
@@ -77,47 +90,77 @@ pub fn configure_voting_mint(
     max_extra_lockup_vote_weight_scaled_factor: u64,
     lockup_saturation_secs: u64,
     grant_authority: Option<Pubkey>,
+    early_unlock_penalty_scaled_factor: u64,
 ) -> Result<()> {
     require_gt!(
         lockup_saturation_secs,
         0,
         VsrError::LockupSaturationMustBePositive
     );
-    let registrar = &mut ctx.accounts.registrar.load_mut()?;
-    let mint = ctx.accounts.mint.key();
-    let idx = idx as usize;
-    require_gt!(
-        registrar.voting_mints.len(),
-        idx,
-        VsrError::OutOfBoundsVotingMintConfigIndex
+    require_gte!(
+        SCALED_FACTOR_BASE,
+        early_unlock_penalty_scaled_factor,
+        VsrError::InvalidEarlyUnlockPenalty
     );
-
-    // Either it's reconfiguring an existing mint with the correct index,
-    // or configuring a new mint on an unused index.
-    match registrar.voting_mint_config_index(mint) {
-        Ok(existing_idx) => require_eq!(
-            existing_idx,
+    unpack_mint(&ctx.accounts.mint)?;
+    {
+        let registrar = &mut ctx.accounts.registrar.load_full_mut()?;
+        let mint = ctx.accounts.mint.key();
+        let idx = idx as usize;
+        require_gt!(
+            registrar.voting_mints_len(),
             idx,
-            VsrError::VotingMintConfiguredWithDifferentIndex
-        ),
-        Err(_) => require!(
-            !registrar.voting_mints[idx].in_use(),
-            VsrError::VotingMintConfigIndexAlreadyInUse
-        ),
-    };
+            VsrError::OutOfBoundsVotingMintConfigIndex
+        );
 
-    registrar.voting_mints[idx] = VotingMintConfig {
-        mint,
-        digit_shift,
-        baseline_vote_weight_scaled_factor,
-        max_extra_lockup_vote_weight_scaled_factor,
-        lockup_saturation_secs,
-        grant_authority: grant_authority.unwrap_or_default(),
-        reserved1: [0; 7],
-        reserved2: [0; 7],
-    };
+        // Either it's reconfiguring an existing mint with the correct index,
+        // or configuring a new mint on an unused index.
+        let limits = match registrar.voting_mint_config_index(mint) {
+            Ok(existing_idx) => {
+                require_eq!(
+                    existing_idx,
+                    idx,
+                    VsrError::VotingMintConfiguredWithDifferentIndex
+                );
+                let existing = *registrar.voting_mint(idx);
+                existing.check_not_retired()?;
+                existing
+            }
+            Err(_) => {
+                require!(
+                    !registrar.voting_mint(idx).in_use(),
+                    VsrError::VotingMintConfigIndexAlreadyInUse
+                );
+                VotingMintConfig::default()
+            }
+        };
+
+        *registrar.voting_mint_mut(idx) = VotingMintConfig {
+            mint,
+            digit_shift,
+            retired: false,
+            priced: limits.priced,
+            baseline_vote_weight_scaled_factor,
+            max_extra_lockup_vote_weight_scaled_factor,
+            lockup_saturation_secs,
+            grant_authority: grant_authority.unwrap_or_default(),
+            reserved1: [0; 5],
+            early_unlock_penalty_scaled_factor,
+            min_deposit_native: limits.min_deposit_native,
+            max_deposited_native: limits.max_deposited_native,
+            max_voter_deposited_native: limits.max_voter_deposited_native,
+            min_lockup_secs: limits.min_lockup_secs,
+            max_lockup_secs: limits.max_lockup_secs,
+            allowed_lockup_kinds: limits.allowed_lockup_kinds,
+            reserved2: [0; 4],
+        };
+    }
 
     // Check for overflow in vote weight
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
     registrar.max_vote_weight(ctx.remaining_accounts)?;
 
     Ok(())
@@ -0,0 +1,84 @@
+use crate::error::*;
+use crate::instructions::is_freshly_initialized;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct ConfigureVotingMintRate<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [registrar.key().as_ref(), b"voting-mint-rate".as_ref(), mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<VotingMintRate>(),
+    )]
+    pub voting_mint_rate: AccountLoader<'info, VotingMintRate>,
+
+    /// CHECK: Must be a configured voting mint, checked in the instruction
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Makes a configured voting mint priced: the vote weight of its deposits is
+/// scaled by a rate that `authority` updates with set_voting_mint_rate.
+///
+/// * `authority`: the key that can update the rate, e.g. a keeper that
+///   reads an oracle or a pool's exchange rate
+/// * `max_rate`: maximum rate the authority can set, in 1/1e9 units,
+///   zero for no limit
+/// * `max_staleness_secs`: number of seconds after an update the rate
+///   stays valid
+///
+/// The rate is multiplied into both vote weight factors of the voting mint,
+/// see configure_voting_mint. Until the first update and whenever the rate
+/// is stale, the mint's deposits have no vote weight.
+///
+/// Instructions that compute the vote weight of deposits of priced voting
+/// mints, like update_voter_weight_record and update_max_vote_weight, need
+/// the VotingMintRate account among their remaining accounts.
+///
+/// Can be called again to change the authority and the bounds, the rate is
+/// kept.
+pub fn configure_voting_mint_rate(
+    ctx: Context<ConfigureVotingMintRate>,
+    authority: Pubkey,
+    max_rate: u64,
+    max_staleness_secs: u64,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_full_mut()?;
+    let mint = ctx.accounts.mint.key();
+    let idx = registrar.voting_mint_config_index(mint)?;
+    registrar.voting_mint_mut(idx).priced = true;
+
+    let new_rate = is_freshly_initialized(ctx.accounts.voting_mint_rate.as_ref())?;
+    let mut voting_mint_rate = if new_rate {
+        ctx.accounts.voting_mint_rate.load_init()?
+    } else {
+        ctx.accounts.voting_mint_rate.load_mut()?
+    };
+    if new_rate {
+        voting_mint_rate.registrar = ctx.accounts.registrar.key();
+        voting_mint_rate.mint = mint;
+        voting_mint_rate.bump = *ctx.bumps.get("voting_mint_rate").unwrap();
+    }
+    if max_rate > 0 {
+        require_gte!(
+            max_rate,
+            voting_mint_rate.rate,
+            VsrError::VotingMintRateTooHigh
+        );
+    }
+    voting_mint_rate.authority = authority;
+    voting_mint_rate.max_rate = max_rate;
+    voting_mint_rate.max_staleness_secs = max_staleness_secs;
+    Ok(())
+}
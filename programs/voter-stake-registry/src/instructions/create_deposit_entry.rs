@@ -1,14 +1,19 @@
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::*;
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{Mint, Token, TokenAccount};
 use std::convert::TryFrom;
 
 #[derive(Accounts)]
 pub struct CreateDepositEntry<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     // checking the PDA address it just an extra precaution,
     // the other constraints must be exhaustive
     #[account(
@@ -19,23 +24,28 @@ pub struct CreateDepositEntry<'info> {
         has_one = voter_authority)]
     pub voter: AccountLoader<'info, Voter>,
 
+    /// The vault is created if needed.
+    ///
+    /// CHECK: Address constraint is set, the associated token account of voter
     #[account(
-        init_if_needed,
-        associated_token::authority = voter,
-        associated_token::mint = deposit_mint,
-        payer = payer
+        mut,
+        address = vault_address(&voter.key(), &deposit_mint.key(), &token_program.key()),
     )]
-    pub vault: Box<Account<'info, TokenAccount>>,
+    pub vault: UncheckedAccount<'info>,
 
     pub voter_authority: Signer<'info>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    pub deposit_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub deposit_mint: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
 }
@@ -54,30 +64,34 @@ pub struct CreateDepositEntry<'info> {
 ///    the vesting start time and the anchor for the periods computation.
 ///
 /// - `periods`: How long to lock up, depending on `kind`. See LockupKind::period_secs()
+/// - `cliff_periods`: For MonthlyWithCliff, the number of initial periods during
+///    which nothing vests. Must be 0 for other lockup kinds.
 /// - `allow_clawback`: When enabled, the the realm_authority is allowed to
 ///    unilaterally claim locked tokens.
+///
+/// The lockup must be allowed by the voting mint's lockup limits, see
+/// configure_lockup_limits.
 pub fn create_deposit_entry(
     ctx: Context<CreateDepositEntry>,
     deposit_entry_index: u8,
     kind: LockupKind,
     start_ts: Option<u64>,
     periods: u32,
+    cliff_periods: u32,
     allow_clawback: bool,
 ) -> Result<()> {
     // Load accounts.
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx.accounts.registrar.load_full()?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
 
     // Get the exchange rate entry associated with this deposit.
     let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+    registrar.voting_mint(mint_idx).check_not_retired()?;
+    let mint_stats_before = voter.mint_stats(mint_idx);
 
     // Get and set up the deposit entry.
-    require_gt!(
-        voter.deposits.len(),
-        deposit_entry_index as usize,
-        VsrError::OutOfBoundsDepositEntryIndex
-    );
-    let d_entry = &mut voter.deposits[deposit_entry_index as usize];
+    let d_entry = voter.deposit_mut(deposit_entry_index)?;
     require!(!d_entry.is_used, VsrError::UnusedDepositEntryIndex);
 
     let curr_ts = registrar.clock_unix_timestamp();
@@ -93,7 +107,30 @@ pub fn create_deposit_entry(
     d_entry.amount_deposited_native = 0;
     d_entry.amount_initially_locked_native = 0;
     d_entry.allow_clawback = allow_clawback;
-    d_entry.lockup = Lockup::new_from_periods(kind, curr_ts, start_ts, periods)?;
+    d_entry.lockup =
+        Lockup::new_from_periods_with_cliff(kind, curr_ts, start_ts, periods, cliff_periods)?;
+    registrar
+        .voting_mint(mint_idx)
+        .check_lockup(&d_entry.lockup, curr_ts)?;
+
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    create_vault_if_needed(
+        &ctx.accounts.payer,
+        &ctx.accounts.vault,
+        ctx.accounts.voter.as_ref(),
+        &ctx.accounts.deposit_mint,
+        ctx.accounts.system_program.as_ref(),
+        &ctx.accounts.token_program,
+        ctx.accounts.associated_token_program.as_ref(),
+    )?;
 
     Ok(())
 }
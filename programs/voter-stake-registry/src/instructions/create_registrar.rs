@@ -1,7 +1,7 @@
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::is_token_program;
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
 use spl_governance::state::realm;
 use std::mem::size_of;
 
@@ -30,7 +30,11 @@ pub struct CreateRegistrar<'info> {
     /// The program id of the spl-governance program the realm belongs to.
     pub governance_program_id: UncheckedAccount<'info>,
     /// Either the realm community mint or the council mint.
-    pub realm_governing_token_mint: Account<'info, Mint>,
+    ///
+    /// CHECK: Must be owned by spl-token or spl-token-2022, and the realm
+    /// check in the instruction ensures it's a mint
+    #[account(constraint = is_token_program(realm_governing_token_mint.owner) @ VsrError::InvalidTokenProgram)]
+    pub realm_governing_token_mint: UncheckedAccount<'info>,
     pub realm_authority: Signer<'info>,
 
     #[account(mut)]
@@ -0,0 +1,47 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct CreateRegistrarStats<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), b"registrar-stats".as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<RegistrarStats>(),
+    )]
+    pub registrar_stats: AccountLoader<'info, RegistrarStats>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the account holding the registrar's deposit totals per voting mint.
+///
+/// The totals start at zero, so this must be called after create_registrar
+/// and before any voting mint is configured. Registrars without it keep
+/// working, but have no totals.
+///
+/// Once it exists, instructions that change deposit entries must pass it,
+/// see update_registrar_stats().
+pub fn create_registrar_stats(ctx: Context<CreateRegistrarStats>) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_full_mut()?;
+    require!(
+        registrar.voting_mints().all(|mint| !mint.in_use()),
+        VsrError::IncompleteRegistrarStats
+    );
+    registrar.has_registrar_stats = true;
+
+    let stats = &mut ctx.accounts.registrar_stats.load_init()?;
+    stats.registrar = ctx.accounts.registrar.key();
+    stats.bump = *ctx.bumps.get("registrar_stats").unwrap();
+    Ok(())
+}
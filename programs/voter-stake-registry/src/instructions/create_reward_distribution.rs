@@ -0,0 +1,86 @@
+use crate::error::*;
+use crate::state::*;
+use crate::token_interface::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct CreateRewardDistribution<'info> {
+    #[account(has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), b"reward-distribution".as_ref(), reward_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<RewardDistribution>(),
+    )]
+    pub distribution: AccountLoader<'info, RewardDistribution>,
+
+    /// The vault is created if needed.
+    ///
+    /// CHECK: Address constraint is set, the associated token account of distribution
+    #[account(
+        mut,
+        address = vault_address(&distribution.key(), &reward_mint.key(), &token_program.key()),
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub reward_mint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// Creates a distribution of `reward_mint` tokens to the registrar's voters.
+///
+/// `duration_secs`: The time over which each funding is paid out, see
+/// fund_reward_distribution.
+///
+/// Voters take part by creating a VoterReward with create_voter_reward and
+/// receive rewards in proportion to their locked vote weight.
+pub fn create_reward_distribution(
+    ctx: Context<CreateRewardDistribution>,
+    duration_secs: u64,
+) -> Result<()> {
+    require_gt!(duration_secs, 0, VsrError::InvalidRewardDuration);
+    require_gt!(
+        i64::MAX as u64,
+        duration_secs,
+        VsrError::InvalidRewardDuration
+    );
+
+    {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let distribution = &mut ctx.accounts.distribution.load_init()?;
+        distribution.registrar = ctx.accounts.registrar.key();
+        distribution.reward_mint = ctx.accounts.reward_mint.key();
+        distribution.duration_secs = duration_secs;
+        distribution.last_update_ts = registrar.clock_unix_timestamp();
+        distribution.period_end_ts = distribution.last_update_ts;
+        distribution.bump = *ctx.bumps.get("distribution").unwrap();
+    }
+
+    create_vault_if_needed(
+        &ctx.accounts.payer,
+        &ctx.accounts.vault,
+        ctx.accounts.distribution.as_ref(),
+        &ctx.accounts.reward_mint,
+        ctx.accounts.system_program.as_ref(),
+        &ctx.accounts.token_program,
+        ctx.accounts.associated_token_program.as_ref(),
+    )?;
+
+    Ok(())
+}
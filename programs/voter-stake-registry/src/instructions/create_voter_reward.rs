@@ -0,0 +1,66 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct CreateVoterReward<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    #[account(mut, has_one = registrar)]
+    pub distribution: AccountLoader<'info, RewardDistribution>,
+
+    #[account(
+        init,
+        seeds = [distribution.key().as_ref(), b"voter-reward".as_ref(), voter.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<VoterReward>(),
+    )]
+    pub voter_reward: AccountLoader<'info, VoterReward>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Makes a voter take part in a reward distribution.
+///
+/// From now on the voter accrues rewards in proportion to its locked vote
+/// weight. Instructions that change the voter's deposits must be passed the
+/// new VoterReward account and its RewardDistribution as remaining accounts,
+/// see sync_voter_rewards().
+///
+/// The VestingSchedule accounts of the voter's deposits with LockupKind::Tranches
+/// must be passed as remaining accounts.
+pub fn create_voter_reward(ctx: Context<CreateVoterReward>) -> Result<()> {
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
+    let weight = reward_weight(voter, registrar, &schedules)?;
+
+    let distribution = &mut ctx.accounts.distribution.load_mut()?;
+    let position = &mut ctx.accounts.voter_reward.load_init()?;
+    position.voter = ctx.accounts.voter.key();
+    position.distribution = ctx.accounts.distribution.key();
+    position.bump = *ctx.bumps.get("voter_reward").unwrap();
+    distribution.update_position(position, registrar.clock_unix_timestamp(), weight);
+
+    voter.reward_positions = voter.reward_positions.checked_add(1).unwrap();
+
+    Ok(())
+}
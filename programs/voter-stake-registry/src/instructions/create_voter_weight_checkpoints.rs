@@ -0,0 +1,60 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+use std::mem::size_of;
+
+#[derive(Accounts)]
+pub struct CreateVoterWeightCheckpoints<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [voter.key().as_ref(), b"voter-weight-checkpoints".as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + size_of::<VoterWeightCheckpoints>(),
+    )]
+    pub checkpoints: AccountLoader<'info, VoterWeightCheckpoints>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Starts recording the history of a voter's vote weight.
+///
+/// The first checkpoint is the voter's current weight. From now on,
+/// instructions that change the voter's weight must be passed the new
+/// VoterWeightCheckpoints account as a remaining account, see
+/// record_weight_checkpoint().
+///
+/// The VestingSchedule accounts of the voter's deposits with LockupKind::Tranches
+/// must be passed as remaining accounts.
+pub fn create_voter_weight_checkpoints(ctx: Context<CreateVoterWeightCheckpoints>) -> Result<()> {
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
+    let checkpoint = voter_weight_checkpoint(voter, registrar, &schedules)?;
+    voter.has_weight_checkpoints = true;
+
+    let checkpoints = &mut ctx.accounts.checkpoints.load_init()?;
+    checkpoints.voter = ctx.accounts.voter.key();
+    checkpoints.bump = *ctx.bumps.get("checkpoints").unwrap();
+    checkpoints.push(checkpoint);
+
+    Ok(())
+}
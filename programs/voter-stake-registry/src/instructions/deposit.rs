@@ -1,12 +1,17 @@
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     // checking the PDA address it just an extra precaution,
     // the other constraints must be exhaustive
     #[account(
@@ -16,28 +21,35 @@ pub struct Deposit<'info> {
         has_one = registrar)]
     pub voter: AccountLoader<'info, Voter>,
 
+    /// CHECK: Address constraint is set, the associated token account of voter
     #[account(
         mut,
-        associated_token::authority = voter,
-        associated_token::mint = deposit_token.mint,
+        address = vault_address(&voter.key(), &deposit_mint.key(), &token_program.key()),
     )]
-    pub vault: Box<Account<'info, TokenAccount>>,
+    pub vault: UncheckedAccount<'info>,
 
-    #[account(
-        mut,
-        constraint = deposit_token.owner == deposit_authority.key(),
-    )]
-    pub deposit_token: Box<Account<'info, TokenAccount>>,
+    /// CHECK: deposit_token is validated in the instruction:
+    /// - owner is deposit_authority
+    /// - mint is deposit_mint
+    #[account(mut)]
+    pub deposit_token: UncheckedAccount<'info>,
     pub deposit_authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub deposit_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
 }
 
 impl<'info> Deposit<'info> {
-    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
         let program = self.token_program.to_account_info();
-        let accounts = token::Transfer {
+        let accounts = TransferChecked {
             from: self.deposit_token.to_account_info(),
+            mint: self.deposit_mint.to_account_info(),
             to: self.vault.to_account_info(),
             authority: self.deposit_authority.to_account_info(),
         };
@@ -54,6 +66,9 @@ impl<'info> Deposit<'info> {
 /// `deposit_entry_index`: Index of the deposit entry.
 /// `amount`: Number of native tokens to transfer.
 ///
+/// With spl-token-2022 mints that charge transfer fees, only the amount that
+/// actually arrives in the vault is credited to the deposit entry.
+///
 /// Note that adding tokens to a deposit entry with vesting, where some vesting
 /// periods are already in the past is supported. What happens is that the tokens
 /// get distributed over vesting points in the future.
@@ -61,18 +76,42 @@ impl<'info> Deposit<'info> {
 /// Example: 20 tokens are deposited to a three-day vesting deposit entry
 /// that started 36 hours ago. That means 10 extra tokens will vest in 12 hours
 /// and another 10 in 36 hours.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
 pub fn deposit(ctx: Context<Deposit>, deposit_entry_index: u8, amount: u64) -> Result<()> {
     if amount == 0 {
         return Ok(());
     }
 
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+
+    // Get the exchange rate entry associated with this deposit.
+    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+    registrar.voting_mint(mint_idx).check_not_retired()?;
+    let mint_stats_before = voter.mint_stats(mint_idx);
 
     let d_entry = voter.active_deposit_mut(deposit_entry_index)?;
 
-    // Get the exchange rate entry associated with this deposit.
-    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_token.mint)?;
+    let deposit_token = unpack_token_account(&ctx.accounts.deposit_token)?;
+    require_keys_eq!(
+        deposit_token.owner,
+        ctx.accounts.deposit_authority.key(),
+        VsrError::InvalidAuthority
+    );
+    require_keys_eq!(
+        deposit_token.mint,
+        ctx.accounts.deposit_mint.key(),
+        VsrError::InvalidMint
+    );
+
     require_eq!(
         mint_idx,
         d_entry.voting_mint_config_idx as usize,
@@ -91,20 +130,59 @@ pub fn deposit(ctx: Context<Deposit>, deposit_entry_index: u8, amount: u64) -> R
     d_entry.resolve_vesting(curr_ts)?;
 
     // Deposit tokens into the vault and increase the lockup amount too.
-    token::transfer(ctx.accounts.transfer_ctx(), amount)?;
-    d_entry.amount_deposited_native = d_entry.amount_deposited_native.checked_add(amount).unwrap();
+    // Transfer fees may mean that less than `amount` arrives in the vault.
+    let vault_before = unpack_token_account(&ctx.accounts.vault)?.amount;
+    transfer_checked(ctx.accounts.transfer_ctx(), amount)?;
+    let vault_after = unpack_token_account(&ctx.accounts.vault)?.amount;
+    let received = vault_after.checked_sub(vault_before).unwrap();
+    d_entry.amount_deposited_native = d_entry
+        .amount_deposited_native
+        .checked_add(received)
+        .unwrap();
     d_entry.amount_initially_locked_native = d_entry
         .amount_initially_locked_native
-        .checked_add(amount)
+        .checked_add(received)
         .unwrap();
 
     msg!(
         "Deposited amount {} at deposit index {} with lockup kind {:?} and {} seconds left",
-        amount,
+        received,
         deposit_entry_index,
         d_entry.lockup.kind,
         d_entry.lockup.seconds_left(curr_ts),
     );
 
+    // Update the registrar's deposit totals.
+    let registrar_mint_stats = update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    // Enforce the voting mint's deposit limits.
+    let mint_config = registrar.voting_mint(mint_idx);
+    mint_config.check_min_deposit(received)?;
+    mint_config.check_max_deposited(
+        voter.mint_stats(mint_idx).deposited_native,
+        registrar_mint_stats.map(|stats| stats.deposited_native),
+    )?;
+
+    // Rewards accrue at the new locked vote weight from now on.
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
     Ok(())
 }
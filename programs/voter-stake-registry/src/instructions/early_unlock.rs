@@ -0,0 +1,263 @@
+use crate::error::*;
+use crate::state::*;
+use crate::token_interface::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct EarlyUnlock<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority,
+    )]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    /// The token_owner_record for the voter_authority. Early unlocks are
+    /// forbidden whenever withdraws are, for example while the voter is
+    /// engaged with a vote or has an open proposal.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is voter_authority
+    pub token_owner_record: UncheckedAccount<'info>,
+
+    /// Early unlocks must update the voter weight record, to prevent a stale
+    /// record being used to vote after the lockup bonus is gone.
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.voter_authority,
+        constraint = voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    /// The token_owner_record for the delegate, required if the voter has one.
+    /// The delegate may be voting with the unlocked tokens.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is voter.delegate
+    pub delegate_token_owner_record: Option<UncheckedAccount<'info>>,
+
+    /// The voter weight record of the delegate, required if the voter has one.
+    /// It is reset, since it may include the lockup bonus of the unlocked tokens.
+    #[account(
+        mut,
+        constraint = delegate_voter_weight_record.realm == registrar.load()?.realm,
+        constraint = delegate_voter_weight_record.governing_token_owner == voter.load()?.delegate,
+        constraint = delegate_voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub delegate_voter_weight_record: Option<Account<'info, VoterWeightRecord>>,
+
+    /// CHECK: Address constraint is set, the associated token account of voter
+    #[account(
+        mut,
+        address = vault_address(&voter.key(), &deposit_mint.key(), &token_program.key()),
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// The token account that receives the penalty.
+    ///
+    /// CHECK: Owner is validated in the instruction to be registrar.early_unlock_treasury,
+    /// the mint is validated by the token program
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub deposit_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
+}
+
+impl<'info> EarlyUnlock<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.deposit_mint.to_account_info(),
+            to: self.treasury.to_account_info(),
+            authority: self.voter.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+/// Unlocks a deposit entry before its lockup ends, in exchange for a penalty.
+///
+/// `deposit_entry_index`: The deposit entry to unlock.
+///
+/// The penalty is paid from the locked tokens to the treasury token account.
+/// Each locked token pays a share of the voting mint's
+/// early_unlock_penalty_scaled_factor that grows with its remaining lockup
+/// time and saturates at lockup_saturation_secs. The remaining tokens are
+/// unlocked and can be withdrawn right away.
+///
+/// Early unlocks must be enabled with set_early_unlock_treasury and a non-zero
+/// penalty in configure_voting_mint. They are not possible on deposit entries
+/// that allow clawback, and are forbidden whenever withdraws are.
+///
+/// If the voter delegated its vote weight, the delegate's token owner record
+/// and voter weight record must be passed too.
+///
+/// The VestingSchedule accounts of the voter's deposits with LockupKind::Tranches
+/// must be passed as remaining accounts. So must the voter's VoterReward accounts
+/// and their RewardDistributions, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
+pub fn early_unlock(ctx: Context<EarlyUnlock>, deposit_entry_index: u8) -> Result<()> {
+    let penalty = {
+        // Load the accounts.
+        let registrar = &ctx
+            .accounts
+            .registrar
+            .load_full_with_rates(ctx.remaining_accounts)?;
+        require!(!registrar.paused, VsrError::RegistrarPaused);
+        let voter = &mut ctx.accounts.voter.load_full_mut()?;
+        let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
+
+        // Check that early unlocks are enabled and the penalty goes to the treasury.
+        let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+        let mint_config = registrar.voting_mint(mint_idx);
+        let mint_stats_before = voter.mint_stats(mint_idx);
+        require!(
+            registrar.early_unlock_treasury != Pubkey::default()
+                && mint_config.early_unlock_penalty_scaled_factor > 0,
+            VsrError::EarlyUnlockDisabled
+        );
+        let treasury = unpack_token_account(&ctx.accounts.treasury)?;
+        require_keys_eq!(
+            treasury.owner,
+            registrar.early_unlock_treasury,
+            VsrError::InvalidEarlyUnlockTreasury
+        );
+
+        // Governance may forbid withdraws, for example when engaged in a vote.
+        // Not applicable for tokens that don't contribute to voting power.
+        if mint_config.grants_vote_weight() {
+            let token_owner_record = voter.load_token_owner_record(
+                &ctx.accounts.token_owner_record.to_account_info(),
+                registrar,
+            )?;
+            token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+            if voter.has_delegate() {
+                let delegate_token_owner_record = ctx
+                    .accounts
+                    .delegate_token_owner_record
+                    .as_ref()
+                    .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+                let delegate_token_owner_record = voter.load_delegate_token_owner_record(
+                    &delegate_token_owner_record.to_account_info(),
+                    registrar,
+                )?;
+                delegate_token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+                let delegate_record = ctx
+                    .accounts
+                    .delegate_voter_weight_record
+                    .as_mut()
+                    .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+                delegate_record.voter_weight = 0;
+                delegate_record.voter_weight_expiry = Some(Clock::get()?.slot);
+            }
+        }
+
+        // Get the deposit being unlocked.
+        let curr_ts = registrar.clock_unix_timestamp();
+        let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
+        require_eq!(
+            mint_idx,
+            deposit_entry.voting_mint_config_idx as usize,
+            VsrError::InvalidMint
+        );
+        // Otherwise grantees could escape a clawback for the price of the penalty.
+        require!(
+            !deposit_entry.allow_clawback,
+            VsrError::InvalidChangeToClawbackDepositEntry
+        );
+        let schedule = schedules.for_deposit(deposit_entry_index as usize, deposit_entry)?;
+        let penalty = deposit_entry.early_unlock_penalty(mint_config, curr_ts, schedule)?;
+
+        msg!(
+            "Unlocked deposit index {} with lockup kind {:?} and {} seconds left early, for a penalty of {}",
+            deposit_entry_index,
+            deposit_entry.lockup.kind,
+            deposit_entry.lockup.seconds_left(curr_ts),
+            penalty,
+        );
+
+        // Bookkeeping for the penalty, the rest is unlocked.
+        deposit_entry.amount_deposited_native = deposit_entry
+            .amount_deposited_native
+            .checked_sub(penalty)
+            .unwrap();
+        deposit_entry.unlock_deposit();
+
+        // Update the registrar's deposit totals.
+        update_registrar_stats(
+            registrar,
+            &ctx.accounts.registrar_stats,
+            mint_idx,
+            &mint_stats_before,
+            &voter.mint_stats(mint_idx),
+        )?;
+
+        // Update the voter weight record. With a predecessor plugin, only
+        // update_voter_weight_record can compute the full weight.
+        let record = &mut ctx.accounts.voter_weight_record;
+        record.voter_weight = if registrar.has_predecessor() {
+            0
+        } else {
+            voter.undelegated_weight(registrar, &schedules)?
+        };
+        record.voter_weight_expiry = Some(Clock::get()?.slot);
+
+        sync_voter_rewards(
+            &ctx.accounts.voter.key(),
+            voter,
+            registrar,
+            ctx.remaining_accounts,
+        )?;
+        record_weight_checkpoint(
+            &ctx.accounts.voter.key(),
+            voter,
+            registrar,
+            ctx.remaining_accounts,
+        )?;
+
+        penalty
+    };
+
+    {
+        // Transfer the penalty to the treasury.
+        let voter = &mut ctx.accounts.voter.load()?;
+        let voter_seeds = voter_seeds!(voter);
+        transfer_checked(
+            ctx.accounts.transfer_ctx().with_signer(&[voter_seeds]),
+            penalty,
+        )?;
+    }
+
+    Ok(())
+}
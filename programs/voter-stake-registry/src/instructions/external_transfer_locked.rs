@@ -0,0 +1,374 @@
+use crate::error::*;
+use crate::state::*;
+use crate::token_interface::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+
+#[derive(Accounts)]
+pub struct ExternalTransferLocked<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority,
+    )]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    /// The token_owner_record for the voter_authority. This is needed
+    /// to be able to forbid transfers while the voter is engaged with
+    /// a vote or has an open proposal.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is voter_authority
+    pub token_owner_record: UncheckedAccount<'info>,
+
+    /// Transfers must update the voter weight record, to prevent a stale
+    /// record being used to vote after the transfer.
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.voter_authority,
+        constraint = voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub voter_weight_record: Box<Account<'info, VoterWeightRecord>>,
+
+    /// The token_owner_record for the delegate, required if the voter has one.
+    /// The delegate may be voting with the transferred tokens.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is voter.delegate
+    pub delegate_token_owner_record: Option<UncheckedAccount<'info>>,
+
+    /// The voter weight record of the delegate, required if the voter has one.
+    /// It is reset, since it may include the transferred tokens.
+    #[account(
+        mut,
+        constraint = delegate_voter_weight_record.realm == registrar.load()?.realm,
+        constraint = delegate_voter_weight_record.governing_token_owner == voter.load()?.delegate,
+        constraint = delegate_voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub delegate_voter_weight_record: Option<Box<Account<'info, VoterWeightRecord>>>,
+
+    /// CHECK: Address constraint is set, the associated token account of voter
+    #[account(
+        mut,
+        address = vault_address(&voter.key(), &deposit_mint.key(), &token_program.key()),
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), target_voter_authority.key().as_ref()],
+        bump = target_voter.load()?.voter_bump,
+        has_one = registrar,
+        constraint = target_voter.load()?.voter_authority == target_voter_authority.key(),
+        constraint = target_voter.key() != voter.key(),
+    )]
+    pub target_voter: AccountLoader<'info, Voter>,
+
+    /// The target voter's authority agrees to receive the locked tokens and
+    /// pays for the target vault if it doesn't exist yet.
+    #[account(mut)]
+    pub target_voter_authority: Signer<'info>,
+
+    /// The vault is created if needed.
+    ///
+    /// CHECK: Address constraint is set, the associated token account of target_voter
+    #[account(
+        mut,
+        address = vault_address(&target_voter.key(), &deposit_mint.key(), &token_program.key()),
+    )]
+    pub target_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub deposit_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExternalTransferLocked<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.deposit_mint.to_account_info(),
+            to: self.target_vault.to_account_info(),
+            authority: self.voter.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+/// Transfers locked tokens from a deposit entry of the voter to a deposit entry
+/// of another voter of the same registrar, like internal_transfer_locked does
+/// between deposit entries of one voter.
+///
+/// If the target deposit entry is unused, it is set up with the source's exact
+/// remaining lockup. Otherwise it must be of the same mint, have an equal or
+/// longer lockup period and be of a kind that is at least equally strict.
+/// Neither entry may allow clawback.
+///
+/// The tokens move from the voter's vault to the target voter's vault. With
+/// spl-token-2022 transfer fees, the target receives less than `amount`.
+///
+/// Like withdraws, transfers are forbidden while the voter is engaged in a vote,
+/// and need the delegate's token owner record and voter weight record if the
+/// voter has a delegate.
+///
+/// The target's lockup must be allowed by the voting mint's lockup limits, see
+/// configure_lockup_limits, and the received tokens count towards the target
+/// voter's deposit limits, see configure_deposit_limits.
+///
+/// The VoterReward accounts of both voters and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards(). So must the voters'
+/// VoterWeightCheckpoints accounts if they have them, see record_weight_checkpoint(),
+/// and the source voter's VestingSchedules.
+pub fn external_transfer_locked<'key, 'accounts, 'remaining, 'info>(
+    ctx: Context<'key, 'accounts, 'remaining, 'info, ExternalTransferLocked<'info>>,
+    source_deposit_entry_index: u8,
+    target_deposit_entry_index: u8,
+    amount: u64,
+) -> Result<()> {
+    create_vault_if_needed(
+        ctx.accounts.target_voter_authority.as_ref(),
+        &ctx.accounts.target_vault,
+        ctx.accounts.target_voter.as_ref(),
+        &ctx.accounts.deposit_mint,
+        ctx.accounts.system_program.as_ref(),
+        &ctx.accounts.token_program,
+        ctx.accounts.associated_token_program.as_ref(),
+    )?;
+
+    // Transfer the tokens. Transfer fees may mean that less than `amount`
+    // arrives in the target vault.
+    let target_vault_before = unpack_token_account(&ctx.accounts.target_vault)?.amount;
+    {
+        let voter = &ctx.accounts.voter.load()?;
+        let voter_seeds = voter_seeds!(voter);
+        transfer_checked(
+            ctx.accounts.transfer_ctx().with_signer(&[voter_seeds]),
+            amount,
+        )?;
+    }
+    let target_vault_after = unpack_token_account(&ctx.accounts.target_vault)?.amount;
+    let received = target_vault_after.checked_sub(target_vault_before).unwrap();
+
+    // Load the accounts.
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let target_voter = &mut ctx.accounts.target_voter.load_full_mut()?;
+    let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
+    let curr_ts = registrar.clock_unix_timestamp();
+
+    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+    let mint_stats_before = voter.mint_stats(mint_idx);
+    let target_mint_stats_before = target_voter.mint_stats(mint_idx);
+
+    // Governance may forbid transfers, like withdraws, for example when
+    // engaged in a vote.
+    if registrar.voting_mint(mint_idx).grants_vote_weight() {
+        let token_owner_record = voter.load_token_owner_record(
+            &ctx.accounts.token_owner_record.to_account_info(),
+            registrar,
+        )?;
+        token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+        if voter.has_delegate() {
+            let delegate_token_owner_record = ctx
+                .accounts
+                .delegate_token_owner_record
+                .as_ref()
+                .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+            let delegate_token_owner_record = voter.load_delegate_token_owner_record(
+                &delegate_token_owner_record.to_account_info(),
+                registrar,
+            )?;
+            delegate_token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+            let delegate_record = ctx
+                .accounts
+                .delegate_voter_weight_record
+                .as_mut()
+                .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+            delegate_record.voter_weight = 0;
+            delegate_record.voter_weight_expiry = Some(Clock::get()?.slot);
+        }
+    }
+
+    let source = voter.active_deposit_mut(source_deposit_entry_index)?;
+    require_eq!(
+        source.voting_mint_config_idx as usize,
+        mint_idx,
+        VsrError::InvalidMint
+    );
+
+    // Allowing transfers from clawback-enabled deposits could be used to avoid
+    // clawback, see internal_transfer_locked.
+    require!(
+        !source.allow_clawback,
+        VsrError::InvalidChangeToClawbackDepositEntry
+    );
+    source.resolve_vesting(curr_ts)?;
+    let source_lockup = source.lockup;
+
+    // Reduce source amounts
+    require_gte!(
+        source.amount_initially_locked_native,
+        amount,
+        VsrError::InsufficientLockedTokens
+    );
+    source.amount_deposited_native = source.amount_deposited_native.checked_sub(amount).unwrap();
+    source.amount_initially_locked_native =
+        source.amount_initially_locked_native.saturating_sub(amount);
+
+    // Set up or check the target
+    let target = target_voter.deposit_mut(target_deposit_entry_index)?;
+    if !target.is_used {
+        registrar.voting_mint(mint_idx).check_not_retired()?;
+        *target = DepositEntry::default();
+        target.is_used = true;
+        target.voting_mint_config_idx = mint_idx as u8;
+        target.lockup = source_lockup;
+    } else {
+        // Clawback would reach the transferred tokens.
+        require!(
+            !target.allow_clawback,
+            VsrError::InvalidChangeToClawbackDepositEntry
+        );
+        target.resolve_vesting(curr_ts)?;
+        require_eq!(
+            target.voting_mint_config_idx as usize,
+            mint_idx,
+            VsrError::InvalidMint
+        );
+        require_gte!(
+            target.lockup.seconds_left(curr_ts),
+            source_lockup.seconds_left(curr_ts),
+            VsrError::InvalidLockupPeriod
+        );
+        require_gte!(
+            target.lockup.cliff_seconds_left(curr_ts),
+            source_lockup.cliff_seconds_left(curr_ts),
+            VsrError::InvalidLockupPeriod
+        );
+        require_gte!(
+            target.lockup.kind.strictness(),
+            source_lockup.kind.strictness(),
+            VsrError::InvalidLockupKind
+        );
+    }
+    registrar
+        .voting_mint(mint_idx)
+        .check_lockup(&target.lockup, curr_ts)?;
+
+    // Add target amounts
+    target.amount_deposited_native = target
+        .amount_deposited_native
+        .checked_add(received)
+        .unwrap();
+    target.amount_initially_locked_native = target
+        .amount_initially_locked_native
+        .checked_add(received)
+        .unwrap();
+
+    msg!(
+        "Transferred amount {} at deposit index {} to voter {} at deposit index {}, lockup kind {:?} with {} seconds left",
+        amount,
+        source_deposit_entry_index,
+        target_voter.voter_authority,
+        target_deposit_entry_index,
+        source_lockup.kind,
+        source_lockup.seconds_left(curr_ts),
+    );
+
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+    let registrar_mint_stats = update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &target_mint_stats_before,
+        &target_voter.mint_stats(mint_idx),
+    )?;
+
+    // Enforce the voting mint's deposit limits for the target voter.
+    let mint_config = registrar.voting_mint(mint_idx);
+    mint_config.check_min_deposit(received)?;
+    mint_config.check_max_deposited(
+        target_voter.mint_stats(mint_idx).deposited_native,
+        registrar_mint_stats.map(|stats| stats.deposited_native),
+    )?;
+
+    // Update the voter weight record. With a predecessor plugin, only
+    // update_voter_weight_record can compute the full weight, and with
+    // historical voter weights only update_historical_voter_weight_record.
+    let record = &mut ctx.accounts.voter_weight_record;
+    record.voter_weight = if registrar.has_predecessor() || registrar.historical_voter_weight {
+        0
+    } else {
+        voter.undelegated_weight(registrar, &schedules)?
+    };
+    record.voter_weight_expiry = Some(Clock::get()?.slot);
+
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+    sync_voter_rewards(
+        &ctx.accounts.target_voter.key(),
+        target_voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+    record_weight_checkpoint(
+        &ctx.accounts.target_voter.key(),
+        target_voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
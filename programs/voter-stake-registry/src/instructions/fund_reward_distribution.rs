@@ -0,0 +1,76 @@
+use crate::error::*;
+use crate::state::*;
+use crate::token_interface::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct FundRewardDistribution<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = reward_mint)]
+    pub distribution: AccountLoader<'info, RewardDistribution>,
+
+    /// CHECK: Address constraint is set, the associated token account of distribution
+    #[account(
+        mut,
+        address = vault_address(&distribution.key(), &reward_mint.key(), &token_program.key()),
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the token program, must be a reward_mint token
+    /// account that funder may transfer from
+    #[account(mut)]
+    pub funding_token: UncheckedAccount<'info>,
+    pub funder: Signer<'info>,
+
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub reward_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
+}
+
+impl<'info> FundRewardDistribution<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = TransferChecked {
+            from: self.funding_token.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.funder.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+/// Adds reward tokens to a distribution.
+///
+/// `amount`: Number of native tokens to transfer from funding_token.
+///
+/// Anyone can fund a distribution. The tokens are paid out to the voters
+/// evenly over the next duration_secs, together with any rewards of earlier
+/// fundings that were not paid out yet.
+///
+/// With spl-token-2022 mints that charge transfer fees, only the amount that
+/// actually arrives in the vault is paid out.
+pub fn fund_reward_distribution(ctx: Context<FundRewardDistribution>, amount: u64) -> Result<()> {
+    let vault_before = unpack_token_account(&ctx.accounts.vault)?.amount;
+    transfer_checked(ctx.accounts.transfer_ctx(), amount)?;
+    let vault_after = unpack_token_account(&ctx.accounts.vault)?.amount;
+    let received = vault_after.checked_sub(vault_before).unwrap();
+
+    let registrar = &ctx.accounts.registrar.load()?;
+    let distribution = &mut ctx.accounts.distribution.load_mut()?;
+    let curr_ts = registrar.clock_unix_timestamp();
+    distribution.fund(curr_ts, received);
+
+    msg!(
+        "Funded reward distribution with {}, paid out until {}",
+        received,
+        distribution.period_end_ts,
+    );
+
+    Ok(())
+}
@@ -1,8 +1,11 @@
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::*;
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use std::cell::RefMut;
 use std::convert::TryFrom;
 use std::mem::size_of;
 
@@ -10,6 +13,11 @@ use std::mem::size_of;
 pub struct Grant<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     #[account(
         init_if_needed,
         seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
@@ -35,20 +43,20 @@ pub struct Grant<'info> {
     )]
     pub voter_weight_record: Account<'info, VoterWeightRecord>,
 
-    #[account(
-        init_if_needed,
-        associated_token::authority = voter,
-        associated_token::mint = deposit_mint,
-        payer = payer
-    )]
-    pub vault: Box<Account<'info, TokenAccount>>,
-
+    /// The vault is created if needed.
+    ///
+    /// CHECK: Address constraint is set, the associated token account of voter
     #[account(
         mut,
-        constraint = deposit_token.owner == token_authority.key(),
-        constraint = deposit_token.mint == deposit_mint.key(),
+        address = vault_address(&voter.key(), &deposit_mint.key(), &token_program.key()),
     )]
-    pub deposit_token: Box<Account<'info, TokenAccount>>,
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: deposit_token is validated in the instruction:
+    /// - owner is token_authority
+    /// - mint is deposit_mint
+    #[account(mut)]
+    pub deposit_token: UncheckedAccount<'info>,
 
     /// Authority for transfering tokens away from deposit_token
     pub token_authority: Signer<'info>,
@@ -62,19 +70,32 @@ pub struct Grant<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    pub deposit_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub deposit_mint: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// The vesting schedule for the new deposit entry, required for
+    /// LockupKind::Tranches. It is created if needed.
+    ///
+    /// CHECK: The address is validated in the instruction, it must be the
+    /// vesting schedule PDA of the voter and the new deposit entry's index
+    #[account(mut)]
+    pub vesting_schedule: Option<UncheckedAccount<'info>>,
 }
 
 impl<'info> Grant<'info> {
-    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
         let program = self.token_program.to_account_info();
-        let accounts = token::Transfer {
+        let accounts = TransferChecked {
             from: self.deposit_token.to_account_info(),
+            mint: self.deposit_mint.to_account_info(),
             to: self.vault.to_account_info(),
             authority: self.token_authority.to_account_info(),
         };
@@ -91,10 +112,119 @@ pub fn is_freshly_initialized(account_info: &AccountInfo) -> Result<bool> {
     Ok(discriminator == 0)
 }
 
+/// Creates the vesting schedule account for a deposit entry, unless it already
+/// exists because a previous deposit entry at the same index used it.
+///
+/// Like Anchor's `init`, this also works when the address already holds lamports.
+pub fn init_vesting_schedule<'a, 'info>(
+    schedule: &'a AccountInfo<'info>,
+    voter: &Pubkey,
+    deposit_entry_index: u8,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<RefMut<'a, VestingSchedule>> {
+    let (address, bump) = Pubkey::find_program_address(
+        &[
+            voter.as_ref(),
+            b"vesting-schedule".as_ref(),
+            &[deposit_entry_index],
+        ],
+        &crate::ID,
+    );
+    require_keys_eq!(schedule.key(), address, VsrError::InvalidVestingSchedule);
+
+    if schedule.owner != &crate::ID {
+        let space = 8 + size_of::<VestingSchedule>();
+        let rent = Rent::get()?.minimum_balance(space);
+        let seeds = vesting_schedule_seeds!(voter, deposit_entry_index, bump);
+        let system_program = system_program.clone();
+        let payer = payer.clone();
+        let lamports = schedule.lamports();
+        if lamports == 0 {
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program,
+                    system_program::CreateAccount {
+                        from: payer,
+                        to: schedule.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                rent,
+                space as u64,
+                &crate::ID,
+            )?;
+        } else {
+            let required_lamports = rent.saturating_sub(lamports);
+            if required_lamports > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        system_program.clone(),
+                        system_program::Transfer {
+                            from: payer,
+                            to: schedule.to_account_info(),
+                        },
+                    ),
+                    required_lamports,
+                )?;
+            }
+            system_program::allocate(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    system_program::Allocate {
+                        account_to_allocate: schedule.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                space as u64,
+            )?;
+            system_program::assign(
+                CpiContext::new_with_signer(
+                    system_program,
+                    system_program::Assign {
+                        account_to_assign: schedule.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                &crate::ID,
+            )?;
+        }
+        schedule.try_borrow_mut_data()?[..8].copy_from_slice(&VestingSchedule::discriminator());
+    }
+    require!(
+        VestingSchedules::is_vesting_schedule(schedule),
+        VsrError::InvalidVestingSchedule
+    );
+
+    let data = schedule.try_borrow_mut_data()?;
+    let mut schedule: RefMut<VestingSchedule> = RefMut::map(data, |data| {
+        bytemuck::from_bytes_mut(&mut data[8..8 + size_of::<VestingSchedule>()])
+    });
+    *schedule = VestingSchedule::default();
+    schedule.voter = *voter;
+    schedule.deposit_entry_index = deposit_entry_index;
+    schedule.bump = bump;
+    Ok(schedule)
+}
+
 /// Gives a grant to a voter.
 ///
 /// The voter may or may not exist in advance.
 /// Creates a new deposit entry -- errors if no free ones are available.
+/// Its lockup must be allowed by the voting mint's lockup limits, see
+/// configure_lockup_limits.
+///
+/// For LockupKind::Tranches, `tranches` lists when which part of the grant
+/// vests, sorted by unlock time and summing to `amount`. `start_ts`, `periods`
+/// and `cliff_periods` must be unset, and the vesting schedule account must
+/// be passed. Each tranche is locked like a cliff lockup ending at its unlock
+/// time. If transfer fees reduce the received amount, the last tranches are
+/// reduced to match. For other lockup kinds `tranches` must be empty.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards(). So must the
+/// VestingSchedules of the voter's other Tranches deposits if it has reward
+/// positions.
 #[allow(clippy::too_many_arguments)]
 pub fn grant(
     ctx: Context<Grant>,
@@ -103,8 +233,10 @@ pub fn grant(
     kind: LockupKind,
     start_ts: Option<u64>,
     periods: u32,
+    cliff_periods: u32,
     allow_clawback: bool,
     amount: u64,
+    tranches: Vec<Tranche>,
 ) -> Result<()> {
     require_eq!(voter_bump, *ctx.bumps.get("voter").unwrap());
     require_eq!(
@@ -113,12 +245,29 @@ pub fn grant(
     );
 
     // Load accounts.
-    let registrar = &ctx.accounts.registrar.load()?;
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
     let voter_authority = ctx.accounts.voter_authority.key();
 
+    let deposit_token = unpack_token_account(&ctx.accounts.deposit_token)?;
+    require_keys_eq!(
+        deposit_token.owner,
+        ctx.accounts.token_authority.key(),
+        VsrError::InvalidAuthority
+    );
+    require_keys_eq!(
+        deposit_token.mint,
+        ctx.accounts.deposit_mint.key(),
+        VsrError::InvalidMint
+    );
+
     // Get the exchange rate entry associated with this deposit.
-    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_token.mint)?;
-    let mint_config = &registrar.voting_mints[mint_idx];
+    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+    let mint_config = registrar.voting_mint(mint_idx);
+    mint_config.check_not_retired()?;
 
     // The grant instruction creates a new deposit entry for the target voter. This is a
     // limited resource. If anyone could call "grant" then it could be used for denial of
@@ -134,9 +283,9 @@ pub fn grant(
     // Init the voter if it hasn't been already.
     let new_voter = is_freshly_initialized(ctx.accounts.voter.as_ref())?;
     let mut voter = if new_voter {
-        ctx.accounts.voter.load_init()?
+        ctx.accounts.voter.load_full_init()?
     } else {
-        ctx.accounts.voter.load_mut()?
+        ctx.accounts.voter.load_full_mut()?
     };
     if new_voter {
         voter.voter_bump = voter_bump;
@@ -156,12 +305,20 @@ pub fn grant(
     }
 
     // Get and init the first free deposit entry.
+    let mint_stats_before = voter.mint_stats(mint_idx);
     let free_entry_idx = voter
-        .deposits
-        .iter()
+        .deposits()
         .position(|d_entry| !d_entry.is_used)
         .ok_or(VsrError::DepositEntryFull)?;
-    let d_entry = &mut voter.deposits[free_entry_idx];
+    let d_entry = voter.deposit_mut(free_entry_idx as u8)?;
+
+    // Tranches lockups are defined by their schedule alone.
+    if kind == LockupKind::Tranches {
+        require!(
+            start_ts.is_none() && periods == 0 && cliff_periods == 0,
+            VsrError::InvalidVestingSchedule
+        );
+    }
 
     let curr_ts = registrar.clock_unix_timestamp();
     let start_ts = if let Some(v) = start_ts {
@@ -175,20 +332,84 @@ pub fn grant(
     d_entry.is_used = true;
     d_entry.voting_mint_config_idx = mint_idx as u8;
     d_entry.allow_clawback = allow_clawback;
-    d_entry.lockup = Lockup::new_from_periods(kind, curr_ts, start_ts, periods)?;
+    let mut schedule = if kind == LockupKind::Tranches {
+        let schedule = ctx
+            .accounts
+            .vesting_schedule
+            .as_ref()
+            .ok_or_else(|| error!(VsrError::MissingVestingSchedule))?;
+        let mut schedule = init_vesting_schedule(
+            schedule.as_ref(),
+            &ctx.accounts.voter.key(),
+            free_entry_idx as u8,
+            ctx.accounts.payer.as_ref(),
+            ctx.accounts.system_program.as_ref(),
+        )?;
+        let total = schedule.set_tranches(&tranches)?;
+        require_eq!(total, amount, VsrError::InvalidVestingSchedule);
+        d_entry.lockup = Lockup::new_from_tranches(curr_ts, &schedule)?;
+        Some(schedule)
+    } else {
+        require!(tranches.is_empty(), VsrError::InvalidVestingSchedule);
+        d_entry.lockup =
+            Lockup::new_from_periods_with_cliff(kind, curr_ts, start_ts, periods, cliff_periods)?;
+        None
+    };
+    mint_config.check_lockup(&d_entry.lockup, curr_ts)?;
 
     // Deposit tokens, locking them all.
-    token::transfer(ctx.accounts.transfer_ctx(), amount)?;
-    d_entry.amount_deposited_native = amount;
-    d_entry.amount_initially_locked_native = amount;
+    // Transfer fees may mean that less than `amount` arrives in the vault.
+    create_vault_if_needed(
+        &ctx.accounts.payer,
+        &ctx.accounts.vault,
+        ctx.accounts.voter.as_ref(),
+        &ctx.accounts.deposit_mint,
+        ctx.accounts.system_program.as_ref(),
+        &ctx.accounts.token_program,
+        ctx.accounts.associated_token_program.as_ref(),
+    )?;
+    let vault_before = unpack_token_account(&ctx.accounts.vault)?.amount;
+    transfer_checked(ctx.accounts.transfer_ctx(), amount)?;
+    let vault_after = unpack_token_account(&ctx.accounts.vault)?.amount;
+    let received = vault_after.checked_sub(vault_before).unwrap();
+    d_entry.amount_deposited_native = received;
+    d_entry.amount_initially_locked_native = received;
+    if let Some(schedule) = schedule.as_mut() {
+        schedule.remove_from_end(amount.checked_sub(received).unwrap())?;
+    }
 
     msg!(
         "Granted amount {} at deposit index {} with lockup kind {:?} for {} periods",
-        amount,
+        received,
         free_entry_idx,
         d_entry.lockup.kind,
         periods,
     );
 
+    // Update the registrar's deposit totals.
+    let registrar_mint_stats = update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    // Enforce the voting mint's deposit limits.
+    mint_config.check_min_deposit(received)?;
+    mint_config.check_max_deposited(
+        voter.mint_stats(mint_idx).deposited_native,
+        registrar_mint_stats.map(|stats| stats.deposited_native),
+    )?;
+
+    // Rewards accrue at the new locked vote weight from now on, including
+    // the one of the granted tranches.
+    drop(schedule);
+    let mut accounts = ctx.remaining_accounts.to_vec();
+    if let Some(vesting_schedule) = ctx.accounts.vesting_schedule.as_ref() {
+        accounts.push(vesting_schedule.to_account_info());
+    }
+    sync_voter_rewards(&ctx.accounts.voter.key(), &voter, registrar, &accounts)?;
+
     Ok(())
 }
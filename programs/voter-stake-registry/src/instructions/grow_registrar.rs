@@ -0,0 +1,48 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use std::mem::size_of;
+
+#[derive(Accounts)]
+#[instruction(voting_mint_slots: u8)]
+pub struct GrowRegistrar<'info> {
+    #[account(
+        mut,
+        has_one = realm_authority,
+        realloc = registrar.as_ref().data_len() + voting_mint_slots as usize * size_of::<VotingMintConfig>(),
+        realloc::payer = payer,
+        realloc::zero = true,
+    )]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+
+    /// Pays for the rent of the additional space
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Adds `voting_mint_slots` unused voting mint slots to the registrar.
+///
+/// The registrar account is reallocated to make space for them. New slots get
+/// the next indexes and are set up with configure_voting_mint like the initial
+/// ones.
+///
+/// A registrar can have at most MAX_VOTING_MINTS slots and one call can grow the
+/// account by at most 10240 bytes.
+pub fn grow_registrar(ctx: Context<GrowRegistrar>, _voting_mint_slots: u8) -> Result<()> {
+    let registrar = &ctx.accounts.registrar.load_full()?;
+    require_gte!(
+        MAX_VOTING_MINTS,
+        registrar.voting_mints_len(),
+        VsrError::TooManyVotingMints
+    );
+
+    msg!(
+        "Registrar now has {} voting mint slots",
+        registrar.voting_mints_len()
+    );
+
+    Ok(())
+}
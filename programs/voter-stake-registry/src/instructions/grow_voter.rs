@@ -0,0 +1,48 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use std::mem::size_of;
+
+#[derive(Accounts)]
+#[instruction(deposit_entry_slots: u8)]
+pub struct GrowVoter<'info> {
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [voter.load()?.registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority,
+        realloc = voter.as_ref().data_len() + deposit_entry_slots as usize * size_of::<DepositEntry>(),
+        realloc::payer = voter_authority,
+        realloc::zero = true,
+    )]
+    pub voter: AccountLoader<'info, Voter>,
+
+    /// Pays for the rent of the additional space
+    #[account(mut)]
+    pub voter_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Adds `deposit_entry_slots` unused deposit entries to the voter.
+///
+/// The voter account is reallocated to make space for them, paid for by the
+/// voter authority. New entries get the next indexes and can be used like the
+/// initial ones.
+///
+/// A voter can have at most MAX_DEPOSIT_ENTRIES entries and one call can grow
+/// the account by at most 10240 bytes.
+pub fn grow_voter(ctx: Context<GrowVoter>, _deposit_entry_slots: u8) -> Result<()> {
+    let voter = &ctx.accounts.voter.load_full()?;
+    require_gte!(
+        MAX_DEPOSIT_ENTRIES,
+        voter.deposits_len(),
+        VsrError::TooManyDepositEntries
+    );
+
+    msg!("Voter now has {} deposit entries", voter.deposits_len());
+
+    Ok(())
+}
@@ -6,6 +6,11 @@ use anchor_lang::prelude::*;
 pub struct InternalTransferLocked<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     // checking the PDA address it just an extra precaution,
     // the other constraints must be exhaustive
     #[account(
@@ -31,19 +36,37 @@ pub struct InternalTransferLocked<'info> {
 /// - transfering a small part of a big "constant" lockup deposit entry into a "cliff"
 ///   locked deposit entry to start the unlocking process (reset_lockup could only
 ///   change the whole deposit entry to "cliff")
+///
+/// The target's lockup must be allowed by the voting mint's lockup limits, see
+/// configure_lockup_limits.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
 pub fn internal_transfer_locked(
     ctx: Context<InternalTransferLocked>,
     source_deposit_entry_index: u8,
     target_deposit_entry_index: u8,
     amount: u64,
 ) -> Result<()> {
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
     let curr_ts = registrar.clock_unix_timestamp();
 
+    let mint_idx = voter
+        .active_deposit_mut(source_deposit_entry_index)?
+        .voting_mint_config_idx as usize;
+    let mint_stats_before = voter.mint_stats(mint_idx);
+
     let source = voter.active_deposit_mut(source_deposit_entry_index)?;
     source.resolve_vesting(curr_ts)?;
     let source_seconds_left = source.lockup.seconds_left(curr_ts);
+    let source_cliff_seconds_left = source.lockup.cliff_seconds_left(curr_ts);
     let source_strictness = source.lockup.kind.strictness();
     let source_mint_idx = source.voting_mint_config_idx;
 
@@ -77,11 +100,19 @@ pub fn internal_transfer_locked(
         source_seconds_left,
         VsrError::InvalidLockupPeriod
     );
+    require_gte!(
+        target.lockup.cliff_seconds_left(curr_ts),
+        source_cliff_seconds_left,
+        VsrError::InvalidLockupPeriod
+    );
     require_gte!(
         target.lockup.kind.strictness(),
         source_strictness,
         VsrError::InvalidLockupKind
     );
+    registrar
+        .voting_mint(mint_idx)
+        .check_lockup(&target.lockup, curr_ts)?;
 
     // Add target amounts
     target.amount_deposited_native = target.amount_deposited_native.checked_add(amount).unwrap();
@@ -90,5 +121,28 @@ pub fn internal_transfer_locked(
         .checked_add(amount)
         .unwrap();
 
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
     Ok(())
 }
@@ -31,16 +31,24 @@ pub fn internal_transfer_unlocked(
     target_deposit_entry_index: u8,
     amount: u64,
 ) -> Result<()> {
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx.accounts.registrar.load_full()?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
     let curr_ts = registrar.clock_unix_timestamp();
 
     let source = voter.active_deposit_mut(source_deposit_entry_index)?;
     let source_mint_idx = source.voting_mint_config_idx;
 
+    // Unlocked tokens of Tranches deposits can only be withdrawn, since
+    // computing them needs the VestingSchedule.
+    require!(
+        source.lockup.kind != LockupKind::Tranches,
+        VsrError::InvalidLockupKind
+    );
+
     // Reduce source amounts
     require_gte!(
-        source.amount_unlocked(curr_ts),
+        source.amount_unlocked(curr_ts, None),
         amount,
         VsrError::InsufficientUnlockedTokens
     );
@@ -53,6 +61,9 @@ pub fn internal_transfer_unlocked(
         source_mint_idx,
         VsrError::InvalidMint
     );
+    registrar
+        .voting_mint(source_mint_idx as usize)
+        .check_min_deposit(amount)?;
 
     // Add target amounts
     target.amount_deposited_native = target.amount_deposited_native.checked_add(amount).unwrap();
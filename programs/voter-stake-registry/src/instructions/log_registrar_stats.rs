@@ -0,0 +1,32 @@
+use crate::events::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct LogRegistrarStats<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar)]
+    pub registrar_stats: AccountLoader<'info, RegistrarStats>,
+}
+
+/// A no-effect instruction that logs the deposit totals of all voting mints.
+pub fn log_registrar_stats(ctx: Context<LogRegistrarStats>) -> Result<()> {
+    let registrar = &ctx.accounts.registrar.load_full()?;
+    let stats = &ctx.accounts.registrar_stats.load()?;
+
+    for (index, voting_mint) in registrar.voting_mints().enumerate() {
+        if !voting_mint.in_use() {
+            continue;
+        }
+        let mint_stats = &stats.voting_mints[index];
+        emit!(VotingMintStatsInfo {
+            voting_mint_config_index: index as u8,
+            mint: voting_mint.mint,
+            deposited_native: mint_stats.deposited_native,
+            initially_locked_native: mint_stats.initially_locked_native,
+            deposit_entries: mint_stats.deposit_entries,
+        });
+    }
+    Ok(())
+}
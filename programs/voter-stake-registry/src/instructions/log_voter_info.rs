@@ -17,59 +17,71 @@ pub struct LogVoterInfo<'info> {
 ///
 /// With the current setup, all information about deposits can be logged by calling
 /// this with deposit_entry_begin=0, =8, =16, =24 and deposit_entry_count=8.
+/// Voters that were grown with grow_voter have further entries starting at index 32.
+///
+/// The VestingSchedule accounts of deposits with LockupKind::Tranches must be
+/// passed as remaining accounts.
 pub fn log_voter_info(
     ctx: Context<LogVoterInfo>,
     deposit_entry_begin: u8,
     deposit_entry_count: u8,
 ) -> Result<()> {
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = ctx.accounts.voter.load()?;
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = ctx.accounts.voter.load_full()?;
+    let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
     let curr_ts = registrar.clock_unix_timestamp();
     let deposit_entry_begin = deposit_entry_begin as usize;
     let deposit_entry_count = deposit_entry_count as usize;
 
     msg!("voter");
     emit!(VoterInfo {
-        voting_power: voter.weight(registrar)?,
+        voting_power: voter.weight(registrar, &schedules)?,
         voting_power_baseline: voter.weight_baseline(registrar)?,
     });
 
     msg!("deposit_entries");
-    for (deposit_index, deposit) in voter.deposits.iter().enumerate() {
+    for (deposit_index, deposit) in voter.deposits().enumerate() {
         if !deposit.is_used
             || deposit_index < deposit_entry_begin
             || deposit_index >= deposit_entry_begin + deposit_entry_count
         {
             continue;
         }
+        let schedule = schedules.for_deposit(deposit_index, deposit)?;
         let lockup = &deposit.lockup;
         let seconds_left = lockup.seconds_left(curr_ts);
         let end_ts = curr_ts as u64 + seconds_left;
         let periods_total = lockup.periods_total()?;
         let periods_left = lockup.periods_left(curr_ts)?;
-        let voting_mint_config = &registrar.voting_mints[deposit.voting_mint_config_idx as usize];
+        let voting_mint_config =
+            registrar.vote_weight_config(deposit.voting_mint_config_idx as usize)?;
         let locking_info = (seconds_left > 0).then(|| LockingInfo {
-            amount: deposit.amount_locked(curr_ts),
+            amount: deposit.amount_locked(curr_ts, schedule),
             end_timestamp: (lockup.kind != LockupKind::Constant).then_some(end_ts),
             vesting: lockup.kind.is_vesting().then(|| VestingInfo {
                 rate: deposit
                     .amount_initially_locked_native
                     .checked_div(periods_total)
                     .unwrap(),
-                next_timestamp: end_ts.saturating_sub(
-                    periods_left
-                        .saturating_sub(1)
-                        .checked_mul(lockup.kind.period_secs())
-                        .unwrap(),
-                ),
+                next_timestamp: end_ts
+                    .saturating_sub(
+                        periods_left
+                            .saturating_sub(1)
+                            .checked_mul(lockup.kind.period_secs())
+                            .unwrap(),
+                    )
+                    .max(lockup.cliff_end_ts() as u64),
             }),
         });
 
         emit!(DepositEntryInfo {
             deposit_entry_index: deposit_index as u8,
             voting_mint_config_index: deposit.voting_mint_config_idx,
-            unlocked: deposit.amount_unlocked(curr_ts),
-            voting_power: deposit.voting_power(voting_mint_config, curr_ts)?,
+            unlocked: deposit.amount_unlocked(curr_ts, schedule),
+            voting_power: deposit.voting_power(&voting_mint_config, curr_ts, schedule)?,
             voting_power_baseline: voting_mint_config
                 .baseline_vote_weight(deposit.amount_deposited_native)?,
             locking: locking_info,
@@ -0,0 +1,103 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MergeDepositEntries<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority,
+        has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+}
+
+/// Moves all tokens of the source deposit entry into the target deposit entry
+/// and frees the source entry.
+///
+/// Both entries must be of the same mint, lockup kind and end time, see
+/// DepositEntry::merge(). Tranches and clawback deposit entries can't be
+/// merged.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
+pub fn merge_deposit_entries(
+    ctx: Context<MergeDepositEntries>,
+    source_deposit_entry_index: u8,
+    target_deposit_entry_index: u8,
+) -> Result<()> {
+    require_neq!(
+        source_deposit_entry_index,
+        target_deposit_entry_index,
+        VsrError::SameDepositEntry
+    );
+
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let curr_ts = registrar.clock_unix_timestamp();
+
+    let mint_idx = voter
+        .active_deposit_mut(source_deposit_entry_index)?
+        .voting_mint_config_idx as usize;
+    let mint_stats_before = voter.mint_stats(mint_idx);
+
+    // Merging clawback-enabled deposits could be used to avoid clawback,
+    // see internal_transfer_locked.
+    let source = voter.active_deposit_mut(source_deposit_entry_index)?;
+    require!(
+        !source.allow_clawback,
+        VsrError::InvalidChangeToClawbackDepositEntry
+    );
+    let merged = *source;
+    *source = DepositEntry::default();
+    source.is_used = false;
+
+    let target = voter.active_deposit_mut(target_deposit_entry_index)?;
+    require!(
+        !target.allow_clawback,
+        VsrError::InvalidChangeToClawbackDepositEntry
+    );
+    target.merge(merged, curr_ts)?;
+
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
@@ -1,37 +1,105 @@
+pub use accept_voter_authority::*;
+pub use claim_reward::*;
 pub use clawback::*;
+pub use clear_voting_mint::*;
 pub use close_deposit_entry::*;
 pub use close_voter::*;
+pub use close_voter_reward::*;
+pub use close_voter_weight_checkpoints::*;
+pub use configure_deposit_limits::*;
+pub use configure_lockup_limits::*;
+pub use configure_predecessor_plugin::*;
 pub use configure_voting_mint::*;
+pub use configure_voting_mint_rate::*;
 pub use create_deposit_entry::*;
 pub use create_registrar::*;
+pub use create_registrar_stats::*;
+pub use create_reward_distribution::*;
 pub use create_voter::*;
+pub use create_voter_reward::*;
+pub use create_voter_weight_checkpoints::*;
 pub use deposit::*;
+pub use early_unlock::*;
+pub use external_transfer_locked::*;
+pub use fund_reward_distribution::*;
 pub use grant::*;
+pub use grow_registrar::*;
+pub use grow_voter::*;
 pub use internal_transfer_locked::*;
 pub use internal_transfer_unlocked::*;
+pub use log_registrar_stats::*;
 pub use log_voter_info::*;
+pub use merge_deposit_entries::*;
+pub use propose_voter_authority::*;
+pub use relock_deposit_entry::*;
 pub use reset_lockup::*;
+pub use retire_voting_mint::*;
+pub use set_delegate::*;
+pub use set_early_unlock_treasury::*;
+pub use set_historical_voter_weight::*;
+pub use set_pause_guardian::*;
+pub use set_paused::*;
 pub use set_time_offset::*;
+pub use set_voting_mint_rate::*;
+pub use split_deposit_entry::*;
+pub use start_unlocking::*;
+pub use stop_unlocking::*;
 pub use unlock_deposit::*;
+pub use update_historical_voter_weight_record::*;
 pub use update_max_vote_weight::*;
+pub use update_voter_rewards::*;
 pub use update_voter_weight_record::*;
 pub use withdraw::*;
 
+mod accept_voter_authority;
+mod claim_reward;
 mod clawback;
+mod clear_voting_mint;
 mod close_deposit_entry;
 mod close_voter;
+mod close_voter_reward;
+mod close_voter_weight_checkpoints;
+mod configure_deposit_limits;
+mod configure_lockup_limits;
+mod configure_predecessor_plugin;
 mod configure_voting_mint;
+mod configure_voting_mint_rate;
 mod create_deposit_entry;
 mod create_registrar;
+mod create_registrar_stats;
+mod create_reward_distribution;
 mod create_voter;
+mod create_voter_reward;
+mod create_voter_weight_checkpoints;
 mod deposit;
+mod early_unlock;
+mod external_transfer_locked;
+mod fund_reward_distribution;
 mod grant;
+mod grow_registrar;
+mod grow_voter;
 mod internal_transfer_locked;
 mod internal_transfer_unlocked;
+mod log_registrar_stats;
 mod log_voter_info;
+mod merge_deposit_entries;
+mod propose_voter_authority;
+mod relock_deposit_entry;
 mod reset_lockup;
+mod retire_voting_mint;
+mod set_delegate;
+mod set_early_unlock_treasury;
+mod set_historical_voter_weight;
+mod set_pause_guardian;
+mod set_paused;
 mod set_time_offset;
+mod set_voting_mint_rate;
+mod split_deposit_entry;
+mod start_unlocking;
+mod stop_unlocking;
 mod unlock_deposit;
+mod update_historical_voter_weight_record;
 mod update_max_vote_weight;
+mod update_voter_rewards;
 mod update_voter_weight_record;
 mod withdraw;
@@ -0,0 +1,37 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeVoterAuthority<'info> {
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [voter.load()?.registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority,
+    )]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+}
+
+/// Proposes to transfer the voter to `new_authority`, who completes the
+/// transfer with accept_voter_authority.
+///
+/// Pass the default pubkey to cancel a pending transfer. Proposing again
+/// replaces the pending authority.
+pub fn propose_voter_authority(
+    ctx: Context<ProposeVoterAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    let voter = &mut ctx.accounts.voter.load_mut()?;
+    voter.pending_authority = new_authority;
+
+    msg!(
+        "Voter {} proposed {} as new authority",
+        voter.voter_authority,
+        new_authority
+    );
+
+    Ok(())
+}
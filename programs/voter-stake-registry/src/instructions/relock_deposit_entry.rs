@@ -0,0 +1,158 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RelockDepositEntry<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority,
+        has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+}
+
+/// Moves `amount` native tokens of the source deposit entry into the target
+/// deposit entry and locks all of them for `periods` starting at the current
+/// slot timestamp. Like reset_lockup for only part of an entry: the rest of
+/// the source deposit entry keeps its lockup and unlocked tokens.
+///
+/// The new lockup must not be shorter, less strict or have a shorter cliff
+/// than the source's, see Lockup::relocked().
+///
+/// If the target deposit entry is unused, it gets the new lockup. Otherwise
+/// its lockup must be at least as long and strict as the new lockup and the
+/// moved tokens join it, like in internal_transfer_locked.
+///
+/// Tranches and clawback deposit entries can't be relocked.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
+pub fn relock_deposit_entry(
+    ctx: Context<RelockDepositEntry>,
+    source_deposit_entry_index: u8,
+    target_deposit_entry_index: u8,
+    amount: u64,
+    kind: LockupKind,
+    periods: u32,
+) -> Result<()> {
+    require_neq!(
+        source_deposit_entry_index,
+        target_deposit_entry_index,
+        VsrError::SameDepositEntry
+    );
+
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let curr_ts = registrar.clock_unix_timestamp();
+
+    let mint_idx = voter
+        .active_deposit_mut(source_deposit_entry_index)?
+        .voting_mint_config_idx as usize;
+    let mint_stats_before = voter.mint_stats(mint_idx);
+
+    let source = voter.active_deposit_mut(source_deposit_entry_index)?;
+
+    // Don't re-lock clawback deposits, see reset_lockup.
+    require!(
+        !source.allow_clawback,
+        VsrError::InvalidChangeToClawbackDepositEntry
+    );
+    let mut relocked = source.split(amount, curr_ts)?;
+
+    // Must not decrease duration, strictness or cliff
+    let lockup = relocked.lockup.relocked(kind, periods, curr_ts)?;
+    relocked.lockup = lockup;
+    relocked.amount_initially_locked_native = relocked.amount_deposited_native;
+
+    let target = voter.deposit_mut(target_deposit_entry_index)?;
+    if !target.is_used {
+        registrar.voting_mint(mint_idx).check_not_retired()?;
+        registrar
+            .voting_mint(mint_idx)
+            .check_lockup(&lockup, curr_ts)?;
+        *target = relocked;
+    } else {
+        // Check target compatibility
+        require!(
+            !target.allow_clawback,
+            VsrError::InvalidChangeToClawbackDepositEntry
+        );
+        target.resolve_vesting(curr_ts)?;
+        require_eq!(
+            target.voting_mint_config_idx as usize,
+            mint_idx,
+            VsrError::InvalidMint
+        );
+        require_gte!(
+            target.lockup.seconds_left(curr_ts),
+            lockup.seconds_left(curr_ts),
+            VsrError::InvalidLockupPeriod
+        );
+        require_gte!(
+            target.lockup.cliff_seconds_left(curr_ts),
+            lockup.cliff_seconds_left(curr_ts),
+            VsrError::InvalidLockupPeriod
+        );
+        require_gte!(
+            target.lockup.kind.strictness(),
+            lockup.kind.strictness(),
+            VsrError::InvalidLockupKind
+        );
+        registrar
+            .voting_mint(mint_idx)
+            .check_lockup(&target.lockup, curr_ts)?;
+
+        // Add target amounts
+        target.amount_deposited_native = target
+            .amount_deposited_native
+            .checked_add(relocked.amount_deposited_native)
+            .unwrap();
+        target.amount_initially_locked_native = target
+            .amount_initially_locked_native
+            .checked_add(relocked.amount_deposited_native)
+            .unwrap();
+    }
+
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
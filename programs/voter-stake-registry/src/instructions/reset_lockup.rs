@@ -6,6 +6,11 @@ use anchor_lang::prelude::*;
 pub struct ResetLockup<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     // checking the PDA address it just an extra precaution,
     // the other constraints must be exhaustive
     #[account(
@@ -21,29 +26,37 @@ pub struct ResetLockup<'info> {
 /// Resets a lockup to start at the current slot timestamp and to last for
 /// `periods`, which must be >= the number of periods left on the lockup.
 /// This will re-lock any non-withdrawn vested funds.
+///
+/// When resetting to MonthlyWithCliff, the cliff of the new lockup covers what
+/// is left of the previous cliff, rounded up to full periods.
+///
+/// The new lockup must be allowed by the voting mint's lockup limits, see
+/// configure_lockup_limits.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
 pub fn reset_lockup(
     ctx: Context<ResetLockup>,
     deposit_entry_index: u8,
     kind: LockupKind,
     periods: u32,
 ) -> Result<()> {
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
     let curr_ts = registrar.clock_unix_timestamp();
 
-    let source = voter.active_deposit_mut(deposit_entry_index)?;
+    let mint_idx = voter
+        .active_deposit_mut(deposit_entry_index)?
+        .voting_mint_config_idx as usize;
+    let mint_stats_before = voter.mint_stats(mint_idx);
 
-    // Must not decrease duration or strictness
-    require_gte!(
-        (periods as u64).checked_mul(kind.period_secs()).unwrap(),
-        source.lockup.seconds_left(curr_ts),
-        VsrError::InvalidLockupPeriod
-    );
-    require_gte!(
-        kind.strictness(),
-        source.lockup.kind.strictness(),
-        VsrError::InvalidLockupKind
-    );
+    let source = voter.active_deposit_mut(deposit_entry_index)?;
 
     // Don't re-lock clawback deposits. Users must withdraw and create a new one.
     require!(
@@ -51,10 +64,39 @@ pub fn reset_lockup(
         VsrError::InvalidChangeToClawbackDepositEntry
     );
 
+    // Must not decrease duration, strictness or cliff
+    let lockup = source.lockup.relocked(kind, periods, curr_ts)?;
+    registrar
+        .voting_mint(mint_idx)
+        .check_lockup(&lockup, curr_ts)?;
+
     // Change the deposit entry.
     let d_entry = voter.active_deposit_mut(deposit_entry_index)?;
     d_entry.amount_initially_locked_native = d_entry.amount_deposited_native;
-    d_entry.lockup = Lockup::new_from_periods(kind, curr_ts, curr_ts, periods)?;
+    d_entry.lockup = lockup;
+
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
 
     Ok(())
 }
@@ -0,0 +1,35 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RetireVotingMint<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Retires the voting mint at index `idx`, the first step of removing it.
+///
+/// Retired mints take no new deposits or grants, and their deposits have no
+/// vote weight anymore: the vote weight factors are set to zero. Voters can
+/// still withdraw and close their deposit entries. Once all are closed, the
+/// slot can be cleared with clear_voting_mint.
+///
+/// Retiring can't be undone.
+pub fn retire_voting_mint(ctx: Context<RetireVotingMint>, idx: u16) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_full_mut()?;
+    let idx = idx as usize;
+    require_gt!(
+        registrar.voting_mints_len(),
+        idx,
+        VsrError::OutOfBoundsVotingMintConfigIndex
+    );
+    let mint_config = registrar.voting_mint_mut(idx);
+    require!(mint_config.in_use(), VsrError::MintConfigNotUsed);
+
+    mint_config.retired = true;
+    mint_config.baseline_vote_weight_scaled_factor = 0;
+    mint_config.max_extra_lockup_vote_weight_scaled_factor = 0;
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar,
+        has_one = voter_authority,
+    )]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+
+    /// The token_owner_record for the voter_authority. The delegate can't
+    /// change while the voter's own weight is engaged in a vote.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is voter_authority
+    pub token_owner_record: UncheckedAccount<'info>,
+
+    /// The voter's weight moves to or from the delegate, so the voter's
+    /// current record is reset.
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.voter_authority,
+        constraint = voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    /// The voter of the new delegate. None to revoke the delegation.
+    #[account(mut, has_one = registrar)]
+    pub new_delegate_voter: Option<AccountLoader<'info, Voter>>,
+
+    /// The voter of the previous delegate, required if there is one.
+    #[account(
+        mut,
+        has_one = registrar,
+        constraint = previous_delegate_voter.load()?.voter_authority == voter.load()?.delegate,
+    )]
+    pub previous_delegate_voter: Option<AccountLoader<'info, Voter>>,
+
+    /// The token_owner_record for the previous delegate, required if
+    /// there is one. The delegate can't change while the delegated
+    /// weight is engaged in a vote.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is the previous delegate
+    pub previous_delegate_token_owner_record: Option<UncheckedAccount<'info>>,
+
+    /// The voter weight record of the previous delegate, required if
+    /// there is one. It is reset, since it may include the voter's weight.
+    #[account(
+        mut,
+        constraint = previous_delegate_voter_weight_record.realm == registrar.load()?.realm,
+        constraint = previous_delegate_voter_weight_record.governing_token_owner == voter.load()?.delegate,
+        constraint = previous_delegate_voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub previous_delegate_voter_weight_record: Option<Account<'info, VoterWeightRecord>>,
+}
+
+/// Delegates the voter's vote weight to another voter, or revokes the delegation.
+///
+/// While delegated, the voter's own voter weight record has zero weight and the
+/// delegate's record includes the voter's weight, see update_voter_weight_record.
+/// Delegated weight is not passed on further if the delegate delegates too.
+///
+/// The delegate can only be changed if neither the voter nor the previous
+/// delegate have votes or proposals that would prevent withdrawing.
+///
+/// The delegates' Voter::delegators counts are updated, which keeps delegates
+/// from being transferred or closed while voters delegate to them.
+///
+/// If the voter has a VoterWeightCheckpoints account, it must be passed as a
+/// remaining account together with the VestingSchedule accounts of the voter's
+/// deposits with LockupKind::Tranches, see record_weight_checkpoint().
+pub fn set_delegate(ctx: Context<SetDelegate>) -> Result<()> {
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let curr_slot = Clock::get()?.slot;
+
+    let token_owner_record = voter.load_token_owner_record(
+        &ctx.accounts.token_owner_record.to_account_info(),
+        registrar,
+    )?;
+    token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+    if voter.has_delegate() {
+        let previous_token_owner_record = ctx
+            .accounts
+            .previous_delegate_token_owner_record
+            .as_ref()
+            .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+        let previous_token_owner_record = voter.load_delegate_token_owner_record(
+            &previous_token_owner_record.to_account_info(),
+            registrar,
+        )?;
+        previous_token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+        let previous_record = ctx
+            .accounts
+            .previous_delegate_voter_weight_record
+            .as_mut()
+            .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+        previous_record.voter_weight = 0;
+        previous_record.voter_weight_expiry = Some(curr_slot);
+
+        let previous_voter = ctx
+            .accounts
+            .previous_delegate_voter
+            .as_ref()
+            .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+        let previous_voter = &mut previous_voter.load_mut()?;
+        previous_voter.delegators = previous_voter.delegators.checked_sub(1).unwrap();
+    }
+
+    voter.delegate = match &ctx.accounts.new_delegate_voter {
+        Some(new_delegate_voter) => {
+            require_keys_neq!(
+                new_delegate_voter.key(),
+                ctx.accounts.voter.key(),
+                VsrError::InvalidDelegate
+            );
+            let new_voter = &mut new_delegate_voter.load_mut()?;
+            new_voter.delegators = new_voter.delegators.checked_add(1).unwrap();
+            new_voter.voter_authority
+        }
+        None => Pubkey::default(),
+    };
+
+    let record = &mut ctx.accounts.voter_weight_record;
+    record.voter_weight = 0;
+    record.voter_weight_expiry = Some(curr_slot);
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    msg!("Voter delegate set to {}", voter.delegate);
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetEarlyUnlockTreasury<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Sets the owner of the token accounts that receive early unlock penalties.
+///
+/// `early_unlock_treasury`: The new treasury owner, for example the realm's
+/// native treasury. Pass the default pubkey to disable early unlocks.
+///
+/// The penalty rate is configured per voting mint, see configure_voting_mint.
+pub fn set_early_unlock_treasury(
+    ctx: Context<SetEarlyUnlockTreasury>,
+    early_unlock_treasury: Pubkey,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    registrar.early_unlock_treasury = early_unlock_treasury;
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetHistoricalVoterWeight<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Picks how voter weight records are written, see
+/// Registrar::historical_voter_weight.
+///
+/// `historical_voter_weight`: Whether votes use the voters' weight at a past
+/// time, with update_historical_voter_weight_record, instead of their current
+/// weight, with update_voter_weight_record.
+///
+/// Voters need weight checkpoints reaching back to the times asked for, see
+/// create_voter_weight_checkpoints.
+pub fn set_historical_voter_weight(
+    ctx: Context<SetHistoricalVoterWeight>,
+    historical_voter_weight: bool,
+) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    registrar.historical_voter_weight = historical_voter_weight;
+    Ok(())
+}
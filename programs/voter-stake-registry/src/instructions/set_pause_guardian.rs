@@ -0,0 +1,20 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPauseGuardian<'info> {
+    #[account(mut, has_one = realm_authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm_authority: Signer<'info>,
+}
+
+/// Sets the key that can pause and unpause the registrar besides the realm
+/// authority.
+///
+/// `pause_guardian`: The new guardian, for example a multisig that can act
+/// faster than a governance proposal. Pass the default pubkey to remove it.
+pub fn set_pause_guardian(ctx: Context<SetPauseGuardian>, pause_guardian: Pubkey) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    registrar.pause_guardian = pause_guardian;
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::error::*;
+use crate::events::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The realm authority or the registrar's pause guardian.
+    pub authority: Signer<'info>,
+}
+
+/// Pauses or unpauses the registrar, see Registrar::paused.
+///
+/// Meant to halt the program when a bug is found, until it is fixed with
+/// a program upgrade. Can be called by the realm authority or the pause
+/// guardian, see set_pause_guardian.
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    let registrar = &mut ctx.accounts.registrar.load_mut()?;
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == registrar.realm_authority
+            || (registrar.pause_guardian != Pubkey::default()
+                && authority == registrar.pause_guardian),
+        VsrError::InvalidAuthority
+    );
+
+    if registrar.paused != paused {
+        registrar.paused = paused;
+        emit!(RegistrarPausedChanged {
+            registrar: ctx.accounts.registrar.key(),
+            paused,
+            authority,
+        });
+    }
+    Ok(())
+}
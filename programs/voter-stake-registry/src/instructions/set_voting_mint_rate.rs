@@ -0,0 +1,33 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetVotingMintRate<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voting_mint_rate: AccountLoader<'info, VotingMintRate>,
+    pub authority: Signer<'info>,
+}
+
+/// Updates the rate of a priced voting mint, see configure_voting_mint_rate.
+///
+/// `rate`: vote weight per native token before the voting mint's factors,
+/// in 1/1e9 units. Must not exceed the rate's max_rate.
+///
+/// The rate stays valid for max_staleness_secs from now on.
+pub fn set_voting_mint_rate(ctx: Context<SetVotingMintRate>, rate: u64) -> Result<()> {
+    let registrar = &ctx.accounts.registrar.load()?;
+    let voting_mint_rate = &mut ctx.accounts.voting_mint_rate.load_mut()?;
+    if voting_mint_rate.max_rate > 0 {
+        require_gte!(
+            voting_mint_rate.max_rate,
+            rate,
+            VsrError::VotingMintRateTooHigh
+        );
+    }
+    voting_mint_rate.rate = rate;
+    voting_mint_rate.updated_ts = registrar.clock_unix_timestamp();
+    Ok(())
+}
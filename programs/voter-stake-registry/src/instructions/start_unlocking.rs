@@ -0,0 +1,107 @@
+use crate::error::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct StartUnlocking<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = voter_authority,
+        has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub voter_authority: Signer<'info>,
+}
+
+/// Turns `amount` native tokens of a Constant source deposit entry into a
+/// Cliff lockup that ends as many seconds from now as the Constant lockup
+/// has, see Lockup::start_unlocking().
+///
+/// To convert the whole deposit entry, pass it as both source and target with
+/// its full deposited amount. Otherwise `amount` is split off into the unused
+/// target deposit entry, see split_deposit_entry.
+///
+/// This is how voting_power_locked_guaranteed() assumes a voter can unlock
+/// Constant deposits, so the voting mint's lockup limits aren't checked.
+/// Clawback deposit entries can't be changed.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
+pub fn start_unlocking(
+    ctx: Context<StartUnlocking>,
+    source_deposit_entry_index: u8,
+    target_deposit_entry_index: u8,
+    amount: u64,
+) -> Result<()> {
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(!registrar.paused, VsrError::RegistrarPaused);
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let curr_ts = registrar.clock_unix_timestamp();
+
+    let mint_idx = voter
+        .active_deposit_mut(source_deposit_entry_index)?
+        .voting_mint_config_idx as usize;
+    let mint_stats_before = voter.mint_stats(mint_idx);
+
+    let source = voter.active_deposit_mut(source_deposit_entry_index)?;
+    require!(
+        !source.allow_clawback,
+        VsrError::InvalidChangeToClawbackDepositEntry
+    );
+
+    if source_deposit_entry_index == target_deposit_entry_index {
+        require_eq!(
+            amount,
+            source.amount_deposited_native,
+            VsrError::MustChangeWholeDepositEntry
+        );
+        source.lockup.start_unlocking(curr_ts)?;
+    } else {
+        let mut split = source.split(amount, curr_ts)?;
+        split.lockup.start_unlocking(curr_ts)?;
+
+        let target = voter.deposit_mut(target_deposit_entry_index)?;
+        require!(!target.is_used, VsrError::UnusedDepositEntryIndex);
+        *target = split;
+    }
+
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
@@ -22,15 +22,24 @@ pub struct UnlockDeposit<'info> {
     pub grant_authority: Signer<'info>,
 }
 
+/// Makes all tokens in a deposit entry available for immediate withdrawal.
+///
+/// The voter's VoterReward accounts and their RewardDistributions must be
+/// passed as remaining accounts, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
 pub fn unlock_deposit(ctx: Context<UnlockDeposit>, deposit_entry_index: u8) -> Result<()> {
     // Load accounts.
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
 
     let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
     // Get the grant_authority for the DepositEntry
     let mint_idx = deposit_entry.voting_mint_config_idx;
-    let mint_config: &VotingMintConfig = &registrar.voting_mints[mint_idx as usize];
+    let mint_config: &VotingMintConfig = registrar.voting_mint(mint_idx as usize);
     let grant_authority = ctx.accounts.grant_authority.key();
 
     // Validate the VotingMintConfig was initialized and is in use
@@ -47,5 +56,19 @@ pub fn unlock_deposit(ctx: Context<UnlockDeposit>, deposit_entry_index: u8) -> R
     // Change the DepositEntry to unlock all unvested tokens
     deposit_entry.unlock_deposit();
 
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,103 @@
+use crate::error::*;
+use crate::instructions::load_predecessor_weight;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+// Remaining accounts must be the predecessor plugin's VoterWeightRecord
+// if the registrar has a predecessor plugin.
+#[derive(Accounts)]
+pub struct UpdateHistoricalVoterWeightRecord<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // checking the PDA address it just an extra precaution,
+    // the other constraints must be exhaustive
+    #[account(
+        seeds = [registrar.key().as_ref(), b"voter".as_ref(), voter.load()?.voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_bump,
+        has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(has_one = voter)]
+    pub checkpoints: AccountLoader<'info, VoterWeightCheckpoints>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), b"voter-weight-record".as_ref(), voter.load()?.voter_authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.voter_authority,
+        constraint = voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+}
+
+/// Writes the voter's weight at a past time into its `VoteWeightRecord`.
+///
+/// `unix_ts`: The time to take the weight at, for example the creation time
+/// of the proposal to vote on. Like the lockups, it is in the registrar's
+/// time, see Registrar::clock_unix_timestamp().
+///
+/// The weight is the one of the newest checkpoint at or before `unix_ts`,
+/// decayed until `unix_ts`, see VoterWeightCheckpoints::weight_at(). It is
+/// the voter's own weight only: delegates don't get the weight of voters
+/// that delegated to them.
+/// Fails if the checkpoints don't reach back to `unix_ts`.
+///
+/// * `weight_action`: the action the weight may be used for, None for any
+/// * `weight_action_target`: the account the action applies to, like the
+///   proposal for CastVote, None for any
+///
+/// Like for update_voter_weight_record, spl-governance rejects records whose
+/// action or target don't match the instruction they are used in.
+///
+/// If the registrar has a predecessor plugin, its VoterWeightRecord for the
+/// voter must be passed as a remaining account and is combined with the
+/// voter's weight, like in update_voter_weight_record. That record holds the
+/// predecessor's current weight.
+///
+/// Fails unless the registrar uses historical voter weights, see
+/// set_historical_voter_weight.
+///
+/// This "revise" instruction must be called immediately before voting, in
+/// the same transaction.
+pub fn update_historical_voter_weight_record(
+    ctx: Context<UpdateHistoricalVoterWeightRecord>,
+    unix_ts: i64,
+    weight_action: Option<VoterWeightAction>,
+    weight_action_target: Option<Pubkey>,
+) -> Result<()> {
+    let registrar = &ctx.accounts.registrar.load()?;
+    require!(
+        registrar.historical_voter_weight,
+        VsrError::HistoricalVoterWeightDisabled
+    );
+    require_gte!(
+        registrar.clock_unix_timestamp(),
+        unix_ts,
+        VsrError::InvalidTimestampArguments
+    );
+    let checkpoints = &ctx.accounts.checkpoints.load()?;
+    let mut weight = checkpoints.weight_at(unix_ts)?;
+
+    if registrar.has_predecessor() {
+        let voter = ctx.accounts.voter.load()?;
+        let predecessor_weight = load_predecessor_weight(
+            registrar,
+            &voter.voter_authority,
+            weight_action,
+            weight_action_target,
+            ctx.remaining_accounts,
+        )?;
+        weight = registrar
+            .predecessor_weight_mode
+            .combine(predecessor_weight, weight)?;
+    }
+
+    let record = &mut ctx.accounts.voter_weight_record;
+    record.voter_weight = weight;
+    record.voter_weight_expiry = Some(Clock::get()?.slot);
+    record.weight_action = weight_action.map(Into::into);
+    record.weight_action_target = weight_action_target;
+
+    Ok(())
+}
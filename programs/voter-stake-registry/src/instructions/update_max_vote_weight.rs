@@ -1,28 +1,58 @@
 use crate::state::*;
 use anchor_lang::prelude::*;
+use std::mem::size_of;
 
 // Remaining accounts should all the token mints that have registered
-// exchange rates.
+// exchange rates, and the VotingMintRate accounts of priced voting mints.
 #[derive(Accounts)]
 pub struct UpdateMaxVoteWeight<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
-    // TODO: SPL governance has not yet implemented this.
-    /// CHECK: TODO. Will be implemented when max_vote_weight_record is rolled out.
-    pub max_vote_weight_record: UncheckedAccount<'info>,
+
+    /// The max voter weight record is the account that will be shown to spl-governance
+    /// to prove what the maximum possible vote weight in the realm is.
+    #[account(
+        init_if_needed,
+        seeds = [b"max-voter-weight-record".as_ref(), registrar.load()?.realm.key().as_ref(), registrar.load()?.realm_governing_token_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = size_of::<MaxVoterWeightRecord>(),
+    )]
+    pub max_voter_weight_record: Account<'info, MaxVoterWeightRecord>,
+
+    /// Rent payer if the max voter weight record is to be created
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 /// Calculates the max vote weight for the registry. This is a function
 /// of the total supply of all exchange rate mints, converted into a
 /// common currency with a common number of decimals.
 ///
+/// The result is written into a `MaxVoterWeightRecord` account, creating it
+/// if necessary, to be used by the SPL governance program. Like the voter
+/// weight record, it expires after the current slot, so this instruction
+/// must be called in the same transaction as the spl-governance instruction
+/// that needs the max vote weight.
+///
 /// Note that this method is only safe to use if the cumulative supply for
 /// all tokens fits into a u64 *after* converting into common decimals, as
 /// defined by the registrar's `rate_decimal` field.
 pub fn update_max_vote_weight(ctx: Context<UpdateMaxVoteWeight>) -> Result<()> {
-    let registrar = &ctx.accounts.registrar.load()?;
-    let _max_vote_weight = registrar.max_vote_weight(ctx.remaining_accounts)?;
-    // TODO: SPL governance has not yet implemented this feature.
-    //       When it has, probably need to write the result into an account,
-    //       similar to VoterWeightRecord.
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let max_vote_weight = registrar.max_vote_weight(ctx.remaining_accounts)?;
+
+    let record = &mut ctx.accounts.max_voter_weight_record;
+    record.account_discriminator =
+        spl_governance_addin_api::max_voter_weight::MaxVoterWeightRecord::ACCOUNT_DISCRIMINATOR;
+    record.realm = registrar.realm;
+    record.governing_token_mint = registrar.realm_governing_token_mint;
+    record.max_voter_weight = max_vote_weight;
+    record.max_voter_weight_expiry = Some(Clock::get()?.slot);
+
     Ok(())
 }
@@ -0,0 +1,36 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+// Remaining accounts are the VoterReward accounts to update, their
+// RewardDistributions and the VestingSchedule accounts of the voter's
+// Tranches deposits.
+#[derive(Accounts)]
+pub struct UpdateVoterRewards<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+}
+
+/// Updates reward positions of a voter to its current locked vote weight.
+///
+/// A position accrues rewards at the weight it had on its last update, while
+/// the lockup bonus of most lockups decays over time. Anyone can call this
+/// to update the weight of positions that haven't been updated in a while.
+/// The rewards since the last update are paid at no more than the current
+/// weight, see RewardDistribution::settle_position().
+pub fn update_voter_rewards(ctx: Context<UpdateVoterRewards>) -> Result<()> {
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = &ctx.accounts.voter.load_full()?;
+    update_reward_positions(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+        true,
+    )?;
+    Ok(())
+}
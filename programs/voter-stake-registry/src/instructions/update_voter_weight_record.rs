@@ -1,6 +1,13 @@
+use crate::error::*;
 use crate::state::*;
 use anchor_lang::prelude::*;
 
+// Remaining accounts may be Voter accounts that delegated their
+// vote weight to this voter, the VestingSchedule accounts of
+// Tranches deposits of all these voters, the VotingMintRate
+// accounts of priced voting mints, the voter's
+// VoterWeightCheckpoints account and the predecessor plugin's
+// VoterWeightRecord.
 #[derive(Accounts)]
 pub struct UpdateVoterWeightRecord<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
@@ -30,14 +37,151 @@ pub struct UpdateVoterWeightRecord<'info> {
 /// voter and writes it into a `VoteWeightRecord` account to be used by
 /// the SPL governance program.
 ///
+/// Voters that delegated their vote weight get a weight of zero. Delegates
+/// pass the Voter accounts that delegated to them as remaining accounts to
+/// have their weight included.
+///
+/// The VestingSchedule accounts of deposits with LockupKind::Tranches must be
+/// passed as remaining accounts too, like the VotingMintRate accounts of
+/// priced voting mints the deposits use.
+///
+/// If the voter's VoterWeightCheckpoints account is passed as a remaining
+/// account, the voter's own weight is appended to it. That keeps the
+/// checkpoints current after grants and decaying lockups.
+///
+/// * `weight_action`: the action the weight may be used for, None for any
+/// * `weight_action_target`: the account the action applies to, like the
+///   proposal for CastVote, None for any
+///
+/// spl-governance rejects records whose action or target don't match the
+/// instruction they are used in, so a weight refreshed for one purpose can't
+/// be reused for another within the same slot.
+///
+/// If the registrar has a predecessor plugin, its VoterWeightRecord for the
+/// voter must be passed as a remaining account. Its weight is combined with
+/// the voter's, see configure_predecessor_plugin. It must be current and
+/// scoped to no action or the same action and target.
+///
+/// Fails while the registrar uses historical voter weights, see
+/// set_historical_voter_weight.
+///
 /// This "revise" instruction must be called immediately before voting, in
 /// the same transaction.
-pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = ctx.accounts.voter.load()?;
+pub fn update_voter_weight_record(
+    ctx: Context<UpdateVoterWeightRecord>,
+    weight_action: Option<VoterWeightAction>,
+    weight_action_target: Option<Pubkey>,
+) -> Result<()> {
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    require!(
+        !registrar.historical_voter_weight,
+        VsrError::HistoricalVoterWeightEnabled
+    );
+    let voter = ctx.accounts.voter.load_full()?;
+    let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
+    let mut weight = voter.undelegated_weight(registrar, &schedules)?;
+
+    let mut delegators: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+    for account in ctx.remaining_accounts {
+        if VestingSchedules::is_vesting_schedule(account)
+            || is_program_account::<VoterWeightCheckpoints>(account)
+            || is_program_account::<VotingMintRate>(account)
+            || (registrar.has_predecessor() && *account.owner == registrar.predecessor_program_id)
+        {
+            continue;
+        }
+        require!(!delegators.contains(account.key), VsrError::InvalidDelegate);
+        delegators.push(*account.key);
+
+        let delegator_loader = AccountLoader::<Voter>::try_from(account)?;
+        let delegator = delegator_loader.load_full()?;
+        require_keys_eq!(
+            delegator.registrar,
+            ctx.accounts.registrar.key(),
+            VsrError::InvalidDelegate
+        );
+        require_keys_eq!(
+            delegator.delegate,
+            voter.voter_authority,
+            VsrError::InvalidDelegate
+        );
+        let delegator_schedules = VestingSchedules::load(account.key, ctx.remaining_accounts)?;
+        weight = weight
+            .checked_add(delegator.weight(registrar, &delegator_schedules)?)
+            .ok_or_else(|| error!(VsrError::VoterWeightOverflow))?;
+    }
+
+    if find_weight_checkpoints(&ctx.accounts.voter.key(), ctx.remaining_accounts).is_some() {
+        record_weight_checkpoint(
+            &ctx.accounts.voter.key(),
+            &voter,
+            registrar,
+            ctx.remaining_accounts,
+        )?;
+    }
+
+    if registrar.has_predecessor() {
+        let predecessor_weight = load_predecessor_weight(
+            registrar,
+            &voter.voter_authority,
+            weight_action,
+            weight_action_target,
+            ctx.remaining_accounts,
+        )?;
+        weight = registrar
+            .predecessor_weight_mode
+            .combine(predecessor_weight, weight)?;
+    }
+
     let record = &mut ctx.accounts.voter_weight_record;
-    record.voter_weight = voter.weight(registrar)?;
+    record.voter_weight = weight;
     record.voter_weight_expiry = Some(Clock::get()?.slot);
+    record.weight_action = weight_action.map(Into::into);
+    record.weight_action_target = weight_action_target;
 
     Ok(())
 }
+
+/// The weight in the predecessor plugin's VoterWeightRecord of the voter
+/// among `accounts`.
+///
+/// The record must be for the registrar's realm and governing token mint and
+/// the voter's authority, not expired, and scoped to no action or to
+/// `weight_action` and `weight_action_target`.
+pub fn load_predecessor_weight(
+    registrar: &Registrar,
+    voter_authority: &Pubkey,
+    weight_action: Option<VoterWeightAction>,
+    weight_action_target: Option<Pubkey>,
+    accounts: &[AccountInfo],
+) -> Result<u64> {
+    use spl_governance_addin_api::voter_weight::VoterWeightRecord as SplVoterWeightRecord;
+
+    let account = accounts
+        .iter()
+        .find(|a| *a.owner == registrar.predecessor_program_id)
+        .ok_or_else(|| error!(VsrError::MissingPredecessorVoterWeightRecord))?;
+    let data = account.try_borrow_data()?;
+    let record: SplVoterWeightRecord = AnchorDeserialize::deserialize(&mut &data[..])
+        .map_err(|_| error!(VsrError::InvalidPredecessorVoterWeightRecord))?;
+
+    let curr_slot = Clock::get()?.slot;
+    let weight_action = weight_action.map(Into::into);
+    require!(
+        record.account_discriminator == SplVoterWeightRecord::ACCOUNT_DISCRIMINATOR
+            && record.realm == registrar.realm
+            && record.governing_token_mint == registrar.realm_governing_token_mint
+            && record.governing_token_owner == *voter_authority
+            && record
+                .voter_weight_expiry
+                .map_or(true, |slot| slot >= curr_slot)
+            && (record.weight_action.is_none() || record.weight_action == weight_action)
+            && (record.weight_action_target.is_none()
+                || record.weight_action_target == weight_action_target),
+        VsrError::InvalidPredecessorVoterWeightRecord
+    );
+    Ok(record.voter_weight)
+}
@@ -1,12 +1,17 @@
 use crate::error::*;
 use crate::state::*;
+use crate::token_interface::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     pub registrar: AccountLoader<'info, Registrar>,
 
+    /// The registrar's deposit totals, if it has them.
+    /// See update_registrar_stats().
+    #[account(mut, has_one = registrar)]
+    pub registrar_stats: Option<AccountLoader<'info, RegistrarStats>>,
+
     // checking the PDA address it just an extra precaution,
     // the other constraints must be exhaustive
     #[account(
@@ -42,24 +47,52 @@ pub struct Withdraw<'info> {
     )]
     pub voter_weight_record: Account<'info, VoterWeightRecord>,
 
+    /// The token_owner_record for the delegate, required if the voter has one.
+    /// The delegate may be voting with the withdrawn tokens.
+    ///
+    /// CHECK: token_owner_record is validated in the instruction:
+    /// - owned by registrar.governance_program_id
+    /// - for the registrar.realm
+    /// - for the registrar.realm_governing_token_mint
+    /// - governing_token_owner is voter.delegate
+    pub delegate_token_owner_record: Option<UncheckedAccount<'info>>,
+
+    /// The voter weight record of the delegate, required if the voter has one.
+    /// It is reset, since it may include the withdrawn tokens.
+    #[account(
+        mut,
+        constraint = delegate_voter_weight_record.realm == registrar.load()?.realm,
+        constraint = delegate_voter_weight_record.governing_token_owner == voter.load()?.delegate,
+        constraint = delegate_voter_weight_record.governing_token_mint == registrar.load()?.realm_governing_token_mint,
+    )]
+    pub delegate_voter_weight_record: Option<Account<'info, VoterWeightRecord>>,
+
+    /// CHECK: Address constraint is set, the associated token account of voter
     #[account(
         mut,
-        associated_token::authority = voter,
-        associated_token::mint = destination.mint,
+        address = vault_address(&voter.key(), &deposit_mint.key(), &token_program.key()),
     )]
-    pub vault: Box<Account<'info, TokenAccount>>,
+    pub vault: UncheckedAccount<'info>,
 
+    /// CHECK: Validated by the token program, must be a token account for deposit_mint
     #[account(mut)]
-    pub destination: Box<Account<'info, TokenAccount>>,
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: Owner constraint is set, mint data is read by the token program
+    #[account(owner = token_program.key())]
+    pub deposit_mint: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Either the spl-token or the spl-token-2022 program
+    #[account(constraint = is_token_program(&token_program.key()) @ VsrError::InvalidTokenProgram)]
+    pub token_program: UncheckedAccount<'info>,
 }
 
 impl<'info> Withdraw<'info> {
-    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
         let program = self.token_program.to_account_info();
-        let accounts = token::Transfer {
+        let accounts = TransferChecked {
             from: self.vault.to_account_info(),
+            mint: self.deposit_mint.to_account_info(),
             to: self.destination.to_account_info(),
             authority: self.voter.to_account_info(),
         };
@@ -72,39 +105,75 @@ impl<'info> Withdraw<'info> {
 ///
 /// `deposit_entry_index`: The deposit entry to withdraw from.
 /// `amount` is in units of the native currency being withdrawn.
+///
+/// If the voter delegated its vote weight, the delegate's token owner record
+/// and voter weight record must be passed too.
+///
+/// The VestingSchedule accounts of the voter's deposits with LockupKind::Tranches
+/// must be passed as remaining accounts. So must the voter's VoterReward accounts
+/// and their RewardDistributions, see sync_voter_rewards().
+/// So must the voter's VoterWeightCheckpoints account if it has one, see
+/// record_weight_checkpoint().
 pub fn withdraw(ctx: Context<Withdraw>, deposit_entry_index: u8, amount: u64) -> Result<()> {
     {
         // Transfer the tokens to withdraw.
         let voter = &mut ctx.accounts.voter.load()?;
         let voter_seeds = voter_seeds!(voter);
-        token::transfer(
+        transfer_checked(
             ctx.accounts.transfer_ctx().with_signer(&[voter_seeds]),
             amount,
         )?;
     }
 
     // Load the accounts.
-    let registrar = &ctx.accounts.registrar.load()?;
-    let voter = &mut ctx.accounts.voter.load_mut()?;
+    let registrar = &ctx
+        .accounts
+        .registrar
+        .load_full_with_rates(ctx.remaining_accounts)?;
+    let voter = &mut ctx.accounts.voter.load_full_mut()?;
+    let schedules = VestingSchedules::load(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
 
     // Get the exchange rate for the token being withdrawn.
-    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.destination.mint)?;
+    let mint_idx = registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+    let mint_stats_before = voter.mint_stats(mint_idx);
 
     // Governance may forbid withdraws, for example when engaged in a vote.
     // Not applicable for tokens that don't contribute to voting power.
-    if registrar.voting_mints[mint_idx].grants_vote_weight() {
+    if registrar.voting_mint(mint_idx).grants_vote_weight() {
         let token_owner_record = voter.load_token_owner_record(
             &ctx.accounts.token_owner_record.to_account_info(),
             registrar,
         )?;
         token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+        if voter.has_delegate() {
+            let delegate_token_owner_record = ctx
+                .accounts
+                .delegate_token_owner_record
+                .as_ref()
+                .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+            let delegate_token_owner_record = voter.load_delegate_token_owner_record(
+                &delegate_token_owner_record.to_account_info(),
+                registrar,
+            )?;
+            delegate_token_owner_record.assert_can_withdraw_governing_tokens()?;
+
+            let delegate_record = ctx
+                .accounts
+                .delegate_voter_weight_record
+                .as_mut()
+                .ok_or_else(|| error!(VsrError::MissingDelegateAccount))?;
+            delegate_record.voter_weight = 0;
+            delegate_record.voter_weight_expiry = Some(Clock::get()?.slot);
+        }
     }
 
     // Get the deposit being withdrawn from.
     let curr_ts = registrar.clock_unix_timestamp();
     let deposit_entry = voter.active_deposit_mut(deposit_entry_index)?;
+    let schedule = schedules.for_deposit(deposit_entry_index as usize, deposit_entry)?;
     require_gte!(
-        deposit_entry.amount_unlocked(curr_ts),
+        deposit_entry.amount_unlocked(curr_ts, schedule),
         amount,
         VsrError::InsufficientUnlockedTokens
     );
@@ -133,10 +202,39 @@ pub fn withdraw(ctx: Context<Withdraw>, deposit_entry_index: u8, amount: u64) ->
         deposit_entry.lockup.seconds_left(curr_ts),
     );
 
-    // Update the voter weight record
+    // Update the registrar's deposit totals.
+    update_registrar_stats(
+        registrar,
+        &ctx.accounts.registrar_stats,
+        mint_idx,
+        &mint_stats_before,
+        &voter.mint_stats(mint_idx),
+    )?;
+
+    // Update the voter weight record. With a predecessor plugin, only
+    // update_voter_weight_record can compute the full weight, and with
+    // historical voter weights only update_historical_voter_weight_record.
     let record = &mut ctx.accounts.voter_weight_record;
-    record.voter_weight = voter.weight(registrar)?;
+    record.voter_weight = if registrar.has_predecessor() || registrar.historical_voter_weight {
+        0
+    } else {
+        voter.undelegated_weight(registrar, &schedules)?
+    };
     record.voter_weight_expiry = Some(Clock::get()?.slot);
 
+    sync_voter_rewards(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
+    record_weight_checkpoint(
+        &ctx.accounts.voter.key(),
+        voter,
+        registrar,
+        ctx.remaining_accounts,
+    )?;
+
     Ok(())
 }
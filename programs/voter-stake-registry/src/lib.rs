@@ -9,6 +9,7 @@ pub mod events;
 mod governance;
 mod instructions;
 pub mod state;
+mod token_interface;
 
 #[macro_use]
 extern crate static_assertions;
@@ -68,6 +69,7 @@ pub mod voter_stake_registry {
         instructions::create_registrar(ctx, registrar_bump)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn configure_voting_mint(
         ctx: Context<ConfigureVotingMint>,
         idx: u16,
@@ -76,6 +78,7 @@ pub mod voter_stake_registry {
         max_extra_lockup_vote_weight_scaled_factor: u64,
         lockup_saturation_secs: u64,
         grant_authority: Option<Pubkey>,
+        early_unlock_penalty_scaled_factor: u64,
     ) -> Result<()> {
         instructions::configure_voting_mint(
             ctx,
@@ -85,9 +88,108 @@ pub mod voter_stake_registry {
             max_extra_lockup_vote_weight_scaled_factor,
             lockup_saturation_secs,
             grant_authority,
+            early_unlock_penalty_scaled_factor,
+        )
+    }
+
+    pub fn configure_deposit_limits(
+        ctx: Context<ConfigureDepositLimits>,
+        idx: u16,
+        min_deposit_native: u64,
+        max_deposited_native: u64,
+        max_voter_deposited_native: u64,
+    ) -> Result<()> {
+        instructions::configure_deposit_limits(
+            ctx,
+            idx,
+            min_deposit_native,
+            max_deposited_native,
+            max_voter_deposited_native,
+        )
+    }
+
+    pub fn configure_lockup_limits(
+        ctx: Context<ConfigureLockupLimits>,
+        idx: u16,
+        allowed_lockup_kinds: u32,
+        min_lockup_secs: u64,
+        max_lockup_secs: u64,
+    ) -> Result<()> {
+        instructions::configure_lockup_limits(
+            ctx,
+            idx,
+            allowed_lockup_kinds,
+            min_lockup_secs,
+            max_lockup_secs,
         )
     }
 
+    pub fn retire_voting_mint(ctx: Context<RetireVotingMint>, idx: u16) -> Result<()> {
+        instructions::retire_voting_mint(ctx, idx)
+    }
+
+    pub fn clear_voting_mint(ctx: Context<ClearVotingMint>, idx: u16) -> Result<()> {
+        instructions::clear_voting_mint(ctx, idx)
+    }
+
+    pub fn configure_voting_mint_rate(
+        ctx: Context<ConfigureVotingMintRate>,
+        authority: Pubkey,
+        max_rate: u64,
+        max_staleness_secs: u64,
+    ) -> Result<()> {
+        instructions::configure_voting_mint_rate(ctx, authority, max_rate, max_staleness_secs)
+    }
+
+    pub fn set_voting_mint_rate(ctx: Context<SetVotingMintRate>, rate: u64) -> Result<()> {
+        instructions::set_voting_mint_rate(ctx, rate)
+    }
+
+    pub fn set_early_unlock_treasury(
+        ctx: Context<SetEarlyUnlockTreasury>,
+        early_unlock_treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::set_early_unlock_treasury(ctx, early_unlock_treasury)
+    }
+
+    pub fn set_historical_voter_weight(
+        ctx: Context<SetHistoricalVoterWeight>,
+        historical_voter_weight: bool,
+    ) -> Result<()> {
+        instructions::set_historical_voter_weight(ctx, historical_voter_weight)
+    }
+
+    pub fn set_pause_guardian(
+        ctx: Context<SetPauseGuardian>,
+        pause_guardian: Pubkey,
+    ) -> Result<()> {
+        instructions::set_pause_guardian(ctx, pause_guardian)
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::set_paused(ctx, paused)
+    }
+
+    pub fn configure_predecessor_plugin(
+        ctx: Context<ConfigurePredecessorPlugin>,
+        predecessor_program_id: Pubkey,
+        predecessor_weight_mode: PredecessorWeightMode,
+    ) -> Result<()> {
+        instructions::configure_predecessor_plugin(
+            ctx,
+            predecessor_program_id,
+            predecessor_weight_mode,
+        )
+    }
+
+    pub fn create_registrar_stats(ctx: Context<CreateRegistrarStats>) -> Result<()> {
+        instructions::create_registrar_stats(ctx)
+    }
+
+    pub fn grow_registrar(ctx: Context<GrowRegistrar>, voting_mint_slots: u8) -> Result<()> {
+        instructions::grow_registrar(ctx, voting_mint_slots)
+    }
+
     pub fn create_voter(
         ctx: Context<CreateVoter>,
         voter_bump: u8,
@@ -96,12 +198,17 @@ pub mod voter_stake_registry {
         instructions::create_voter(ctx, voter_bump, voter_weight_record_bump)
     }
 
+    pub fn grow_voter(ctx: Context<GrowVoter>, deposit_entry_slots: u8) -> Result<()> {
+        instructions::grow_voter(ctx, deposit_entry_slots)
+    }
+
     pub fn create_deposit_entry(
         ctx: Context<CreateDepositEntry>,
         deposit_entry_index: u8,
         kind: LockupKind,
         start_ts: Option<u64>,
         periods: u32,
+        cliff_periods: u32,
         allow_clawback: bool,
     ) -> Result<()> {
         instructions::create_deposit_entry(
@@ -110,6 +217,7 @@ pub mod voter_stake_registry {
             kind,
             start_ts,
             periods,
+            cliff_periods,
             allow_clawback,
         )
     }
@@ -122,6 +230,10 @@ pub mod voter_stake_registry {
         instructions::withdraw(ctx, deposit_entry_index, amount)
     }
 
+    pub fn early_unlock(ctx: Context<EarlyUnlock>, deposit_entry_index: u8) -> Result<()> {
+        instructions::early_unlock(ctx, deposit_entry_index)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn grant(
         ctx: Context<Grant>,
@@ -130,8 +242,10 @@ pub mod voter_stake_registry {
         kind: LockupKind,
         start_ts: Option<u64>,
         periods: u32,
+        cliff_periods: u32,
         allow_clawback: bool,
         amount: u64,
+        tranches: Vec<Tranche>,
     ) -> Result<()> {
         instructions::grant(
             ctx,
@@ -140,13 +254,19 @@ pub mod voter_stake_registry {
             kind,
             start_ts,
             periods,
+            cliff_periods,
             allow_clawback,
             amount,
+            tranches,
         )
     }
 
-    pub fn clawback(ctx: Context<Clawback>, deposit_entry_index: u8) -> Result<()> {
-        instructions::clawback(ctx, deposit_entry_index)
+    pub fn clawback(
+        ctx: Context<Clawback>,
+        deposit_entry_index: u8,
+        amount: Option<u64>,
+    ) -> Result<()> {
+        instructions::clawback(ctx, deposit_entry_index, amount)
     }
 
     pub fn close_deposit_entry(
@@ -165,6 +285,24 @@ pub mod voter_stake_registry {
         instructions::reset_lockup(ctx, deposit_entry_index, kind, periods)
     }
 
+    pub fn relock_deposit_entry(
+        ctx: Context<RelockDepositEntry>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+        kind: LockupKind,
+        periods: u32,
+    ) -> Result<()> {
+        instructions::relock_deposit_entry(
+            ctx,
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            amount,
+            kind,
+            periods,
+        )
+    }
+
     pub fn internal_transfer_locked(
         ctx: Context<InternalTransferLocked>,
         source_deposit_entry_index: u8,
@@ -179,6 +317,74 @@ pub mod voter_stake_registry {
         )
     }
 
+    pub fn external_transfer_locked<'key, 'accounts, 'remaining, 'info>(
+        ctx: Context<'key, 'accounts, 'remaining, 'info, ExternalTransferLocked<'info>>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::external_transfer_locked(
+            ctx,
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            amount,
+        )
+    }
+
+    pub fn split_deposit_entry(
+        ctx: Context<SplitDepositEntry>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::split_deposit_entry(
+            ctx,
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            amount,
+        )
+    }
+
+    pub fn merge_deposit_entries(
+        ctx: Context<MergeDepositEntries>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+    ) -> Result<()> {
+        instructions::merge_deposit_entries(
+            ctx,
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+        )
+    }
+
+    pub fn start_unlocking(
+        ctx: Context<StartUnlocking>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::start_unlocking(
+            ctx,
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            amount,
+        )
+    }
+
+    pub fn stop_unlocking(
+        ctx: Context<StopUnlocking>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::stop_unlocking(
+            ctx,
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            amount,
+        )
+    }
+
     pub fn internal_transfer_unlocked(
         ctx: Context<InternalTransferUnlocked>,
         source_deposit_entry_index: u8,
@@ -193,8 +399,16 @@ pub mod voter_stake_registry {
         )
     }
 
-    pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
-        instructions::update_voter_weight_record(ctx)
+    pub fn set_delegate(ctx: Context<SetDelegate>) -> Result<()> {
+        instructions::set_delegate(ctx)
+    }
+
+    pub fn update_voter_weight_record(
+        ctx: Context<UpdateVoterWeightRecord>,
+        weight_action: Option<VoterWeightAction>,
+        weight_action_target: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::update_voter_weight_record(ctx, weight_action, weight_action_target)
     }
 
     pub fn update_max_vote_weight(ctx: Context<UpdateMaxVoteWeight>) -> Result<()> {
@@ -207,6 +421,19 @@ pub mod voter_stake_registry {
         instructions::close_voter(ctx)
     }
 
+    pub fn propose_voter_authority(
+        ctx: Context<ProposeVoterAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_voter_authority(ctx, new_authority)
+    }
+
+    pub fn accept_voter_authority<'key, 'accounts, 'remaining, 'info>(
+        ctx: Context<'key, 'accounts, 'remaining, 'info, AcceptVoterAuthority<'info>>,
+    ) -> Result<()> {
+        instructions::accept_voter_authority(ctx)
+    }
+
     pub fn log_voter_info(
         ctx: Context<LogVoterInfo>,
         deposit_entry_begin: u8,
@@ -215,6 +442,64 @@ pub mod voter_stake_registry {
         instructions::log_voter_info(ctx, deposit_entry_begin, deposit_entry_count)
     }
 
+    pub fn log_registrar_stats(ctx: Context<LogRegistrarStats>) -> Result<()> {
+        instructions::log_registrar_stats(ctx)
+    }
+
+    pub fn create_reward_distribution(
+        ctx: Context<CreateRewardDistribution>,
+        duration_secs: u64,
+    ) -> Result<()> {
+        instructions::create_reward_distribution(ctx, duration_secs)
+    }
+
+    pub fn fund_reward_distribution(
+        ctx: Context<FundRewardDistribution>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::fund_reward_distribution(ctx, amount)
+    }
+
+    pub fn create_voter_reward(ctx: Context<CreateVoterReward>) -> Result<()> {
+        instructions::create_voter_reward(ctx)
+    }
+
+    pub fn update_voter_rewards(ctx: Context<UpdateVoterRewards>) -> Result<()> {
+        instructions::update_voter_rewards(ctx)
+    }
+
+    pub fn claim_reward(ctx: Context<ClaimReward>) -> Result<()> {
+        instructions::claim_reward(ctx)
+    }
+
+    pub fn close_voter_reward(ctx: Context<CloseVoterReward>) -> Result<()> {
+        instructions::close_voter_reward(ctx)
+    }
+
+    pub fn create_voter_weight_checkpoints(
+        ctx: Context<CreateVoterWeightCheckpoints>,
+    ) -> Result<()> {
+        instructions::create_voter_weight_checkpoints(ctx)
+    }
+
+    pub fn update_historical_voter_weight_record(
+        ctx: Context<UpdateHistoricalVoterWeightRecord>,
+        unix_ts: i64,
+        weight_action: Option<VoterWeightAction>,
+        weight_action_target: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::update_historical_voter_weight_record(
+            ctx,
+            unix_ts,
+            weight_action,
+            weight_action_target,
+        )
+    }
+
+    pub fn close_voter_weight_checkpoints(ctx: Context<CloseVoterWeightCheckpoints>) -> Result<()> {
+        instructions::close_voter_weight_checkpoints(ctx)
+    }
+
     pub fn set_time_offset(ctx: Context<SetTimeOffset>, time_offset: i64) -> Result<()> {
         instructions::set_time_offset(ctx, time_offset)
     }
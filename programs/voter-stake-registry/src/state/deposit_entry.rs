@@ -1,5 +1,6 @@
 use crate::error::*;
 use crate::state::lockup::{Lockup, LockupKind};
+use crate::state::vesting_schedule::VestingSchedule;
 use crate::state::voting_mint_config::VotingMintConfig;
 use anchor_lang::prelude::*;
 use std::cmp::min;
@@ -80,10 +81,28 @@ impl DepositEntry {
     ///
     /// ## Linear Vesting Lockup
     ///
-    /// Daily/monthly linear vesting can be calculated with series sum, see
+    /// Linear vesting with any period length can be calculated with series sum, see
     /// voting_power_linear_vesting() below.
     ///
-    pub fn voting_power(&self, voting_mint_config: &VotingMintConfig, curr_ts: i64) -> Result<u64> {
+    /// ## Linear Vesting After a Cliff
+    ///
+    /// Until the cliff ends, the tokens that vest at the end of the cliff are
+    /// cliff-locked until then and the rest is a linear vesting lockup that
+    /// starts at the end of the cliff, see voting_power_cliff_vesting() below.
+    /// Afterwards it's the same as linear vesting.
+    ///
+    /// ## Tranches
+    ///
+    /// Each tranche of the VestingSchedule is cliff-locked until its
+    /// unlock_ts, see voting_power_tranches() below.
+    ///
+    /// The `schedule` must be passed for deposits with LockupKind::Tranches.
+    pub fn voting_power(
+        &self,
+        voting_mint_config: &VotingMintConfig,
+        curr_ts: i64,
+        schedule: Option<&VestingSchedule>,
+    ) -> Result<u64> {
         let baseline_vote_weight =
             voting_mint_config.baseline_vote_weight(self.amount_deposited_native)?;
         let max_locked_vote_weight =
@@ -92,6 +111,7 @@ impl DepositEntry {
             curr_ts,
             max_locked_vote_weight,
             voting_mint_config.lockup_saturation_secs,
+            schedule,
         )?;
         require_gte!(
             max_locked_vote_weight,
@@ -109,6 +129,7 @@ impl DepositEntry {
         curr_ts: i64,
         max_locked_vote_weight: u64,
         lockup_saturation_secs: u64,
+        schedule: Option<&VestingSchedule>,
     ) -> Result<u64> {
         if self.lockup.expired(curr_ts) || max_locked_vote_weight == 0 {
             return Ok(0);
@@ -131,6 +152,32 @@ impl DepositEntry {
             LockupKind::Constant => {
                 self.voting_power_cliff(curr_ts, max_locked_vote_weight, lockup_saturation_secs)
             }
+            LockupKind::MonthlyWithCliff => self.voting_power_cliff_vesting(
+                curr_ts,
+                max_locked_vote_weight,
+                lockup_saturation_secs,
+            ),
+            LockupKind::Weekly => self.voting_power_linear_vesting(
+                curr_ts,
+                max_locked_vote_weight,
+                lockup_saturation_secs,
+            ),
+            LockupKind::Quarterly => self.voting_power_linear_vesting(
+                curr_ts,
+                max_locked_vote_weight,
+                lockup_saturation_secs,
+            ),
+            LockupKind::Yearly => self.voting_power_linear_vesting(
+                curr_ts,
+                max_locked_vote_weight,
+                lockup_saturation_secs,
+            ),
+            LockupKind::Tranches => self.voting_power_tranches(
+                curr_ts,
+                max_locked_vote_weight,
+                lockup_saturation_secs,
+                schedule.ok_or_else(|| error!(VsrError::MissingVestingSchedule))?,
+            ),
         }
     }
 
@@ -144,22 +191,25 @@ impl DepositEntry {
         at_ts: i64,
         max_locked_vote_weight: u64,
         lockup_saturation_secs: u64,
+        schedule: Option<&VestingSchedule>,
     ) -> Result<u64> {
         let mut altered = *self;
 
         // Trigger the unlock phase for constant lockups
         if self.lockup.kind == LockupKind::Constant {
-            altered.lockup.kind = LockupKind::Cliff;
-            altered.lockup.start_ts = curr_ts;
-            altered.lockup.end_ts = curr_ts
-                .checked_add(i64::try_from(self.lockup.seconds_left(curr_ts)).unwrap())
-                .unwrap();
+            altered.lockup.start_unlocking(curr_ts)?;
         }
 
         // Other lockup types don't need changes, because the user
-        // cannot reduce their lockup strength.
+        // cannot reduce their lockup strength. That includes the cliff of
+        // MonthlyWithCliff lockups, which can't be shortened either.
 
-        altered.voting_power_locked(at_ts, max_locked_vote_weight, lockup_saturation_secs)
+        altered.voting_power_locked(
+            at_ts,
+            max_locked_vote_weight,
+            lockup_saturation_secs,
+            schedule,
+        )
     }
 
     /// Vote power contribution from funds with linear vesting.
@@ -252,6 +302,39 @@ impl DepositEntry {
         // In the example above, periods_total was 5.
         let denominator = periods_total.checked_mul(lockup_saturation_secs).unwrap();
 
+        let lockup_secs = Self::linear_vesting_lockup_secs(
+            secs_to_closest_cliff,
+            periods_left,
+            period_secs,
+            lockup_saturation_secs,
+        );
+
+        Ok(u64::try_from(
+            (max_locked_vote_weight as u128)
+                .checked_mul(lockup_secs)
+                .unwrap()
+                .checked_div(denominator as u128)
+                .unwrap(),
+        )
+        .unwrap())
+    }
+
+    /// Sum of the seconds left for `periods_left` vesting cliffs that are
+    /// `period_secs` apart, each capped at `lockup_saturation_secs`.
+    ///
+    /// See voting_power_linear_vesting() for details.
+    fn linear_vesting_lockup_secs(
+        secs_to_closest_cliff: u64,
+        periods_left: u64,
+        period_secs: u64,
+        lockup_saturation_secs: u64,
+    ) -> u128 {
+        if secs_to_closest_cliff >= lockup_saturation_secs {
+            return (periods_left as u128)
+                .checked_mul(lockup_saturation_secs as u128)
+                .unwrap();
+        }
+
         let lockup_saturation_periods = (lockup_saturation_secs
             .saturating_sub(secs_to_closest_cliff)
             .checked_add(period_secs)
@@ -276,10 +359,50 @@ impl DepositEntry {
         let lockup_secs_fractional = q.checked_mul(secs_to_closest_cliff).unwrap();
         let lockup_secs_full = sum_full_periods.checked_mul(period_secs).unwrap();
         let lockup_secs_saturated = r.checked_mul(lockup_saturation_secs).unwrap();
-        let lockup_secs = lockup_secs_fractional as u128
-            + lockup_secs_full as u128
-            + lockup_secs_saturated as u128;
+        lockup_secs_fractional as u128 + lockup_secs_full as u128 + lockup_secs_saturated as u128
+    }
+
+    /// Vote power contribution from funds with linear vesting after a cliff.
+    fn voting_power_cliff_vesting(
+        &self,
+        curr_ts: i64,
+        max_locked_vote_weight: u64,
+        lockup_saturation_secs: u64,
+    ) -> Result<u64> {
+        let cliff_periods = self.lockup.cliff_periods as u64;
+        if self.lockup.period_current(curr_ts)? >= cliff_periods {
+            return self.voting_power_linear_vesting(
+                curr_ts,
+                max_locked_vote_weight,
+                lockup_saturation_secs,
+            );
+        }
+
+        let periods_total = self.lockup.periods_total()?;
+        let period_secs = self.lockup.kind.period_secs();
+        let tail_periods = periods_total.checked_sub(cliff_periods).unwrap();
+
+        // Like in voting_power_linear_vesting(), consider each period's tokens as
+        // cliff-locked until they vest. The tokens of the first cliff_periods
+        // periods all vest at the end of the cliff, the others one period apart
+        // after that.
+        let secs_to_cliff_end = self
+            .lockup
+            .seconds_left(curr_ts)
+            .checked_sub(tail_periods.checked_mul(period_secs).unwrap())
+            .unwrap();
+        let lockup_secs_cliff = (cliff_periods as u128)
+            .checked_mul(min(secs_to_cliff_end, lockup_saturation_secs) as u128)
+            .unwrap();
+        let lockup_secs_tail = Self::linear_vesting_lockup_secs(
+            secs_to_cliff_end.checked_add(period_secs).unwrap(),
+            tail_periods,
+            period_secs,
+            lockup_saturation_secs,
+        );
+        let lockup_secs = lockup_secs_cliff + lockup_secs_tail;
 
+        let denominator = periods_total.checked_mul(lockup_saturation_secs).unwrap();
         Ok(u64::try_from(
             (max_locked_vote_weight as u128)
                 .checked_mul(lockup_secs)
@@ -290,9 +413,44 @@ impl DepositEntry {
         .unwrap())
     }
 
+    /// Vote power contribution from funds locked in tranches.
+    ///
+    /// Each tranche is cliff-locked until its unlock_ts, so:
+    ///
+    /// ```
+    ///   voting_power = max_locked_vote_weight
+    ///                  * sum(amount_i * min(secs_until_unlock_i, lockup_saturation_secs))
+    ///                  / (amount_initially_locked_native * lockup_saturation_secs)
+    /// ```
+    fn voting_power_tranches(
+        &self,
+        curr_ts: i64,
+        max_locked_vote_weight: u64,
+        lockup_saturation_secs: u64,
+        schedule: &VestingSchedule,
+    ) -> Result<u64> {
+        let lockup_secs = schedule.lockup_secs(curr_ts, lockup_saturation_secs);
+        let denominator = (self.amount_initially_locked_native as u128)
+            .checked_mul(lockup_saturation_secs as u128)
+            .unwrap();
+        if denominator == 0 {
+            return Ok(0);
+        }
+        Ok(u64::try_from(
+            (max_locked_vote_weight as u128)
+                .checked_mul(lockup_secs)
+                .unwrap()
+                .checked_div(denominator)
+                .unwrap(),
+        )
+        .unwrap())
+    }
+
     /// Returns the amount of unlocked tokens for this deposit--in native units
     /// of the original token amount (not scaled by the exchange rate).
-    pub fn vested(&self, curr_ts: i64) -> Result<u64> {
+    ///
+    /// The `schedule` must be passed for deposits with LockupKind::Tranches.
+    pub fn vested(&self, curr_ts: i64, schedule: Option<&VestingSchedule>) -> Result<u64> {
         if self.lockup.expired(curr_ts) {
             return Ok(self.amount_initially_locked_native);
         }
@@ -302,6 +460,19 @@ impl DepositEntry {
             LockupKind::Monthly => self.vested_linearly(curr_ts),
             LockupKind::Cliff => Ok(0),
             LockupKind::Constant => Ok(0),
+            LockupKind::MonthlyWithCliff => {
+                if self.lockup.period_current(curr_ts)? < self.lockup.cliff_periods as u64 {
+                    Ok(0)
+                } else {
+                    self.vested_linearly(curr_ts)
+                }
+            }
+            LockupKind::Weekly => self.vested_linearly(curr_ts),
+            LockupKind::Quarterly => self.vested_linearly(curr_ts),
+            LockupKind::Yearly => self.vested_linearly(curr_ts),
+            LockupKind::Tranches => Ok(schedule
+                .ok_or_else(|| error!(VsrError::MissingVestingSchedule))?
+                .vested(curr_ts)),
         }
     }
 
@@ -325,18 +496,18 @@ impl DepositEntry {
 
     /// Returns native tokens still locked.
     #[inline(always)]
-    pub fn amount_locked(&self, curr_ts: i64) -> u64 {
+    pub fn amount_locked(&self, curr_ts: i64, schedule: Option<&VestingSchedule>) -> u64 {
         self.amount_initially_locked_native
-            .checked_sub(self.vested(curr_ts).unwrap())
+            .checked_sub(self.vested(curr_ts, schedule).unwrap())
             .unwrap()
     }
 
     /// Returns native tokens that are unlocked given current vesting
     /// and previous withdraws.
     #[inline(always)]
-    pub fn amount_unlocked(&self, curr_ts: i64) -> u64 {
+    pub fn amount_unlocked(&self, curr_ts: i64, schedule: Option<&VestingSchedule>) -> u64 {
         self.amount_deposited_native
-            .checked_sub(self.amount_locked(curr_ts))
+            .checked_sub(self.amount_locked(curr_ts, schedule))
             .unwrap()
     }
 
@@ -361,8 +532,14 @@ impl DepositEntry {
     ///      vested() = 0
     ///      period_current() = 0
     ///      periods_total() = 1
+    ///
+    /// Not possible for Tranches deposits, their schedule is fixed.
     pub fn resolve_vesting(&mut self, curr_ts: i64) -> Result<()> {
-        let vested_amount = self.vested(curr_ts)?;
+        require!(
+            self.lockup.kind != LockupKind::Tranches,
+            VsrError::InvalidLockupKind
+        );
+        let vested_amount = self.vested(curr_ts, None)?;
         require_gte!(
             self.amount_initially_locked_native,
             vested_amount,
@@ -373,7 +550,170 @@ impl DepositEntry {
             .checked_sub(vested_amount)
             .unwrap();
         self.lockup.remove_past_periods(curr_ts)?;
-        require_eq!(self.vested(curr_ts)?, 0, VsrError::InternalProgramError);
+        require_eq!(
+            self.vested(curr_ts, None)?,
+            0,
+            VsrError::InternalProgramError
+        );
+        Ok(())
+    }
+
+    /// Returns the native tokens forfeited when unlocking the deposit early.
+    ///
+    /// Each locked token pays a share of the voting mint's maximum penalty
+    /// that grows with its remaining lockup time, the same way its lockup
+    /// vote weight bonus does.
+    pub fn early_unlock_penalty(
+        &self,
+        voting_mint_config: &VotingMintConfig,
+        curr_ts: i64,
+        schedule: Option<&VestingSchedule>,
+    ) -> Result<u64> {
+        let max_penalty =
+            voting_mint_config.max_early_unlock_penalty(self.amount_initially_locked_native)?;
+        let penalty = self.voting_power_locked(
+            curr_ts,
+            max_penalty,
+            voting_mint_config.lockup_saturation_secs,
+            schedule,
+        )?;
+        require_gte!(
+            self.amount_locked(curr_ts, schedule),
+            penalty,
+            VsrError::InternalProgramError
+        );
+        Ok(penalty)
+    }
+
+    /// Moves `amount` native tokens into a new deposit with the same lockup
+    /// and returns it.
+    ///
+    /// Vesting is resolved first, so both deposits continue on the remaining
+    /// periods of the original schedule. The locked tokens are divided in
+    /// proportion to `amount`, the new deposit's share rounded down. Since
+    /// each deposit's vested amount is rounded down too, the two never vest
+    /// more than the original would have.
+    pub fn split(&mut self, amount: u64, curr_ts: i64) -> Result<DepositEntry> {
+        self.resolve_vesting(curr_ts)?;
+        require_gte!(
+            self.amount_deposited_native,
+            amount,
+            VsrError::InsufficientDepositedTokens
+        );
+        let locked = if self.amount_deposited_native == 0 {
+            0
+        } else {
+            u64::try_from(
+                (self.amount_initially_locked_native as u128)
+                    .checked_mul(amount as u128)
+                    .unwrap()
+                    .checked_div(self.amount_deposited_native as u128)
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+
+        self.amount_deposited_native = self.amount_deposited_native.checked_sub(amount).unwrap();
+        self.amount_initially_locked_native = self
+            .amount_initially_locked_native
+            .checked_sub(locked)
+            .unwrap();
+
+        Ok(DepositEntry {
+            lockup: self.lockup,
+            amount_deposited_native: amount,
+            amount_initially_locked_native: locked,
+            is_used: true,
+            allow_clawback: self.allow_clawback,
+            voting_mint_config_idx: self.voting_mint_config_idx,
+            reserved: [0; 29],
+        })
+    }
+
+    /// Removes `amount` native tokens from the deposit, taking locked and
+    /// unlocked tokens in proportion, like split() does.
+    ///
+    /// For Tranches deposits the locked tokens are removed from the tranches
+    /// that unlock last, the `schedule` must be passed for them.
+    pub fn remove_proportionally(
+        &mut self,
+        amount: u64,
+        curr_ts: i64,
+        schedule: Option<&mut VestingSchedule>,
+    ) -> Result<()> {
+        if self.lockup.kind != LockupKind::Tranches {
+            self.split(amount, curr_ts)?;
+            return Ok(());
+        }
+        let schedule = schedule.ok_or_else(|| error!(VsrError::MissingVestingSchedule))?;
+        require_gte!(
+            self.amount_deposited_native,
+            amount,
+            VsrError::InsufficientDepositedTokens
+        );
+        let locked = if self.amount_deposited_native == 0 {
+            0
+        } else {
+            u64::try_from(
+                (self.amount_locked(curr_ts, Some(&*schedule)) as u128)
+                    .checked_mul(amount as u128)
+                    .unwrap()
+                    .checked_div(self.amount_deposited_native as u128)
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+        schedule.remove_from_end(locked)?;
+
+        self.amount_deposited_native = self.amount_deposited_native.checked_sub(amount).unwrap();
+        self.amount_initially_locked_native = self
+            .amount_initially_locked_native
+            .checked_sub(locked)
+            .unwrap();
+        Ok(())
+    }
+
+    /// Adds the tokens of `other` to this deposit.
+    ///
+    /// Vesting of both is resolved first. Then their lockups must be of the
+    /// same kind and have the same time left. For vesting kinds the remaining
+    /// periods and cliff must match as well, which only fails for lockups that
+    /// start in the future or are still in their cliff: otherwise resolving
+    /// aligns the periods to the common end.
+    pub fn merge(&mut self, mut other: DepositEntry, curr_ts: i64) -> Result<()> {
+        require_eq!(
+            self.voting_mint_config_idx,
+            other.voting_mint_config_idx,
+            VsrError::InvalidMint
+        );
+        self.resolve_vesting(curr_ts)?;
+        other.resolve_vesting(curr_ts)?;
+        require!(
+            self.lockup.kind == other.lockup.kind,
+            VsrError::InvalidLockupKind
+        );
+        let seconds_left = self.lockup.seconds_left(curr_ts);
+        require_eq!(
+            seconds_left,
+            other.lockup.seconds_left(curr_ts),
+            VsrError::InvalidEndTs
+        );
+        if seconds_left > 0 && self.lockup.kind.is_vesting() {
+            require!(
+                self.lockup.start_ts == other.lockup.start_ts
+                    && self.lockup.cliff_periods == other.lockup.cliff_periods,
+                VsrError::InvalidLockupPeriod
+            );
+        }
+
+        self.amount_deposited_native = self
+            .amount_deposited_native
+            .checked_add(other.amount_deposited_native)
+            .unwrap();
+        self.amount_initially_locked_native = self
+            .amount_initially_locked_native
+            .checked_add(other.amount_initially_locked_native)
+            .unwrap();
         Ok(())
     }
 
@@ -386,7 +726,8 @@ impl DepositEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::LockupKind::{Constant, Daily};
+    use crate::state::vesting_schedule::Tranche;
+    use crate::LockupKind::{Cliff, Constant, Daily};
 
     #[test]
     pub fn resolve_vesting() -> Result<()> {
@@ -406,11 +747,11 @@ mod tests {
             |deposit: &DepositEntry| deposit.amount_initially_locked_native;
 
         let mut time = 1001;
-        assert_eq!(deposit.vested(time).unwrap(), 0);
-        assert_eq!(deposit.amount_unlocked(time), 5);
+        assert_eq!(deposit.vested(time, None).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(time, None), 5);
         deposit.resolve_vesting(time).unwrap(); // no effect
-        assert_eq!(deposit.vested(time).unwrap(), 0);
-        assert_eq!(deposit.amount_unlocked(time), 5);
+        assert_eq!(deposit.vested(time, None).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(time, None), 5);
         assert_eq!(
             deposit.lockup.seconds_left(time),
             initial_deposit.lockup.seconds_left(time)
@@ -420,12 +761,12 @@ mod tests {
         assert_eq!(amount_initially_locked(&deposit), 30);
 
         time = 1001 + month;
-        assert_eq!(deposit.vested(time).unwrap(), 10);
+        assert_eq!(deposit.vested(time, None).unwrap(), 10);
         assert_eq!(deposit.lockup.period_current(time).unwrap(), 1);
         assert_eq!(deposit.lockup.periods_total().unwrap(), 3);
         deposit.resolve_vesting(time).unwrap();
-        assert_eq!(deposit.vested(time).unwrap(), 0);
-        assert_eq!(deposit.amount_unlocked(time), 15);
+        assert_eq!(deposit.vested(time, None).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(time, None), 15);
         assert_eq!(
             deposit.lockup.seconds_left(time),
             initial_deposit.lockup.seconds_left(time)
@@ -435,12 +776,12 @@ mod tests {
         assert_eq!(amount_initially_locked(&deposit), 20);
 
         time = 1001 + 3 * month;
-        assert_eq!(deposit.vested(time).unwrap(), 20);
+        assert_eq!(deposit.vested(time, None).unwrap(), 20);
         assert_eq!(deposit.lockup.period_current(time).unwrap(), 2);
         assert_eq!(deposit.lockup.periods_total().unwrap(), 2);
         deposit.resolve_vesting(time).unwrap();
-        assert_eq!(deposit.vested(time).unwrap(), 0);
-        assert_eq!(deposit.amount_unlocked(time), 35);
+        assert_eq!(deposit.vested(time, None).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(time, None), 35);
         assert_eq!(
             deposit.lockup.seconds_left(time),
             initial_deposit.lockup.seconds_left(time)
@@ -452,6 +793,191 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn resolve_vesting_with_cliff() -> Result<()> {
+        let mut deposit = DepositEntry {
+            amount_deposited_native: 45,
+            amount_initially_locked_native: 40,
+            lockup: Lockup::new_from_periods_with_cliff(
+                LockupKind::MonthlyWithCliff,
+                1000,
+                1000,
+                4,
+                2,
+            )
+            .unwrap(),
+            is_used: true,
+            allow_clawback: false,
+            voting_mint_config_idx: 0,
+            reserved: [0; 29],
+        };
+        let initial_deposit = deposit.clone();
+        let month = deposit.lockup.kind.period_secs() as i64;
+        // function to avoid unaligned references when used with assert!()
+        let amount_initially_locked =
+            |deposit: &DepositEntry| deposit.amount_initially_locked_native;
+        let cliff_periods = |deposit: &DepositEntry| deposit.lockup.cliff_periods;
+
+        // nothing vests during the cliff and resolving keeps the periods
+        let mut time = 1001 + month;
+        assert_eq!(deposit.vested(time, None).unwrap(), 0);
+        deposit.resolve_vesting(time).unwrap(); // no effect
+        assert_eq!(deposit.vested(time, None).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(time, None), 5);
+        assert_eq!(deposit.lockup.period_current(time).unwrap(), 1);
+        assert_eq!(deposit.lockup.periods_total().unwrap(), 4);
+        assert_eq!(cliff_periods(&deposit), 2);
+        assert_eq!(amount_initially_locked(&deposit), 40);
+
+        // at the end of the cliff, the first two periods vest at once
+        time = 1001 + 2 * month;
+        assert_eq!(deposit.vested(time, None).unwrap(), 20);
+        deposit.resolve_vesting(time).unwrap();
+        assert_eq!(deposit.vested(time, None).unwrap(), 0);
+        assert_eq!(deposit.amount_unlocked(time, None), 25);
+        assert_eq!(
+            deposit.lockup.seconds_left(time),
+            initial_deposit.lockup.seconds_left(time)
+        );
+        assert_eq!(deposit.lockup.period_current(time).unwrap(), 0);
+        assert_eq!(deposit.lockup.periods_total().unwrap(), 2);
+        assert_eq!(cliff_periods(&deposit), 0);
+        assert_eq!(amount_initially_locked(&deposit), 20);
+
+        // afterwards it vests linearly
+        time = 1001 + 3 * month;
+        assert_eq!(deposit.vested(time, None).unwrap(), 10);
+        assert_eq!(deposit.amount_unlocked(time, None), 35);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn split_and_merge() -> Result<()> {
+        let month = LockupKind::Monthly.period_secs() as i64;
+        let mut deposit = DepositEntry {
+            amount_deposited_native: 35,
+            amount_initially_locked_native: 30,
+            lockup: Lockup::new_from_periods(LockupKind::Monthly, 1000, 1000, 3).unwrap(),
+            is_used: true,
+            allow_clawback: false,
+            voting_mint_config_idx: 0,
+            reserved: [0; 29],
+        };
+        // function to avoid unaligned references when used with assert!()
+        let amounts = |deposit: &DepositEntry| {
+            (
+                deposit.amount_deposited_native,
+                deposit.amount_initially_locked_native,
+            )
+        };
+
+        // after one month, 20 are locked and 15 unlocked
+        let time = 1001 + month;
+        let split = deposit.split(14, time)?;
+        assert_eq!(amounts(&deposit), (21, 12));
+        assert_eq!(amounts(&split), (14, 8));
+        assert_eq!(deposit.amount_unlocked(time, None), 9);
+        assert_eq!(split.amount_unlocked(time, None), 6);
+        assert_eq!(
+            split.lockup.seconds_left(time),
+            deposit.lockup.seconds_left(time)
+        );
+        assert!(deposit.split(22, time).is_err());
+
+        // both keep vesting on the original schedule
+        let time = 1001 + 2 * month;
+        assert_eq!(deposit.vested(time, None)?, 6);
+        assert_eq!(split.vested(time, None)?, 4);
+
+        deposit.merge(split, time)?;
+        assert_eq!(amounts(&deposit), (35, 10));
+        assert_eq!(deposit.amount_unlocked(time, None), 25);
+
+        // lockups must match
+        let cliff = |start_ts: i64, periods: u32| DepositEntry {
+            amount_deposited_native: 10,
+            amount_initially_locked_native: 10,
+            lockup: Lockup::new_from_periods(LockupKind::Cliff, 1000, start_ts, periods).unwrap(),
+            is_used: true,
+            allow_clawback: false,
+            voting_mint_config_idx: 0,
+            reserved: [0; 29],
+        };
+        let day = LockupKind::Cliff.period_secs() as i64;
+        assert!(deposit.merge(cliff(1000, 90), time).is_err());
+        let mut deposit = cliff(1000, 3);
+        assert!(deposit.merge(cliff(1000, 2), 1001).is_err());
+        deposit.merge(cliff(1000 + day, 2), 1001)?;
+        assert_eq!(amounts(&deposit), (20, 20));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn remove_proportionally() -> Result<()> {
+        let month = LockupKind::Monthly.period_secs() as i64;
+        let mut deposit = DepositEntry {
+            amount_deposited_native: 35,
+            amount_initially_locked_native: 30,
+            lockup: Lockup::new_from_periods(LockupKind::Monthly, 1000, 1000, 3).unwrap(),
+            is_used: true,
+            allow_clawback: false,
+            voting_mint_config_idx: 0,
+            reserved: [0; 29],
+        };
+        let amounts = |deposit: &DepositEntry| {
+            (
+                deposit.amount_deposited_native,
+                deposit.amount_initially_locked_native,
+            )
+        };
+
+        // same as split(): after one month, 20 are locked and 15 unlocked
+        deposit.remove_proportionally(14, 1001 + month, None)?;
+        assert_eq!(amounts(&deposit), (21, 12));
+        assert!(deposit
+            .remove_proportionally(22, 1001 + month, None)
+            .is_err());
+
+        // locked tokens of Tranches deposits come out of the last tranches
+        let day: i64 = 86_400;
+        let start = 10_000_000_000;
+        let mut schedule = VestingSchedule::default();
+        schedule.set_tranches(&[
+            Tranche {
+                unlock_ts: start + day,
+                amount: 1_000,
+            },
+            Tranche {
+                unlock_ts: start + 5 * day,
+                amount: 4_000,
+            },
+            Tranche {
+                unlock_ts: start + 20 * day,
+                amount: 5_000,
+            },
+        ])?;
+        let mut deposit = DepositEntry {
+            amount_deposited_native: 10_000,
+            amount_initially_locked_native: 10_000,
+            lockup: Lockup::new_from_tranches(start, &schedule)?,
+            is_used: true,
+            allow_clawback: false,
+            voting_mint_config_idx: 0,
+            reserved: [0; 29],
+        };
+        let time = start + 5 * day;
+        assert!(deposit.remove_proportionally(100, time, None).is_err());
+        deposit.remove_proportionally(100, time, Some(&mut schedule))?;
+        assert_eq!(amounts(&deposit), (9_900, 9_950));
+        assert_eq!(schedule.tranches()[2].amount, 4_950);
+        assert_eq!(deposit.amount_locked(time, Some(&schedule)), 4_950);
+        assert_eq!(deposit.amount_unlocked(time, Some(&schedule)), 4_950);
+
+        Ok(())
+    }
+
     #[test]
     pub fn far_future_lockup_start_test() -> Result<()> {
         // Check that voting power stays correct even if the lockup is very far in the
@@ -466,7 +992,9 @@ mod tests {
                 start_ts: lockup_start,
                 end_ts: lockup_start + 2 * day,
                 kind: Daily,
-                reserved: [0; 15],
+                padding: [0; 3],
+                cliff_periods: 0,
+                reserved: [0; 8],
             },
             is_used: true,
             allow_clawback: false,
@@ -480,8 +1008,17 @@ mod tests {
             max_extra_lockup_vote_weight_scaled_factor: 1_000_000_000, // 1x
             lockup_saturation_secs: saturation as u64,
             digit_shift: 0,
-            reserved1: [0; 7],
-            reserved2: [0; 7],
+            retired: false,
+            priced: false,
+            reserved1: [0; 5],
+            early_unlock_penalty_scaled_factor: 0,
+            min_deposit_native: 0,
+            max_deposited_native: 0,
+            max_voter_deposited_native: 0,
+            min_lockup_secs: 0,
+            max_lockup_secs: 0,
+            allowed_lockup_kinds: 0,
+            reserved2: [0; 4],
         };
 
         let baseline_vote_weight =
@@ -492,33 +1029,47 @@ mod tests {
         assert_eq!(max_locked_vote_weight, 10_000);
 
         // The timestamp 100_000 is very far before the lockup_start timestamp
-        let withdrawable = deposit.amount_unlocked(100_000);
+        let withdrawable = deposit.amount_unlocked(100_000, None);
         assert_eq!(withdrawable, 0);
-        let voting_power = deposit.voting_power(&voting_mint_config, 100_000).unwrap();
+        let voting_power = deposit
+            .voting_power(&voting_mint_config, 100_000, None)
+            .unwrap();
         assert_eq!(voting_power, 20_000);
 
         let voting_power = deposit
-            .voting_power(&voting_mint_config, lockup_start - saturation)
+            .voting_power(&voting_mint_config, lockup_start - saturation, None)
             .unwrap();
         assert_eq!(voting_power, 20_000);
 
         let voting_power = deposit
-            .voting_power(&voting_mint_config, lockup_start - saturation + day)
+            .voting_power(&voting_mint_config, lockup_start - saturation + day, None)
             .unwrap();
         assert_eq!(voting_power, 20_000);
 
         let voting_power = deposit
-            .voting_power(&voting_mint_config, lockup_start - saturation + day + 1)
+            .voting_power(
+                &voting_mint_config,
+                lockup_start - saturation + day + 1,
+                None,
+            )
             .unwrap();
         assert_eq!(voting_power, 19_999);
 
         let voting_power = deposit
-            .voting_power(&voting_mint_config, lockup_start - saturation + 2 * day)
+            .voting_power(
+                &voting_mint_config,
+                lockup_start - saturation + 2 * day,
+                None,
+            )
             .unwrap();
         assert_eq!(voting_power, 19_000); // the second cliff has only 4/5th of lockup period left
 
         let voting_power = deposit
-            .voting_power(&voting_mint_config, lockup_start - saturation + 2 * day + 1)
+            .voting_power(
+                &voting_mint_config,
+                lockup_start - saturation + 2 * day + 1,
+                None,
+            )
             .unwrap();
         assert_eq!(voting_power, 18_999);
 
@@ -538,7 +1089,9 @@ mod tests {
                 start_ts: start,
                 end_ts: start + 5 * day,
                 kind: Constant,
-                reserved: [0; 15],
+                padding: [0; 3],
+                cliff_periods: 0,
+                reserved: [0; 8],
             },
             is_used: true,
             allow_clawback: false,
@@ -553,6 +1106,7 @@ mod tests {
                     start + at_offset,
                     100,
                     saturation,
+                    None,
                 )
                 .unwrap()
         };
@@ -573,4 +1127,148 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    pub fn tranches() -> Result<()> {
+        let day: i64 = 86_400;
+        let saturation = (10 * day) as u64;
+        let start = 10_000_000_000; // arbitrary point
+        let mut schedule = VestingSchedule::default();
+        let total = schedule.set_tranches(&[
+            Tranche {
+                unlock_ts: start + day,
+                amount: 1_000,
+            },
+            Tranche {
+                unlock_ts: start + 5 * day,
+                amount: 4_000,
+            },
+            Tranche {
+                unlock_ts: start + 20 * day,
+                amount: 5_000,
+            },
+        ])?;
+        assert_eq!(total, 10_000);
+        let deposit = DepositEntry {
+            amount_deposited_native: 10_000,
+            amount_initially_locked_native: 10_000,
+            lockup: Lockup::new_from_tranches(start, &schedule)?,
+            is_used: true,
+            allow_clawback: false,
+            voting_mint_config_idx: 0,
+            reserved: [0; 29],
+        };
+
+        assert!(deposit.vested(start, None).is_err());
+        assert_eq!(deposit.vested(start, Some(&schedule))?, 0);
+        assert_eq!(deposit.vested(start + day - 1, Some(&schedule))?, 0);
+        assert_eq!(deposit.vested(start + day, Some(&schedule))?, 1_000);
+        assert_eq!(deposit.vested(start + 19 * day, Some(&schedule))?, 5_000);
+        assert_eq!(deposit.vested(start + 20 * day, Some(&schedule))?, 10_000);
+        assert_eq!(
+            deposit.amount_locked(start + 5 * day, Some(&schedule)),
+            5_000
+        );
+
+        // Each tranche is cliff locked until its unlock time
+        let v = |offset| {
+            deposit
+                .voting_power_locked(start + offset, 1_000, saturation, Some(&schedule))
+                .unwrap()
+        };
+        assert_eq!(v(0), 10 + 200 + 500);
+        assert_eq!(v(day), 160 + 500);
+        assert_eq!(v(5 * day), 500);
+        assert_eq!(v(15 * day), 250);
+        assert_eq!(v(20 * day), 0);
+
+        // Reductions come out of the last tranches
+        schedule.remove_from_end(5_500)?;
+        assert_eq!(schedule.tranches().len(), 2);
+        assert_eq!(schedule.tranches()[1].amount, 3_500);
+        assert_eq!(schedule.vested(start + 20 * day), 4_500);
+
+        // Unsorted or empty tranches are invalid
+        let mut schedule = VestingSchedule::default();
+        assert!(schedule.set_tranches(&[]).is_err());
+        assert!(schedule
+            .set_tranches(&[
+                Tranche {
+                    unlock_ts: start + day,
+                    amount: 1,
+                },
+                Tranche {
+                    unlock_ts: start,
+                    amount: 1,
+                },
+            ])
+            .is_err());
+
+        Ok(())
+    }
+    #[test]
+    pub fn early_unlock_penalty() -> Result<()> {
+        let day: i64 = 86_400;
+        let saturation = (10 * day) as u64;
+        let start = 10_000_000_000; // arbitrary point
+        let mut deposit = DepositEntry {
+            amount_deposited_native: 12_000,
+            amount_initially_locked_native: 10_000,
+            lockup: Lockup {
+                start_ts: start,
+                end_ts: start + 5 * day,
+                kind: Cliff,
+                padding: [0; 3],
+                cliff_periods: 0,
+                reserved: [0; 8],
+            },
+            is_used: true,
+            allow_clawback: false,
+            voting_mint_config_idx: 0,
+            reserved: [0; 29],
+        };
+        let voting_mint_config = VotingMintConfig {
+            mint: Pubkey::default(),
+            grant_authority: Pubkey::default(),
+            baseline_vote_weight_scaled_factor: 1_000_000_000, // 1x
+            max_extra_lockup_vote_weight_scaled_factor: 0,
+            lockup_saturation_secs: saturation,
+            digit_shift: 0,
+            retired: false,
+            priced: false,
+            reserved1: [0; 5],
+            early_unlock_penalty_scaled_factor: 200_000_000, // 20%
+            min_deposit_native: 0,
+            max_deposited_native: 0,
+            max_voter_deposited_native: 0,
+            min_lockup_secs: 0,
+            max_lockup_secs: 0,
+            allowed_lockup_kinds: 0,
+            reserved2: [0; 4],
+        };
+
+        let p = |deposit: &DepositEntry, offset| {
+            deposit
+                .early_unlock_penalty(&voting_mint_config, start + offset, None)
+                .unwrap()
+        };
+
+        // Half the saturation time left: half the maximum penalty
+        assert_eq!(p(&deposit, 0), 1_000);
+        assert_eq!(p(&deposit, 4 * day), 200);
+        assert_eq!(p(&deposit, 5 * day), 0);
+
+        // Penalties saturate, like the lockup vote weight bonus
+        deposit.lockup.end_ts = start + 20 * day;
+        assert_eq!(p(&deposit, 0), 2_000);
+        assert_eq!(p(&deposit, 15 * day), 1_000);
+
+        // Each daily period pays for its own lockup time, vested ones pay nothing
+        deposit.lockup = Lockup::new_from_periods(Daily, start, start, 4)?;
+        assert_eq!(p(&deposit, 0), 50 + 100 + 150 + 200);
+        assert_eq!(p(&deposit, 2 * day), 50 + 100);
+        assert_eq!(p(&deposit, 4 * day), 0);
+
+        Ok(())
+    }
 }
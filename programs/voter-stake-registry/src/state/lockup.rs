@@ -1,4 +1,5 @@
 use crate::error::*;
+use crate::state::vesting_schedule::VestingSchedule;
 use crate::vote_weight_record;
 use anchor_lang::prelude::*;
 use std::convert::TryFrom;
@@ -11,19 +12,30 @@ vote_weight_record!(crate::ID);
 /// Seconds in one day.
 pub const SECS_PER_DAY: u64 = 86_400;
 
+/// Seconds in one week.
+pub const SECS_PER_WEEK: u64 = 7 * SECS_PER_DAY;
+
 /// Seconds in one month.
 pub const SECS_PER_MONTH: u64 = 365 * SECS_PER_DAY / 12;
 
+/// Seconds in one quarter.
+pub const SECS_PER_QUARTER: u64 = 365 * SECS_PER_DAY / 4;
+
+/// Seconds in one year.
+pub const SECS_PER_YEAR: u64 = 365 * SECS_PER_DAY;
+
 /// Maximum acceptable number of lockup periods.
 ///
 /// In the linear vesting voting power computation, a factor like
 /// `periods^2 * period_secs` is used. With the current setting
-/// that would be 36500^2 * SECS_PER_MONTH << 2^64.
+/// that would be 73000^2 * SECS_PER_YEAR << 2^64.
 ///
-/// This setting limits the maximum lockup duration for lockup methods
-/// with daily periods to 200 years.
+/// Only lockups with daily periods can have this many, see MAX_LOCKUP_SECS.
 pub const MAX_LOCKUP_PERIODS: u32 = 365 * 200;
 
+/// Maximum acceptable lockup duration: 200 years, for every lockup kind.
+pub const MAX_LOCKUP_SECS: u64 = MAX_LOCKUP_PERIODS as u64 * SECS_PER_DAY;
+
 pub const MAX_LOCKUP_IN_FUTURE_SECS: i64 = 100 * 365 * 24 * 60 * 60;
 
 #[zero_copy]
@@ -43,10 +55,18 @@ pub struct Lockup {
     /// Type of lockup.
     pub kind: LockupKind,
 
+    // Padding to keep cliff_periods aligned.
+    pub padding: [u8; 3],
+
+    /// Number of periods after start_ts during which nothing vests.
+    ///
+    /// Only used by MonthlyWithCliff lockups, zero otherwise.
+    pub(crate) cliff_periods: u32,
+
     // Empty bytes for future upgrades.
-    pub reserved: [u8; 15],
+    pub reserved: [u8; 8],
 }
-const_assert!(std::mem::size_of::<Lockup>() == 2 * 8 + 1 + 15);
+const_assert!(std::mem::size_of::<Lockup>() == 2 * 8 + 1 + 3 + 4 + 8);
 const_assert!(std::mem::size_of::<Lockup>() % 8 == 0);
 
 impl Default for Lockup {
@@ -55,7 +75,9 @@ impl Default for Lockup {
             kind: LockupKind::None,
             start_ts: 0,
             end_ts: 0,
-            reserved: [0; 15],
+            padding: [0; 3],
+            cliff_periods: 0,
+            reserved: [0; 8],
         }
     }
 }
@@ -68,25 +90,133 @@ impl Lockup {
         start_ts: i64,
         periods: u32,
     ) -> Result<Self> {
+        Self::new_from_periods_with_cliff(kind, curr_ts, start_ts, periods, 0)
+    }
+
+    /// Create lockup for a given period, where nothing vests during the
+    /// first `cliff_periods` periods.
+    ///
+    /// Only MonthlyWithCliff lockups may have a nonzero cliff.
+    pub fn new_from_periods_with_cliff(
+        kind: LockupKind,
+        curr_ts: i64,
+        start_ts: i64,
+        periods: u32,
+        cliff_periods: u32,
+    ) -> Result<Self> {
+        // Tranches lockups are defined by their VestingSchedule instead.
+        require!(kind != LockupKind::Tranches, VsrError::InvalidLockupKind);
+        require!(
+            cliff_periods == 0 || kind == LockupKind::MonthlyWithCliff,
+            VsrError::InvalidLockupKind
+        );
+        require_gte!(periods, cliff_periods, VsrError::InvalidLockupPeriod);
         require_gt!(
             curr_ts + MAX_LOCKUP_IN_FUTURE_SECS,
             start_ts,
             VsrError::DepositStartTooFarInFuture
         );
         require_gte!(MAX_LOCKUP_PERIODS, periods, VsrError::InvalidLockupPeriod);
+        let lockup_secs = (periods as u64).checked_mul(kind.period_secs()).unwrap();
+        require_gte!(MAX_LOCKUP_SECS, lockup_secs, VsrError::InvalidLockupPeriod);
         Ok(Self {
             kind,
             start_ts,
             end_ts: start_ts
-                .checked_add(
-                    i64::try_from((periods as u64).checked_mul(kind.period_secs()).unwrap())
-                        .unwrap(),
-                )
+                .checked_add(i64::try_from(lockup_secs).unwrap())
                 .unwrap(),
-            reserved: [0; 15],
+            padding: [0; 3],
+            cliff_periods,
+            reserved: [0; 8],
+        })
+    }
+
+    /// Create a Tranches lockup that ends when the last tranche of the
+    /// schedule unlocks.
+    pub fn new_from_tranches(curr_ts: i64, schedule: &VestingSchedule) -> Result<Self> {
+        let end_ts = schedule.end_ts().max(curr_ts);
+        require_gt!(
+            curr_ts + MAX_LOCKUP_IN_FUTURE_SECS,
+            end_ts,
+            VsrError::InvalidVestingSchedule
+        );
+        Ok(Self {
+            kind: LockupKind::Tranches,
+            start_ts: curr_ts,
+            end_ts,
+            padding: [0; 3],
+            cliff_periods: 0,
+            reserved: [0; 8],
         })
     }
 
+    /// Create a lockup of `periods` starting at `curr_ts` that replaces this one.
+    ///
+    /// The new lockup must not be shorter, less strict or have a shorter cliff.
+    /// For MonthlyWithCliff, the cliff covers what is left of this lockup's
+    /// cliff, rounded up to full periods.
+    pub fn relocked(&self, kind: LockupKind, periods: u32, curr_ts: i64) -> Result<Self> {
+        require_gte!(
+            (periods as u64).checked_mul(kind.period_secs()).unwrap(),
+            self.seconds_left(curr_ts),
+            VsrError::InvalidLockupPeriod
+        );
+        require_gte!(
+            kind.strictness(),
+            self.kind.strictness(),
+            VsrError::InvalidLockupKind
+        );
+
+        let cliff_periods = if kind == LockupKind::MonthlyWithCliff {
+            let period_secs = kind.period_secs();
+            let cliff_secs = self.cliff_seconds_left(curr_ts);
+            u32::try_from(
+                cliff_secs
+                    .checked_add(period_secs - 1)
+                    .unwrap()
+                    .checked_div(period_secs)
+                    .unwrap(),
+            )
+            .unwrap()
+        } else {
+            0
+        };
+
+        let lockup =
+            Self::new_from_periods_with_cliff(kind, curr_ts, curr_ts, periods, cliff_periods)?;
+        require_gte!(
+            lockup.cliff_seconds_left(curr_ts),
+            self.cliff_seconds_left(curr_ts),
+            VsrError::InvalidLockupPeriod
+        );
+        Ok(lockup)
+    }
+
+    /// Turn a Constant lockup into a Cliff lockup that ends `seconds_left`
+    /// after `curr_ts`, starting the unlock process.
+    pub fn start_unlocking(&mut self, curr_ts: i64) -> Result<()> {
+        require!(
+            self.kind == LockupKind::Constant,
+            VsrError::InvalidLockupKind
+        );
+        let secs = i64::try_from(self.seconds_left(curr_ts)).unwrap();
+        self.kind = LockupKind::Cliff;
+        self.start_ts = curr_ts;
+        self.end_ts = curr_ts.checked_add(secs).unwrap();
+        Ok(())
+    }
+
+    /// Turn a Cliff lockup back into a Constant lockup that keeps the
+    /// `seconds_left` it has at `curr_ts`.
+    pub fn stop_unlocking(&mut self, curr_ts: i64) -> Result<()> {
+        require!(self.kind == LockupKind::Cliff, VsrError::InvalidLockupKind);
+        let secs = i64::try_from(self.seconds_left(curr_ts)).unwrap();
+        self.kind = LockupKind::Constant;
+        self.start_ts = curr_ts;
+        self.end_ts = curr_ts.checked_add(secs).unwrap();
+        Ok(())
+    }
+
     /// True when the lockup is finished.
     pub fn expired(&self, curr_ts: i64) -> bool {
         self.seconds_left(curr_ts) == 0
@@ -144,18 +274,59 @@ impl Lockup {
         Ok(lockup_secs.checked_div(period_secs).unwrap())
     }
 
+    /// Number of seconds until the end of the cliff, during which nothing vests.
+    ///
+    /// For Cliff and Constant lockups, that's the whole lockup.
+    pub fn cliff_seconds_left(&self, curr_ts: i64) -> u64 {
+        match self.kind {
+            LockupKind::MonthlyWithCliff => {
+                let cliff_end_ts = self.cliff_end_ts();
+                if curr_ts >= cliff_end_ts {
+                    0
+                } else {
+                    (cliff_end_ts - curr_ts) as u64
+                }
+            }
+            LockupKind::Cliff | LockupKind::Constant => self.seconds_left(curr_ts),
+            _ => 0,
+        }
+    }
+
+    /// End of the cliff, equal to start_ts when there is none.
+    pub fn cliff_end_ts(&self) -> i64 {
+        self.start_ts
+            .checked_add(
+                i64::try_from(
+                    (self.cliff_periods as u64)
+                        .checked_mul(self.kind.period_secs())
+                        .unwrap(),
+                )
+                .unwrap(),
+            )
+            .unwrap()
+    }
+
     /// Remove the vesting periods that are now in the past.
+    ///
+    /// Periods that passed during a cliff are kept until the cliff is over,
+    /// since nothing vested in them yet.
     pub fn remove_past_periods(&mut self, curr_ts: i64) -> Result<()> {
-        let periods = self.period_current(curr_ts)?;
+        let mut periods = self.period_current(curr_ts)?;
+        if periods < self.cliff_periods as u64 {
+            periods = 0;
+        }
         let period_secs = self.kind.period_secs();
         self.start_ts = self
             .start_ts
             .checked_add(i64::try_from(periods.checked_mul(period_secs).unwrap()).unwrap())
             .unwrap();
+        self.cliff_periods = self
+            .cliff_periods
+            .saturating_sub(u32::try_from(periods).unwrap());
         require_gte!(self.end_ts, self.start_ts, VsrError::InternalProgramError);
-        require_eq!(
-            self.period_current(curr_ts)?,
-            0,
+        let period_current = self.period_current(curr_ts)?;
+        require!(
+            period_current == 0 || period_current < self.cliff_periods as u64,
             VsrError::InternalProgramError
         );
         Ok(())
@@ -180,6 +351,24 @@ pub enum LockupKind {
     /// Lock up permanently. The number of days specified becomes the minimum
     /// unlock period when the deposit (or a part of it) is changed to Cliff.
     Constant,
+
+    /// Lock up for a number of months, where nothing vests for the first
+    /// `cliff_periods` months. At the end of the cliff, the linear fraction of
+    /// all months so far vests at once, then a linear fraction vests each month.
+    MonthlyWithCliff,
+
+    /// Lock up for a number of weeks, where a linear fraction vests each week.
+    Weekly,
+
+    /// Lock up for a number of quarters, where a linear fraction vests each quarter.
+    Quarterly,
+
+    /// Lock up for a number of years, where a linear fraction vests each year.
+    Yearly,
+
+    /// Lock up until the last tranche of a VestingSchedule unlocks. Each
+    /// tranche vests at its own time, like a cliff lockup. See grant.
+    Tranches,
 }
 
 impl LockupKind {
@@ -194,17 +383,30 @@ impl LockupKind {
             LockupKind::Monthly => SECS_PER_MONTH,
             LockupKind::Cliff => SECS_PER_DAY, // arbitrary choice
             LockupKind::Constant => SECS_PER_DAY, // arbitrary choice
+            LockupKind::MonthlyWithCliff => SECS_PER_MONTH,
+            LockupKind::Weekly => SECS_PER_WEEK,
+            LockupKind::Quarterly => SECS_PER_QUARTER,
+            LockupKind::Yearly => SECS_PER_YEAR,
+            LockupKind::Tranches => 0,
         }
     }
 
     /// Lockups cannot decrease in strictness
+    ///
+    /// Vesting lockups with longer periods are stricter. Cliffs are compared
+    /// separately, see Lockup::cliff_seconds_left().
     pub fn strictness(&self) -> u8 {
         match self {
             LockupKind::None => 0,
             LockupKind::Daily => 1,
-            LockupKind::Monthly => 2,
-            LockupKind::Cliff => 3, // can freely move between Cliff and Constant
-            LockupKind::Constant => 3,
+            LockupKind::Weekly => 2,
+            LockupKind::Monthly => 3,
+            LockupKind::MonthlyWithCliff => 3,
+            LockupKind::Quarterly => 4,
+            LockupKind::Yearly => 5,
+            LockupKind::Cliff => 6, // can freely move between Cliff and Constant
+            LockupKind::Constant => 6,
+            LockupKind::Tranches => 6,
         }
     }
 
@@ -215,6 +417,11 @@ impl LockupKind {
             LockupKind::Monthly => true,
             LockupKind::Cliff => false,
             LockupKind::Constant => false,
+            LockupKind::MonthlyWithCliff => true,
+            LockupKind::Weekly => true,
+            LockupKind::Quarterly => true,
+            LockupKind::Yearly => true,
+            LockupKind::Tranches => false,
         }
     }
 }
@@ -252,6 +459,121 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn period_computations_other_kinds() -> Result<()> {
+        for kind in [
+            LockupKind::Weekly,
+            LockupKind::Quarterly,
+            LockupKind::Yearly,
+        ] {
+            let lockup = Lockup::new_from_periods(kind, 1000, 1000, 3)?;
+            let period = kind.period_secs() as i64;
+            assert_eq!(lockup.periods_total()?, 3);
+            assert_eq!(lockup.period_current(1000 + period - 1)?, 0);
+            assert_eq!(lockup.periods_left(1000 + period - 1)?, 3);
+            assert_eq!(lockup.period_current(1000 + period)?, 1);
+            assert_eq!(lockup.periods_left(1000 + period)?, 2);
+            assert_eq!(lockup.period_current(1000 + 3 * period)?, 3);
+            assert_eq!(lockup.periods_left(1000 + 3 * period)?, 0);
+        }
+        assert!(LockupKind::Daily.strictness() < LockupKind::Weekly.strictness());
+        assert!(LockupKind::Weekly.strictness() < LockupKind::Monthly.strictness());
+        assert!(LockupKind::Monthly.strictness() < LockupKind::Quarterly.strictness());
+        assert!(LockupKind::Quarterly.strictness() < LockupKind::Yearly.strictness());
+        assert!(LockupKind::Yearly.strictness() < LockupKind::Cliff.strictness());
+        Ok(())
+    }
+
+    #[test]
+    pub fn max_lockup_duration() -> Result<()> {
+        for (kind, max_periods) in [
+            (LockupKind::Daily, MAX_LOCKUP_PERIODS),
+            (LockupKind::Cliff, MAX_LOCKUP_PERIODS),
+            (LockupKind::Weekly, 10428),
+            (LockupKind::Monthly, 2400),
+            (LockupKind::Quarterly, 800),
+            (LockupKind::Yearly, 200),
+        ] {
+            let lockup = Lockup::new_from_periods(kind, 1000, 1000, max_periods)?;
+            assert!(lockup.seconds_left(1000) <= MAX_LOCKUP_SECS);
+            assert!(Lockup::new_from_periods(kind, 1000, 1000, max_periods + 1).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn cliff_computations() -> Result<()> {
+        let month = SECS_PER_MONTH as i64;
+        let lockup =
+            Lockup::new_from_periods_with_cliff(LockupKind::MonthlyWithCliff, 1000, 1000, 4, 2)?;
+        assert_eq!(lockup.periods_total()?, 4);
+        assert_eq!(lockup.cliff_end_ts(), 1000 + 2 * month);
+        assert_eq!(lockup.cliff_seconds_left(0), 1000 + 2 * month as u64);
+        assert_eq!(lockup.cliff_seconds_left(1000 + month), month as u64);
+        assert_eq!(lockup.cliff_seconds_left(1000 + 2 * month), 0);
+        assert_eq!(lockup.cliff_seconds_left(1000 + 3 * month), 0);
+
+        // a cliff is only allowed on MonthlyWithCliff and can't exceed the lockup
+        assert!(
+            Lockup::new_from_periods_with_cliff(LockupKind::Monthly, 1000, 1000, 4, 2).is_err()
+        );
+        assert!(Lockup::new_from_periods_with_cliff(
+            LockupKind::MonthlyWithCliff,
+            1000,
+            1000,
+            4,
+            5
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn relocked_lockup() -> Result<()> {
+        let day = SECS_PER_DAY as i64;
+        let month = SECS_PER_MONTH as i64;
+        let lockup = Lockup::new_from_periods(LockupKind::Daily, 1000, 1000, 5)?;
+
+        // two days left: the new lockup must last at least as long and be as strict
+        let curr_ts = 1000 + 3 * day;
+        assert!(lockup.relocked(LockupKind::Daily, 1, curr_ts).is_err());
+        assert!(lockup.relocked(LockupKind::None, 2, curr_ts).is_err());
+        let relocked = lockup.relocked(LockupKind::Daily, 2, curr_ts)?;
+        assert_eq!(relocked.seconds_left(curr_ts), 2 * day as u64);
+        assert_eq!(relocked.start_ts, curr_ts);
+
+        // what is left of a cliff is rounded up to full periods
+        let lockup =
+            Lockup::new_from_periods_with_cliff(LockupKind::MonthlyWithCliff, 1000, 1000, 4, 2)?;
+        let curr_ts = 1000 + month / 2;
+        let relocked = lockup.relocked(LockupKind::MonthlyWithCliff, 4, curr_ts)?;
+        assert_eq!(relocked.cliff_periods, 2);
+        assert!(lockup.relocked(LockupKind::Cliff, 3, curr_ts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn start_and_stop_unlocking() -> Result<()> {
+        let day = SECS_PER_DAY as i64;
+        let mut lockup = Lockup::new_from_periods(LockupKind::Constant, 1000, 1000, 5)?;
+        assert!(lockup.stop_unlocking(1000).is_err());
+
+        // a Constant lockup keeps its duration until the unlock starts
+        let curr_ts = 1000 + 3 * day;
+        lockup.start_unlocking(curr_ts)?;
+        assert_eq!(lockup.kind, LockupKind::Cliff);
+        assert_eq!(lockup.seconds_left(curr_ts), 5 * day as u64);
+        assert_eq!(lockup.seconds_left(curr_ts + 2 * day), 3 * day as u64);
+        assert!(lockup.start_unlocking(curr_ts).is_err());
+
+        // freezing keeps what is left
+        let curr_ts = curr_ts + 2 * day;
+        lockup.stop_unlocking(curr_ts)?;
+        assert_eq!(lockup.kind, LockupKind::Constant);
+        assert_eq!(lockup.seconds_left(curr_ts + 10 * day), 3 * day as u64);
+        Ok(())
+    }
+
     #[test]
     pub fn days_left_start() -> Result<()> {
         run_test_days_left(TestDaysLeft {
@@ -731,6 +1053,246 @@ mod tests {
         })
     }
 
+    #[test]
+    pub fn voting_power_monthly_with_cliff_warmup() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: -0.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_start() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 0.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_one_quarter() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 0.25,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_six() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 6.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_before_cliff_end() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 11.75,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_cliff_end() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 12.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_after_cliff_end() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 12.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_thirty() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 30.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_end() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 48,
+            cliff_periods: 12,
+            curr_period: 48.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_without_cliff() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 10,
+            cliff_periods: 0,
+            curr_period: 1.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_monthly_with_cliff_only_cliff() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::MonthlyWithCliff,
+            periods_total: 10,
+            cliff_periods: 10,
+            curr_period: 1.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_weekly_start() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Weekly,
+            periods_total: 10,
+            cliff_periods: 0,
+            curr_period: 0.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_weekly_one_half() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Weekly,
+            periods_total: 10,
+            cliff_periods: 0,
+            curr_period: 0.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_weekly_one() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Weekly,
+            periods_total: 10,
+            cliff_periods: 0,
+            curr_period: 1.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_weekly_nine_dot_five() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Weekly,
+            periods_total: 10,
+            cliff_periods: 0,
+            curr_period: 9.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_weekly_saturation() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Weekly,
+            periods_total: 60,
+            cliff_periods: 0,
+            curr_period: 0.25,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_quarterly_start() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Quarterly,
+            periods_total: 8,
+            cliff_periods: 0,
+            curr_period: 0.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_quarterly_one_half() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Quarterly,
+            periods_total: 8,
+            cliff_periods: 0,
+            curr_period: 0.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_quarterly_three_and_a_quarter() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Quarterly,
+            periods_total: 8,
+            cliff_periods: 0,
+            curr_period: 3.25,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_quarterly_eight() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Quarterly,
+            periods_total: 8,
+            cliff_periods: 0,
+            curr_period: 8.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_yearly_start() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Yearly,
+            periods_total: 4,
+            cliff_periods: 0,
+            curr_period: 0.0,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_yearly_one_half() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Yearly,
+            periods_total: 4,
+            cliff_periods: 0,
+            curr_period: 0.5,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_yearly_two_and_a_quarter() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Yearly,
+            periods_total: 4,
+            cliff_periods: 0,
+            curr_period: 2.25,
+        })
+    }
+
+    #[test]
+    pub fn voting_power_yearly_four() -> Result<()> {
+        run_test_periodic_vesting_power(TestPeriodicVestingPower {
+            kind: LockupKind::Yearly,
+            periods_total: 4,
+            cliff_periods: 0,
+            curr_period: 4.0,
+        })
+    }
+
     struct TestDaysLeft {
         expected_days_left: u64,
         days_total: f64,
@@ -751,6 +1313,13 @@ mod tests {
         kind: LockupKind,
     }
 
+    struct TestPeriodicVestingPower {
+        kind: LockupKind,
+        periods_total: u64,
+        cliff_periods: u64,
+        curr_period: f64,
+    }
+
     fn run_test_days_left(t: TestDaysLeft) -> Result<()> {
         let start_ts = 1634929833;
         let end_ts = start_ts + days_to_secs(t.days_total);
@@ -759,7 +1328,9 @@ mod tests {
             kind: LockupKind::Cliff,
             start_ts,
             end_ts,
-            reserved: [0u8; 15],
+            padding: [0; 3],
+            cliff_periods: 0,
+            reserved: [0u8; 8],
         };
         let days_left = l.periods_left(curr_ts)?;
         assert_eq!(days_left, t.expected_days_left);
@@ -774,7 +1345,9 @@ mod tests {
             kind: LockupKind::Monthly,
             start_ts,
             end_ts,
-            reserved: [0u8; 15],
+            padding: [0; 3],
+            cliff_periods: 0,
+            reserved: [0u8; 8],
         };
         let months_left = l.periods_left(curr_ts)?;
         assert_eq!(months_left, t.expected_months_left);
@@ -794,16 +1367,61 @@ mod tests {
                 start_ts,
                 end_ts,
                 kind: t.kind,
-                reserved: [0u8; 15],
+                padding: [0; 3],
+                cliff_periods: 0,
+                reserved: [0u8; 8],
             },
             reserved: [0; 29],
         };
         let curr_ts = start_ts + days_to_secs(t.curr_day);
-        let power = d.voting_power_locked(curr_ts, t.amount_deposited, MAX_SECS_LOCKED)?;
+        let power = d.voting_power_locked(curr_ts, t.amount_deposited, MAX_SECS_LOCKED, None)?;
         assert_eq!(power, t.expected_voting_power);
         Ok(())
     }
 
+    fn run_test_periodic_vesting_power(t: TestPeriodicVestingPower) -> Result<()> {
+        let start_ts = 1634929833;
+        // whole tokens per period, with 6 decimals
+        let amount_deposited = t.periods_total * 1_000_000;
+        let d = DepositEntry {
+            is_used: true,
+            voting_mint_config_idx: 0,
+            amount_deposited_native: amount_deposited,
+            amount_initially_locked_native: amount_deposited,
+            allow_clawback: false,
+            lockup: Lockup::new_from_periods_with_cliff(
+                t.kind,
+                start_ts,
+                start_ts,
+                t.periods_total as u32,
+                t.cliff_periods as u32,
+            )?,
+            reserved: [0; 29],
+        };
+        let period_secs = t.kind.period_secs() as f64;
+        let curr_ts = start_ts + (period_secs * t.curr_period).round() as i64;
+        let expected_voting_power = locked_periodic_power(
+            amount_deposited,
+            t.kind,
+            t.curr_period,
+            t.periods_total,
+            t.cliff_periods,
+        );
+        let power = d.voting_power_locked(curr_ts, amount_deposited, MAX_SECS_LOCKED, None)?;
+        assert_eq!(power, expected_voting_power);
+
+        // The lockup can't be weakened, so nothing changes for the guaranteed power
+        let guaranteed_power = d.voting_power_locked_guaranteed(
+            curr_ts,
+            curr_ts,
+            amount_deposited,
+            MAX_SECS_LOCKED,
+            None,
+        )?;
+        assert_eq!(guaranteed_power, expected_voting_power);
+        Ok(())
+    }
+
     fn days_to_secs(days: f64) -> i64 {
         let d = (SECS_PER_DAY as f64) * days;
         d.round() as i64
@@ -837,6 +1455,35 @@ mod tests {
         (total + 0.0001).floor() as u64
     }
 
+    // Like locked_daily_power(), but for any vesting period length and where
+    // the tokens of all periods up to the end of the cliff vest at the end of
+    // the cliff.
+    //
+    // deposit - the amount locked up
+    // kind - the lockup kind, defining the period length
+    // period - the current period in the lockup
+    // total_periods - the number of periods locked up
+    // cliff_periods - the number of periods in the cliff
+    fn locked_periodic_power(
+        amount: u64,
+        kind: LockupKind,
+        period: f64,
+        total_periods: u64,
+        cliff_periods: u64,
+    ) -> u64 {
+        let days_per_period = kind.period_secs() as f64 / SECS_PER_DAY as f64;
+        let mut total = 0f64;
+        for k in 1..=total_periods {
+            let vesting_period = k.max(cliff_periods) as f64;
+            if vesting_period <= period {
+                continue;
+            }
+            let remaining_days = (vesting_period - period) * days_per_period;
+            total += locked_cliff_power_float(amount / total_periods, remaining_days);
+        }
+        (total + 0.0001).floor() as u64
+    }
+
     fn locked_cliff_power_float(amount: u64, remaining_days: f64) -> f64 {
         let relevant_days = if remaining_days < MAX_DAYS_LOCKED as f64 {
             remaining_days
@@ -1,11 +1,22 @@
+pub use crate::governance::VoterWeightAction;
 pub use deposit_entry::*;
 pub use lockup::*;
 pub use registrar::*;
+pub use registrar_stats::*;
+pub use reward_distribution::*;
+pub use vesting_schedule::*;
 pub use voter::*;
 pub use voting_mint_config::*;
+pub use voting_mint_rate::*;
+pub use weight_checkpoints::*;
 
 mod deposit_entry;
 mod lockup;
 mod registrar;
+mod registrar_stats;
+mod reward_distribution;
+mod vesting_schedule;
 mod voter;
 mod voting_mint_config;
+mod voting_mint_rate;
+mod weight_checkpoints;
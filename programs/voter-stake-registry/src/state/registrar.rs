@@ -1,7 +1,19 @@
 use crate::error::*;
+use crate::max_voter_weight_record;
 use crate::state::voting_mint_config::VotingMintConfig;
+use crate::state::voting_mint_rate::{load_voting_mint_rates, VotingMintRate};
+use crate::token_interface::unpack_mint;
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
+use std::cell::{Ref, RefMut};
+use std::iter::Chain;
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
+use std::slice;
+
+// Generate a MaxVoterWeightRecord Anchor wrapper, owned by the current program.
+// Like VoterWeightRecords, these are defined by the SPL governance program but
+// owned by this program.
+max_voter_weight_record!(crate::ID);
 
 /// Instance of a voting rights distributor.
 #[account(zero_copy)]
@@ -11,19 +23,60 @@ pub struct Registrar {
     pub realm: Pubkey,
     pub realm_governing_token_mint: Pubkey,
     pub realm_authority: Pubkey,
-    pub reserved1: [u8; 32],
 
-    /// Storage for voting mints and their configuration.
-    /// The length should be adjusted for one's use case.
+    /// Owner of the token accounts that receive early unlock penalties.
+    ///
+    /// Default if early unlocks are disabled. See set_early_unlock_treasury.
+    pub early_unlock_treasury: Pubkey,
+
+    /// Storage for the first voting mints and their configuration.
+    ///
+    /// Further voting mint slots are stored after the Registrar in the account
+    /// data, see grow_registrar. Use RegistrarRef to access all of them.
     pub voting_mints: [VotingMintConfig; 4],
 
     /// Debug only: time offset, to allow tests to move forward in time.
     pub time_offset: i64,
     pub bump: u8,
-    pub reserved2: [u8; 7],
-    pub reserved3: [u64; 11], // split because `Default` does not support [u8; 95]
+
+    /// While set, instructions that add tokens to deposits, move tokens
+    /// between deposits, change lockups, unlock early or claim rewards fail.
+    /// Withdraws, clawbacks, vote weight updates and closing accounts stay
+    /// possible. See set_paused.
+    pub paused: bool,
+
+    /// How the predecessor plugin's weight combines with this registrar's.
+    pub predecessor_weight_mode: PredecessorWeightMode,
+
+    /// Whether the RegistrarStats account exists. Instructions that change
+    /// deposit entries must then pass it, see create_registrar_stats.
+    pub has_registrar_stats: bool,
+
+    /// Whether voter weight records are written from the voters' weight
+    /// checkpoints instead of their current deposits.
+    ///
+    /// While set, update_historical_voter_weight_record is the only way to
+    /// write a voter's weight and update_voter_weight_record fails, so a
+    /// proposal can't be voted on with both. See set_historical_voter_weight.
+    pub historical_voter_weight: bool,
+    pub reserved2: [u8; 3],
+
+    /// Key that can pause and unpause the registrar, besides the realm
+    /// authority.
+    ///
+    /// Default if there is none. See set_pause_guardian.
+    pub pause_guardian: Pubkey,
+
+    /// Voter weight plugin whose VoterWeightRecords are used as input by
+    /// update_voter_weight_record.
+    ///
+    /// Default if there is none. See configure_predecessor_plugin.
+    pub predecessor_program_id: Pubkey,
+    pub reserved3: [u64; 3],
 }
-const_assert!(std::mem::size_of::<Registrar>() == 5 * 32 + 4 * 152 + 8 + 1 + 95);
+const_assert!(
+    std::mem::size_of::<Registrar>() == 7 * 32 + 4 * 152 + 8 + 1 + 1 + 1 + 1 + 1 + 3 + 24
+);
 const_assert!(std::mem::size_of::<Registrar>() % 8 == 0);
 
 impl Registrar {
@@ -35,25 +88,232 @@ impl Registrar {
             .unwrap()
     }
 
-    pub fn voting_mint_config_index(&self, mint: Pubkey) -> Result<usize> {
-        self.voting_mints
+    pub fn has_predecessor(&self) -> bool {
+        self.predecessor_program_id != Pubkey::default()
+    }
+}
+
+/// How update_voter_weight_record combines the weight of a predecessor
+/// plugin with the registrar's own.
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredecessorWeightMode {
+    /// The weights are added, like for tokens counted by another plugin.
+    Add,
+
+    /// The weights are multiplied, for gating plugins like civic passes
+    /// that produce a weight of zero or one.
+    Multiply,
+}
+
+impl Default for PredecessorWeightMode {
+    fn default() -> Self {
+        PredecessorWeightMode::Add
+    }
+}
+
+impl PredecessorWeightMode {
+    pub fn combine(&self, predecessor_weight: u64, weight: u64) -> Result<u64> {
+        match self {
+            PredecessorWeightMode::Add => predecessor_weight.checked_add(weight),
+            PredecessorWeightMode::Multiply => predecessor_weight.checked_mul(weight),
+        }
+        .ok_or_else(|| error!(VsrError::VoterWeightOverflow))
+    }
+}
+
+/// The maximum number of voting mints a registrar can hold.
+///
+/// Deposit entries refer to voting mints by a u8 index.
+pub const MAX_VOTING_MINTS: usize = u8::MAX as usize + 1;
+
+/// The number of voting mint slots after the Registrar struct in the account data.
+///
+/// Trailing bytes that don't form a full slot are ignored.
+fn extra_voting_mints_len(data: &[u8]) -> usize {
+    (data.len() - 8 - size_of::<Registrar>()) / size_of::<VotingMintConfig>()
+}
+
+/// Read access to a registrar account, including all its voting mint slots.
+///
+/// The registrar's `voting_mints` array holds the first slots, the ones
+/// added with grow_registrar follow the Registrar in the account data.
+pub struct RegistrarRef<'a> {
+    registrar: Ref<'a, Registrar>,
+    extra_voting_mints: Ref<'a, [VotingMintConfig]>,
+
+    /// The rates of priced voting mints, if loaded with load_full_with_rates().
+    voting_mint_rates: Vec<VotingMintRate>,
+}
+
+/// Write access to a registrar account, including all its voting mint slots.
+pub struct RegistrarRefMut<'a> {
+    registrar: RefMut<'a, Registrar>,
+    extra_voting_mints: RefMut<'a, [VotingMintConfig]>,
+}
+
+pub trait RegistrarLoader {
+    /// Like AccountLoader::load(), but gives access to all voting mint slots.
+    fn load_full(&self) -> Result<RegistrarRef<'_>>;
+
+    /// Like load_full(), but also reads the VotingMintRate accounts of the
+    /// registrar among `accounts`. Needed to compute the vote weight of
+    /// deposits of priced voting mints.
+    fn load_full_with_rates(&self, accounts: &[AccountInfo]) -> Result<RegistrarRef<'_>>;
+
+    /// Like AccountLoader::load_mut(), but gives access to all voting mint slots.
+    fn load_full_mut(&self) -> Result<RegistrarRefMut<'_>>;
+}
+
+impl<'info> RegistrarLoader for AccountLoader<'info, Registrar> {
+    fn load_full(&self) -> Result<RegistrarRef<'_>> {
+        // Validates the account discriminator
+        drop(self.load()?);
+
+        let data = self.as_ref().try_borrow_data()?;
+        let (registrar, extra_voting_mints) = Ref::map_split(data, |data| {
+            let extra_len = extra_voting_mints_len(data);
+            let (registrar, extra) = data[8..].split_at(size_of::<Registrar>());
+            (
+                bytemuck::from_bytes(registrar),
+                bytemuck::cast_slice(&extra[..extra_len * size_of::<VotingMintConfig>()]),
+            )
+        });
+        Ok(RegistrarRef {
+            registrar,
+            extra_voting_mints,
+            voting_mint_rates: Vec::new(),
+        })
+    }
+
+    fn load_full_with_rates(&self, accounts: &[AccountInfo]) -> Result<RegistrarRef<'_>> {
+        let mut registrar = self.load_full()?;
+        registrar.voting_mint_rates = load_voting_mint_rates(&self.key(), accounts)?;
+        Ok(registrar)
+    }
+
+    fn load_full_mut(&self) -> Result<RegistrarRefMut<'_>> {
+        // Validates the account discriminator and that the account is writable
+        drop(self.load_mut()?);
+
+        let data = self.as_ref().try_borrow_mut_data()?;
+        let (registrar, extra_voting_mints) = RefMut::map_split(data, |data| {
+            let extra_len = extra_voting_mints_len(data);
+            let (registrar, extra) = data[8..].split_at_mut(size_of::<Registrar>());
+            (
+                bytemuck::from_bytes_mut(registrar),
+                bytemuck::cast_slice_mut(&mut extra[..extra_len * size_of::<VotingMintConfig>()]),
+            )
+        });
+        Ok(RegistrarRefMut {
+            registrar,
+            extra_voting_mints,
+        })
+    }
+}
+
+impl<'a> Deref for RegistrarRef<'a> {
+    type Target = Registrar;
+    fn deref(&self) -> &Registrar {
+        &self.registrar
+    }
+}
+
+impl<'a> Deref for RegistrarRefMut<'a> {
+    type Target = Registrar;
+    fn deref(&self) -> &Registrar {
+        &self.registrar
+    }
+}
+
+impl<'a> DerefMut for RegistrarRefMut<'a> {
+    fn deref_mut(&mut self) -> &mut Registrar {
+        &mut self.registrar
+    }
+}
+
+/// Access to all voting mint slots of a registrar.
+pub trait RegistrarVotingMints: Deref<Target = Registrar> {
+    /// The voting mint slots after the Registrar struct in the account data.
+    fn extra_voting_mints(&self) -> &[VotingMintConfig];
+
+    /// The number of voting mint slots, used or not.
+    fn voting_mints_len(&self) -> usize {
+        self.deref().voting_mints.len() + self.extra_voting_mints().len()
+    }
+
+    fn voting_mints(
+        &self,
+    ) -> Chain<slice::Iter<'_, VotingMintConfig>, slice::Iter<'_, VotingMintConfig>> {
+        self.deref()
+            .voting_mints
             .iter()
+            .chain(self.extra_voting_mints().iter())
+    }
+
+    /// The voting mint config in slot `idx`, panics if out of bounds.
+    fn voting_mint(&self, idx: usize) -> &VotingMintConfig {
+        let inline_len = self.deref().voting_mints.len();
+        if idx < inline_len {
+            &self.deref().voting_mints[idx]
+        } else {
+            &self.extra_voting_mints()[idx - inline_len]
+        }
+    }
+
+    fn voting_mint_config_index(&self, mint: Pubkey) -> Result<usize> {
+        self.voting_mints()
             .position(|r| r.mint == mint)
             .ok_or_else(|| error!(VsrError::VotingMintNotFound))
     }
+}
 
-    pub fn max_vote_weight(&self, mint_accounts: &[AccountInfo]) -> Result<u64> {
-        self.voting_mints
+impl<'a> RegistrarVotingMints for RegistrarRef<'a> {
+    fn extra_voting_mints(&self) -> &[VotingMintConfig] {
+        &self.extra_voting_mints
+    }
+}
+
+impl<'a> RegistrarVotingMints for RegistrarRefMut<'a> {
+    fn extra_voting_mints(&self) -> &[VotingMintConfig] {
+        &self.extra_voting_mints
+    }
+}
+
+impl<'a> RegistrarRef<'a> {
+    /// The voting mint config in slot `idx` to compute vote weight with,
+    /// panics if out of bounds.
+    ///
+    /// For priced voting mints, the vote weight factors are scaled by the
+    /// current rate. Their VotingMintRate must have been loaded, see
+    /// RegistrarLoader::load_full_with_rates().
+    pub fn vote_weight_config(&self, idx: usize) -> Result<VotingMintConfig> {
+        let config = self.voting_mint(idx);
+        if !config.priced {
+            return Ok(*config);
+        }
+        let rate = self
+            .voting_mint_rates
             .iter()
-            .try_fold(0u64, |mut sum, voting_mint_config| -> Result<u64> {
-                if !voting_mint_config.in_use() {
+            .find(|r| r.mint == config.mint)
+            .ok_or_else(|| error!(VsrError::MissingVotingMintRate))?;
+        config.with_rate(rate.current_rate(self.clock_unix_timestamp()))
+    }
+
+    pub fn max_vote_weight(&self, mint_accounts: &[AccountInfo]) -> Result<u64> {
+        self.voting_mints().enumerate().try_fold(
+            0u64,
+            |mut sum, (idx, voting_mint_config)| -> Result<u64> {
+                // Retired mints have no vote weight, their mint isn't needed.
+                if !voting_mint_config.in_use() || voting_mint_config.retired {
                     return Ok(sum);
                 }
+                let voting_mint_config = self.vote_weight_config(idx)?;
                 let mint_account = mint_accounts
                     .iter()
                     .find(|a| a.key() == voting_mint_config.mint)
                     .ok_or_else(|| error!(VsrError::VotingMintNotFound))?;
-                let mint = Account::<Mint>::try_from(mint_account)?;
+                let mint = unpack_mint(mint_account)?;
                 sum = sum
                     .checked_add(voting_mint_config.baseline_vote_weight(mint.supply)?)
                     .ok_or_else(|| error!(VsrError::VoterWeightOverflow))?;
@@ -61,7 +321,20 @@ impl Registrar {
                     .checked_add(voting_mint_config.max_extra_lockup_vote_weight(mint.supply)?)
                     .ok_or_else(|| error!(VsrError::VoterWeightOverflow))?;
                 Ok(sum)
-            })
+            },
+        )
+    }
+}
+
+impl<'a> RegistrarRefMut<'a> {
+    /// The voting mint config in slot `idx`, panics if out of bounds.
+    pub fn voting_mint_mut(&mut self, idx: usize) -> &mut VotingMintConfig {
+        let inline_len = self.registrar.voting_mints.len();
+        if idx < inline_len {
+            &mut self.registrar.voting_mints[idx]
+        } else {
+            &mut self.extra_voting_mints[idx - inline_len]
+        }
     }
 }
 
@@ -0,0 +1,121 @@
+use crate::error::*;
+use crate::state::registrar::{Registrar, MAX_VOTING_MINTS};
+use anchor_lang::prelude::*;
+
+/// Totals of deposit entries of a voting mint.
+#[zero_copy]
+#[derive(Default, PartialEq, Debug)]
+pub struct VotingMintStats {
+    /// Sum of amount_deposited_native of the deposit entries.
+    pub deposited_native: u64,
+
+    /// Sum of amount_initially_locked_native of the deposit entries.
+    pub initially_locked_native: u64,
+
+    /// The number of deposit entries in use.
+    pub deposit_entries: u64,
+}
+const_assert!(std::mem::size_of::<VotingMintStats>() == 3 * 8);
+
+impl VotingMintStats {
+    /// Replaces a voter's share `before` of these totals with `after`.
+    pub fn update(&mut self, before: &VotingMintStats, after: &VotingMintStats) {
+        let update = |total: u64, before: u64, after: u64| {
+            total
+                .checked_sub(before)
+                .unwrap()
+                .checked_add(after)
+                .unwrap()
+        };
+        self.deposited_native = update(
+            self.deposited_native,
+            before.deposited_native,
+            after.deposited_native,
+        );
+        self.initially_locked_native = update(
+            self.initially_locked_native,
+            before.initially_locked_native,
+            after.initially_locked_native,
+        );
+        self.deposit_entries = update(
+            self.deposit_entries,
+            before.deposit_entries,
+            after.deposit_entries,
+        );
+    }
+}
+
+/// Replaces a voter's share `before` of the totals of voting mint `mint_idx`
+/// with `after`, and returns the new totals.
+///
+/// Registrars without a RegistrarStats account have no totals, then this
+/// returns None. Registrars that have one must pass it, see
+/// Registrar::has_registrar_stats.
+pub fn update_registrar_stats(
+    registrar: &Registrar,
+    registrar_stats: &Option<AccountLoader<RegistrarStats>>,
+    mint_idx: usize,
+    before: &VotingMintStats,
+    after: &VotingMintStats,
+) -> Result<Option<VotingMintStats>> {
+    match registrar_stats {
+        Some(registrar_stats) => {
+            let registrar_stats = &mut registrar_stats.load_mut()?;
+            let mint_stats = &mut registrar_stats.voting_mints[mint_idx];
+            mint_stats.update(before, after);
+            Ok(Some(*mint_stats))
+        }
+        None => {
+            require!(
+                !registrar.has_registrar_stats,
+                VsrError::MissingRegistrarStats
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Totals of the deposit entries of all voters of a registrar, per voting mint.
+///
+/// Created by create_registrar_stats, at the address derived from the
+/// registrar, before any voting mint is configured. Instructions that change
+/// deposit entries keep it up to date, log_registrar_stats emits it.
+#[account(zero_copy)]
+pub struct RegistrarStats {
+    pub registrar: Pubkey,
+    pub bump: u8,
+    pub reserved: [u8; 31],
+
+    /// Indexed like the registrar's voting mint slots.
+    pub voting_mints: [VotingMintStats; MAX_VOTING_MINTS],
+}
+const_assert!(std::mem::size_of::<RegistrarStats>() == 32 + 1 + 31 + MAX_VOTING_MINTS * 24);
+const_assert!(std::mem::size_of::<RegistrarStats>() % 8 == 0);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn voting_mint_stats_update() {
+        let mut stats = VotingMintStats::default();
+        let voter = VotingMintStats {
+            deposited_native: 100,
+            initially_locked_native: 80,
+            deposit_entries: 2,
+        };
+        stats.update(&VotingMintStats::default(), &voter);
+        assert_eq!(stats, voter);
+
+        let changed = VotingMintStats {
+            deposited_native: 50,
+            initially_locked_native: 80,
+            deposit_entries: 1,
+        };
+        stats.update(&voter, &changed);
+        assert_eq!(stats, changed);
+
+        stats.update(&changed, &VotingMintStats::default());
+        assert_eq!(stats, VotingMintStats::default());
+    }
+}
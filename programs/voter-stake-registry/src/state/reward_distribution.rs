@@ -0,0 +1,393 @@
+use crate::error::*;
+use crate::state::registrar::RegistrarRef;
+use crate::state::vesting_schedule::VestingSchedules;
+use crate::state::voter::VoterDeposits;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use std::cmp::{max, min};
+use std::convert::TryFrom;
+use std::mem::size_of;
+
+/// Scaling of reward rates and rewards per weight, to keep precision when
+/// dividing by large weights.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Reward tokens that are paid out to voters in proportion to their locked
+/// vote weight.
+///
+/// Created by the realm authority with create_reward_distribution and funded
+/// by anyone with fund_reward_distribution. Each funding is paid out evenly
+/// over the following duration_secs, together with what was left of earlier
+/// fundings.
+///
+/// The u128 fields are placed at offsets that are multiples of 16 to get the
+/// same layout no matter their alignment.
+#[account(zero_copy)]
+#[derive(Default)]
+pub struct RewardDistribution {
+    pub registrar: Pubkey,
+
+    /// Mint of the reward tokens. They are held in the associated token
+    /// account of this distribution.
+    pub reward_mint: Pubkey,
+
+    /// Rewards paid out per unit of weight since creation.
+    ///
+    /// In 1/REWARD_PRECISION units.
+    pub reward_per_weight: u128,
+
+    /// Rewards paid out per second until period_end_ts.
+    ///
+    /// In 1/REWARD_PRECISION units.
+    pub reward_rate: u128,
+
+    /// Sum of the weight of all VoterReward accounts of this distribution.
+    pub total_weight: u64,
+
+    /// Rewards that were due while total_weight was zero, or that positions
+    /// forfeited, see settle_position(). They are added to the next funding.
+    pub undistributed: u64,
+
+    /// Time up to which rewards were paid out.
+    pub last_update_ts: i64,
+
+    /// Time at which the current funding is fully paid out.
+    pub period_end_ts: i64,
+
+    /// Duration over which each funding is paid out.
+    pub duration_secs: u64,
+
+    pub bump: u8,
+    pub reserved: [u8; 23],
+}
+const_assert!(std::mem::size_of::<RewardDistribution>() == 2 * 32 + 2 * 16 + 5 * 8 + 1 + 23);
+const_assert!(std::mem::size_of::<RewardDistribution>() % 8 == 0);
+
+/// A voter's position in a RewardDistribution.
+///
+/// Created by create_voter_reward. The position's weight is the voter's
+/// locked vote weight at its last update, rewards accrue at that weight
+/// until the next update. Claims and update_voter_rewards pay them at no
+/// more than the current weight, see RewardDistribution::settle_position().
+#[account(zero_copy)]
+#[derive(Default)]
+pub struct VoterReward {
+    pub voter: Pubkey,
+    pub distribution: Pubkey,
+
+    /// The distribution's reward_per_weight at the last update.
+    pub reward_per_weight_paid: u128,
+
+    /// The voter's locked vote weight at the last update.
+    pub weight: u64,
+
+    /// Rewards accrued and not claimed yet, in native reward tokens.
+    pub rewards_owed: u64,
+
+    pub bump: u8,
+    pub reserved: [u8; 15],
+}
+const_assert!(std::mem::size_of::<VoterReward>() == 2 * 32 + 16 + 2 * 8 + 1 + 15);
+const_assert!(std::mem::size_of::<VoterReward>() % 8 == 0);
+
+impl RewardDistribution {
+    /// Pays out the rewards up to `curr_ts` to the current total_weight.
+    pub fn accrue(&mut self, curr_ts: i64) {
+        let end_ts = min(curr_ts, self.period_end_ts);
+        if end_ts > self.last_update_ts {
+            let secs = (end_ts - self.last_update_ts) as u128;
+            let rewards = self.reward_rate.checked_mul(secs).unwrap();
+            if self.total_weight > 0 {
+                self.reward_per_weight = self
+                    .reward_per_weight
+                    .checked_add(rewards / self.total_weight as u128)
+                    .unwrap();
+            } else {
+                let rewards = u64::try_from(rewards / REWARD_PRECISION).unwrap();
+                self.undistributed = self.undistributed.checked_add(rewards).unwrap();
+            }
+        }
+        self.last_update_ts = max(self.last_update_ts, curr_ts);
+    }
+
+    /// Adds `amount` native reward tokens, to be paid out over the next
+    /// duration_secs together with the rewards not paid out yet.
+    pub fn fund(&mut self, curr_ts: i64, amount: u64) {
+        self.accrue(curr_ts);
+        let remaining = if self.period_end_ts > curr_ts {
+            let secs = (self.period_end_ts - curr_ts) as u128;
+            self.reward_rate.checked_mul(secs).unwrap()
+        } else {
+            0
+        };
+        let total = (amount as u128 + self.undistributed as u128)
+            .checked_mul(REWARD_PRECISION)
+            .unwrap()
+            .checked_add(remaining)
+            .unwrap();
+        self.reward_rate = total / self.duration_secs as u128;
+        self.period_end_ts = curr_ts
+            .checked_add(i64::try_from(self.duration_secs).unwrap())
+            .unwrap();
+        self.undistributed = 0;
+    }
+
+    /// Pays out the rewards up to `curr_ts` to `position` and changes its
+    /// weight to `weight`.
+    pub fn update_position(&mut self, position: &mut VoterReward, curr_ts: i64, weight: u64) {
+        self.pay_position(position, curr_ts, position.weight);
+        self.set_position_weight(position, weight);
+    }
+
+    /// Like update_position(), but pays the rewards since the last update at
+    /// the lower of the position's weight and `weight`.
+    ///
+    /// Locked vote weight decays, so a position that wasn't updated in a while
+    /// would otherwise earn at a weight the voter no longer has. The rewards
+    /// the position doesn't get are added to the next funding.
+    pub fn settle_position(&mut self, position: &mut VoterReward, curr_ts: i64, weight: u64) {
+        self.pay_position(position, curr_ts, min(position.weight, weight));
+        self.set_position_weight(position, weight);
+    }
+
+    /// Pays out the rewards up to `curr_ts` to `position`, at `paid_weight`
+    /// instead of the position's weight.
+    fn pay_position(&mut self, position: &mut VoterReward, curr_ts: i64, paid_weight: u64) {
+        self.accrue(curr_ts);
+        let reward_per_weight = self.reward_per_weight - position.reward_per_weight_paid;
+        let earned = (paid_weight as u128)
+            .checked_mul(reward_per_weight)
+            .unwrap()
+            / REWARD_PRECISION;
+        let forfeited = ((position.weight - paid_weight) as u128)
+            .checked_mul(reward_per_weight)
+            .unwrap()
+            / REWARD_PRECISION;
+        position.rewards_owed = position
+            .rewards_owed
+            .checked_add(u64::try_from(earned).unwrap())
+            .unwrap();
+        self.undistributed = self
+            .undistributed
+            .checked_add(u64::try_from(forfeited).unwrap())
+            .unwrap();
+        position.reward_per_weight_paid = self.reward_per_weight;
+    }
+
+    fn set_position_weight(&mut self, position: &mut VoterReward, weight: u64) {
+        self.total_weight = self
+            .total_weight
+            .checked_sub(position.weight)
+            .unwrap()
+            .checked_add(weight)
+            .unwrap();
+        position.weight = weight;
+    }
+}
+
+#[macro_export]
+macro_rules! reward_distribution_seeds {
+    ( $distribution:expr ) => {
+        &[
+            $distribution.registrar.as_ref(),
+            b"reward-distribution".as_ref(),
+            $distribution.reward_mint.as_ref(),
+            &[$distribution.bump],
+        ]
+    };
+}
+
+pub use reward_distribution_seeds;
+
+/// The weight a voter's reward positions should have: its locked vote weight.
+///
+/// The `schedules` must include the ones of all Tranches deposits.
+pub fn reward_weight(
+    voter: &impl VoterDeposits,
+    registrar: &RegistrarRef,
+    schedules: &VestingSchedules,
+) -> Result<u64> {
+    let curr_ts = registrar.clock_unix_timestamp();
+    voter.weight_locked_guaranteed(registrar, curr_ts, curr_ts, schedules)
+}
+
+/// True if the account is a `T` owned by this program.
+pub(crate) fn is_program_account<T: Discriminator>(account: &AccountInfo) -> bool {
+    if account.owner != &crate::ID {
+        return false;
+    }
+    let data = match account.try_borrow_data() {
+        Ok(data) => data,
+        Err(_) => return false,
+    };
+    data.len() == 8 + size_of::<T>() && data[..8] == T::discriminator()
+}
+
+/// Updates the reward positions of a voter among `accounts` to its current
+/// locked vote weight. Returns the number of updated positions.
+///
+/// With `settle`, the rewards since the last update are paid at no more than
+/// the current weight, see RewardDistribution::settle_position().
+///
+/// The RewardDistributions of the positions and the VestingSchedules of the
+/// voter's Tranches deposits must be among `accounts` too. Other accounts
+/// are ignored.
+pub fn update_reward_positions(
+    voter_address: &Pubkey,
+    voter: &impl VoterDeposits,
+    registrar: &RegistrarRef,
+    accounts: &[AccountInfo],
+    settle: bool,
+) -> Result<usize> {
+    let mut positions: Vec<&AccountInfo> = Vec::new();
+    for account in accounts {
+        if !is_program_account::<VoterReward>(account)
+            || positions.iter().any(|p| p.key == account.key)
+        {
+            continue;
+        }
+        let data = account.try_borrow_data()?;
+        let position: &VoterReward = bytemuck::from_bytes(&data[8..]);
+        if position.voter == *voter_address {
+            positions.push(account);
+        }
+    }
+    if positions.is_empty() {
+        return Ok(0);
+    }
+
+    let schedules = VestingSchedules::load(voter_address, accounts)?;
+    let weight = reward_weight(voter, registrar, &schedules)?;
+    let curr_ts = registrar.clock_unix_timestamp();
+    for account in positions.iter() {
+        let mut position_data = account.try_borrow_mut_data()?;
+        let position: &mut VoterReward = bytemuck::from_bytes_mut(&mut position_data[8..]);
+        let distribution_account = accounts
+            .iter()
+            .find(|a| *a.key == position.distribution)
+            .filter(|a| is_program_account::<RewardDistribution>(a))
+            .ok_or_else(|| error!(VsrError::MissingVoterReward))?;
+        let mut distribution_data = distribution_account.try_borrow_mut_data()?;
+        let distribution: &mut RewardDistribution =
+            bytemuck::from_bytes_mut(&mut distribution_data[8..]);
+        if settle {
+            distribution.settle_position(position, curr_ts, weight);
+        } else {
+            distribution.update_position(position, curr_ts, weight);
+        }
+    }
+    Ok(positions.len())
+}
+
+/// Updates all reward positions of a voter to its current locked vote weight.
+///
+/// Instructions that change the deposits of a voter call this afterwards,
+/// so the voter doesn't keep accruing rewards at its old weight. All the
+/// voter's VoterReward accounts must be among `accounts`, see
+/// update_reward_positions().
+pub fn sync_voter_rewards(
+    voter_address: &Pubkey,
+    voter: &impl VoterDeposits,
+    registrar: &RegistrarRef,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    let updated = update_reward_positions(voter_address, voter, registrar, accounts, false)?;
+    require_eq!(
+        updated,
+        voter.reward_positions as usize,
+        VsrError::MissingVoterReward
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn reward_accrual() {
+        let start = 10_000_000_000; // arbitrary point
+        let mut distribution = RewardDistribution {
+            duration_secs: 100,
+            last_update_ts: start,
+            period_end_ts: start,
+            ..RewardDistribution::default()
+        };
+        let mut a = VoterReward::default();
+        let mut b = VoterReward::default();
+        distribution.update_position(&mut a, start, 1);
+        distribution.update_position(&mut b, start, 3);
+        assert_eq!(distribution.total_weight, 4);
+
+        // 10 tokens per second, shared 1:3
+        distribution.fund(start, 1_000);
+        distribution.update_position(&mut a, start + 50, 1);
+        assert_eq!(a.rewards_owed, 125);
+
+        // nothing accrues after the period ends
+        distribution.update_position(&mut b, start + 200, 0);
+        distribution.update_position(&mut a, start + 200, 1);
+        assert_eq!(a.rewards_owed, 250);
+        assert_eq!(b.rewards_owed, 750);
+        assert_eq!(distribution.total_weight, 1);
+
+        // funding during a period adds what wasn't paid out yet
+        distribution.fund(start + 200, 1_000);
+        distribution.fund(start + 250, 500);
+        assert_eq!(distribution.period_end_ts, start + 350);
+        distribution.update_position(&mut a, start + 350, 1);
+        assert_eq!(a.rewards_owed, 250 + 1_000 + 500);
+    }
+
+    #[test]
+    pub fn reward_without_weight() {
+        let start = 10_000_000_000; // arbitrary point
+        let mut distribution = RewardDistribution {
+            duration_secs: 100,
+            last_update_ts: start,
+            period_end_ts: start,
+            ..RewardDistribution::default()
+        };
+
+        // rewards that were due without any weight are paid out later
+        distribution.fund(start, 1_000);
+        let mut a = VoterReward::default();
+        distribution.update_position(&mut a, start + 50, 2);
+        assert_eq!(distribution.undistributed, 500);
+        distribution.fund(start + 50, 0);
+        assert_eq!(distribution.undistributed, 0);
+        distribution.update_position(&mut a, start + 150, 2);
+        assert_eq!(a.rewards_owed, 1_000);
+    }
+
+    #[test]
+    pub fn reward_settlement() {
+        let start = 10_000_000_000; // arbitrary point
+        let mut distribution = RewardDistribution {
+            duration_secs: 100,
+            last_update_ts: start,
+            period_end_ts: start,
+            ..RewardDistribution::default()
+        };
+        let mut a = VoterReward::default();
+        let mut b = VoterReward::default();
+        distribution.update_position(&mut a, start, 2);
+        distribution.update_position(&mut b, start, 2);
+
+        // a's lockup expired without an update, it's only paid at weight zero
+        distribution.fund(start, 1_000);
+        distribution.settle_position(&mut a, start + 100, 0);
+        assert_eq!(a.rewards_owed, 0);
+        assert_eq!(distribution.total_weight, 2);
+        assert_eq!(distribution.undistributed, 500);
+
+        // weight increases are paid at the old weight
+        distribution.settle_position(&mut b, start + 100, 4);
+        assert_eq!(b.rewards_owed, 500);
+
+        // the forfeited rewards go to the next funding
+        distribution.fund(start + 100, 0);
+        distribution.settle_position(&mut b, start + 200, 4);
+        assert_eq!(b.rewards_owed, 1_000);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::error::*;
+use crate::state::deposit_entry::DepositEntry;
+use crate::state::lockup::LockupKind;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use std::cmp::min;
+use std::convert::TryFrom;
+use std::mem::size_of;
+
+/// The maximum number of tranches in a vesting schedule.
+pub const MAX_TRANCHES: usize = 32;
+
+/// A part of a vesting schedule: `amount` vests at `unlock_ts`.
+#[zero_copy]
+#[derive(Default, AnchorSerialize, AnchorDeserialize)]
+pub struct Tranche {
+    pub unlock_ts: i64,
+    pub amount: u64,
+}
+const_assert!(std::mem::size_of::<Tranche>() == 2 * 8);
+
+/// The tranches of a deposit entry with LockupKind::Tranches.
+///
+/// Created by grant, at the address derived from the voter and the deposit
+/// entry index. Each tranche is locked like a cliff lockup that ends at
+/// its unlock_ts.
+#[account(zero_copy)]
+#[derive(Default)]
+pub struct VestingSchedule {
+    /// The Voter account whose deposit entry uses this schedule.
+    pub voter: Pubkey,
+
+    /// The index of the deposit entry on the voter.
+    pub deposit_entry_index: u8,
+
+    /// The number of used tranches.
+    pub tranches_len: u8,
+
+    pub bump: u8,
+    pub reserved: [u8; 29],
+
+    /// Tranches, sorted by unlock_ts. Only the first tranches_len are used.
+    pub tranches: [Tranche; MAX_TRANCHES],
+}
+const_assert!(std::mem::size_of::<VestingSchedule>() == 32 + 3 + 29 + MAX_TRANCHES * 16);
+const_assert!(std::mem::size_of::<VestingSchedule>() % 8 == 0);
+
+impl VestingSchedule {
+    pub fn tranches(&self) -> &[Tranche] {
+        &self.tranches[..self.tranches_len as usize]
+    }
+
+    /// Replaces the tranches, checking that they are sorted and non-empty.
+    ///
+    /// Returns the total amount of the tranches.
+    pub fn set_tranches(&mut self, tranches: &[Tranche]) -> Result<u64> {
+        require!(
+            !tranches.is_empty() && tranches.len() <= MAX_TRANCHES,
+            VsrError::InvalidVestingSchedule
+        );
+        require!(
+            tranches
+                .windows(2)
+                .all(|w| w[0].unlock_ts <= w[1].unlock_ts),
+            VsrError::InvalidVestingSchedule
+        );
+        require!(
+            tranches.iter().all(|t| t.amount > 0),
+            VsrError::InvalidVestingSchedule
+        );
+        let total = tranches.iter().try_fold(0u64, |sum, t| {
+            sum.checked_add(t.amount)
+                .ok_or_else(|| error!(VsrError::InvalidVestingSchedule))
+        })?;
+
+        self.tranches = [Tranche::default(); MAX_TRANCHES];
+        self.tranches[..tranches.len()].copy_from_slice(tranches);
+        self.tranches_len = tranches.len() as u8;
+        Ok(total)
+    }
+
+    /// The time the last tranche unlocks.
+    pub fn end_ts(&self) -> i64 {
+        self.tranches().last().map(|t| t.unlock_ts).unwrap_or(0)
+    }
+
+    /// The amount of all tranches that unlocked at `curr_ts`.
+    pub fn vested(&self, curr_ts: i64) -> u64 {
+        self.tranches()
+            .iter()
+            .filter(|t| t.unlock_ts <= curr_ts)
+            .map(|t| t.amount)
+            .sum()
+    }
+
+    /// Sum of amount * min(seconds until unlock, lockup_saturation_secs)
+    /// over all tranches, see DepositEntry::voting_power_tranches().
+    pub fn lockup_secs(&self, curr_ts: i64, lockup_saturation_secs: u64) -> u128 {
+        self.tranches()
+            .iter()
+            .filter(|t| t.unlock_ts > curr_ts)
+            .map(|t| {
+                let secs_left = min((t.unlock_ts - curr_ts) as u64, lockup_saturation_secs);
+                (t.amount as u128).checked_mul(secs_left as u128).unwrap()
+            })
+            .sum()
+    }
+
+    /// Removes `amount` from the tranches that unlock last.
+    ///
+    /// Emptied tranches are dropped.
+    pub fn remove_from_end(&mut self, mut amount: u64) -> Result<()> {
+        while amount > 0 {
+            require_gt!(self.tranches_len, 0, VsrError::InternalProgramError);
+            let last = &mut self.tranches[self.tranches_len as usize - 1];
+            let removed = min(last.amount, amount);
+            last.amount -= removed;
+            amount -= removed;
+            if last.amount == 0 {
+                *last = Tranche::default();
+                self.tranches_len -= 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[macro_export]
+macro_rules! vesting_schedule_seeds {
+    ( $voter:expr, $index:expr, $bump:expr ) => {
+        &[
+            $voter.as_ref(),
+            b"vesting-schedule".as_ref(),
+            &[$index],
+            &[$bump],
+        ]
+    };
+}
+
+pub use vesting_schedule_seeds;
+
+/// The vesting schedules of a voter that were passed to an instruction.
+///
+/// Deposit entries with LockupKind::Tranches need their schedule to compute
+/// vested amounts and vote weight.
+#[derive(Default)]
+pub struct VestingSchedules {
+    schedules: Vec<VestingSchedule>,
+}
+
+impl VestingSchedules {
+    /// True if the account is a VestingSchedule owned by this program.
+    pub fn is_vesting_schedule(account: &AccountInfo) -> bool {
+        if account.owner != &crate::ID {
+            return false;
+        }
+        let data = match account.try_borrow_data() {
+            Ok(data) => data,
+            Err(_) => return false,
+        };
+        data.len() == 8 + size_of::<VestingSchedule>()
+            && data[..8] == VestingSchedule::discriminator()
+    }
+
+    /// Loads the schedules for `voter` among `accounts`. Other accounts are ignored.
+    pub fn load(voter: &Pubkey, accounts: &[AccountInfo]) -> Result<Self> {
+        let mut schedules = Vec::new();
+        for account in accounts {
+            if !Self::is_vesting_schedule(account) {
+                continue;
+            }
+            let data = account.try_borrow_data()?;
+            let schedule: &VestingSchedule = bytemuck::from_bytes(&data[8..]);
+            if schedule.voter == *voter {
+                schedules.push(*schedule);
+            }
+        }
+        Ok(Self { schedules })
+    }
+
+    /// The schedule for the deposit entry at `deposit_entry_index`.
+    ///
+    /// Errors if the deposit entry uses LockupKind::Tranches but its schedule
+    /// wasn't passed. Returns None for other deposit entries.
+    pub fn for_deposit(
+        &self,
+        deposit_entry_index: usize,
+        deposit_entry: &DepositEntry,
+    ) -> Result<Option<&VestingSchedule>> {
+        if deposit_entry.lockup.kind != LockupKind::Tranches {
+            return Ok(None);
+        }
+        let index = u8::try_from(deposit_entry_index).unwrap();
+        self.schedules
+            .iter()
+            .find(|s| s.deposit_entry_index == index)
+            .map(Some)
+            .ok_or_else(|| error!(VsrError::MissingVestingSchedule))
+    }
+}
@@ -1,45 +1,301 @@
 use crate::error::*;
 use crate::state::deposit_entry::DepositEntry;
-use crate::state::registrar::Registrar;
+use crate::state::registrar::{Registrar, RegistrarRef};
+use crate::state::registrar_stats::VotingMintStats;
+use crate::state::vesting_schedule::VestingSchedules;
 use anchor_lang::prelude::*;
 use spl_governance::state::token_owner_record;
+use std::cell::{Ref, RefMut};
+use std::iter::Chain;
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
+use std::slice;
 
 /// User account for minting voting rights.
 #[account(zero_copy)]
 pub struct Voter {
     pub voter_authority: Pubkey,
     pub registrar: Pubkey,
+
+    /// The first deposit entry slots.
+    ///
+    /// Further slots are stored after the Voter in the account data, see
+    /// grow_voter. Use VoterRef and VoterRefMut to access all of them.
     pub deposits: [DepositEntry; 32],
     pub voter_bump: u8,
     pub voter_weight_record_bump: u8,
-    pub reserved: [u8; 94],
+
+    /// The voter authority of the voter this voter delegated its vote weight to.
+    ///
+    /// Default if there is no delegate. See set_delegate.
+    pub delegate: Pubkey,
+
+    /// The number of VoterReward accounts of this voter.
+    ///
+    /// Instructions that change deposits must update all of them, see
+    /// sync_voter_rewards.
+    pub reward_positions: u8,
+
+    /// Whether the voter has a VoterWeightCheckpoints account.
+    ///
+    /// Instructions that change the voter's weight must append to it, see
+    /// record_weight_checkpoint.
+    pub has_weight_checkpoints: bool,
+
+    /// The voter authority this voter is being transferred to.
+    ///
+    /// Default if there is no pending transfer. See propose_voter_authority.
+    pub pending_authority: Pubkey,
+
+    /// The number of voters that delegated their vote weight to this voter.
+    ///
+    /// Their instructions reset this voter's voter weight record, so this
+    /// voter can't be transferred or closed while it has delegators. See
+    /// set_delegate.
+    pub delegators: u32,
+    pub reserved: [u8; 24],
 }
-const_assert!(std::mem::size_of::<Voter>() == 2 * 32 + 32 * 80 + 2 + 94);
+const_assert!(std::mem::size_of::<Voter>() == 2 * 32 + 32 * 80 + 2 + 32 + 1 + 1 + 32 + 4 + 24);
 const_assert!(std::mem::size_of::<Voter>() % 8 == 0);
 
 impl Voter {
-    /// The full vote weight available to the voter
-    pub fn weight(&self, registrar: &Registrar) -> Result<u64> {
-        let curr_ts = registrar.clock_unix_timestamp();
-        self.deposits
+    pub fn load_token_owner_record(
+        &self,
+        account_info: &AccountInfo,
+        registrar: &Registrar,
+    ) -> Result<token_owner_record::TokenOwnerRecordV2> {
+        let record = token_owner_record::get_token_owner_record_data_for_realm_and_governing_mint(
+            &registrar.governance_program_id,
+            account_info,
+            &registrar.realm,
+            &registrar.realm_governing_token_mint,
+        )?;
+        require_keys_eq!(
+            record.governing_token_owner,
+            self.voter_authority,
+            VsrError::InvalidTokenOwnerRecord
+        );
+        Ok(record)
+    }
+
+    pub fn has_delegate(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    /// Like load_token_owner_record(), but for the record of the delegate.
+    pub fn load_delegate_token_owner_record(
+        &self,
+        account_info: &AccountInfo,
+        registrar: &Registrar,
+    ) -> Result<token_owner_record::TokenOwnerRecordV2> {
+        require!(self.has_delegate(), VsrError::InvalidDelegate);
+        let record = token_owner_record::get_token_owner_record_data_for_realm_and_governing_mint(
+            &registrar.governance_program_id,
+            account_info,
+            &registrar.realm,
+            &registrar.realm_governing_token_mint,
+        )?;
+        require_keys_eq!(
+            record.governing_token_owner,
+            self.delegate,
+            VsrError::InvalidTokenOwnerRecord
+        );
+        Ok(record)
+    }
+}
+
+/// The maximum number of deposit entries a voter can hold.
+///
+/// Deposit entries are referred to by a u8 index.
+pub const MAX_DEPOSIT_ENTRIES: usize = u8::MAX as usize + 1;
+
+/// The number of deposit entry slots after the Voter struct in the account data.
+///
+/// Trailing bytes that don't form a full slot are ignored.
+fn extra_deposits_len(data: &[u8]) -> usize {
+    (data.len() - 8 - size_of::<Voter>()) / size_of::<DepositEntry>()
+}
+
+/// Read access to a voter account, including all its deposit entry slots.
+///
+/// The voter's `deposits` array holds the first slots, the ones added with
+/// grow_voter follow the Voter in the account data.
+pub struct VoterRef<'a> {
+    voter: Ref<'a, Voter>,
+    extra_deposits: Ref<'a, [DepositEntry]>,
+}
+
+/// Write access to a voter account, including all its deposit entry slots.
+pub struct VoterRefMut<'a> {
+    voter: RefMut<'a, Voter>,
+    extra_deposits: RefMut<'a, [DepositEntry]>,
+}
+
+pub trait VoterLoader {
+    /// Like AccountLoader::load(), but gives access to all deposit entries.
+    fn load_full(&self) -> Result<VoterRef<'_>>;
+
+    /// Like AccountLoader::load_mut(), but gives access to all deposit entries.
+    fn load_full_mut(&self) -> Result<VoterRefMut<'_>>;
+
+    /// Like AccountLoader::load_init(), but gives access to all deposit entries.
+    fn load_full_init(&self) -> Result<VoterRefMut<'_>>;
+}
+
+fn split_voter_data_mut<'a>(account_info: &'a AccountInfo) -> Result<VoterRefMut<'a>> {
+    let data = account_info.try_borrow_mut_data()?;
+    let (voter, extra_deposits) = RefMut::map_split(data, |data| {
+        let extra_len = extra_deposits_len(data);
+        let (voter, extra) = data[8..].split_at_mut(size_of::<Voter>());
+        (
+            bytemuck::from_bytes_mut(voter),
+            bytemuck::cast_slice_mut(&mut extra[..extra_len * size_of::<DepositEntry>()]),
+        )
+    });
+    Ok(VoterRefMut {
+        voter,
+        extra_deposits,
+    })
+}
+
+impl<'info> VoterLoader for AccountLoader<'info, Voter> {
+    fn load_full(&self) -> Result<VoterRef<'_>> {
+        // Validates the account discriminator
+        drop(self.load()?);
+
+        let data = self.as_ref().try_borrow_data()?;
+        let (voter, extra_deposits) = Ref::map_split(data, |data| {
+            let extra_len = extra_deposits_len(data);
+            let (voter, extra) = data[8..].split_at(size_of::<Voter>());
+            (
+                bytemuck::from_bytes(voter),
+                bytemuck::cast_slice(&extra[..extra_len * size_of::<DepositEntry>()]),
+            )
+        });
+        Ok(VoterRef {
+            voter,
+            extra_deposits,
+        })
+    }
+
+    fn load_full_mut(&self) -> Result<VoterRefMut<'_>> {
+        // Validates the account discriminator and that the account is writable
+        drop(self.load_mut()?);
+        split_voter_data_mut(self.as_ref())
+    }
+
+    fn load_full_init(&self) -> Result<VoterRefMut<'_>> {
+        // Validates that the discriminator is unset and that the account is writable
+        drop(self.load_init()?);
+        split_voter_data_mut(self.as_ref())
+    }
+}
+
+impl<'a> Deref for VoterRef<'a> {
+    type Target = Voter;
+    fn deref(&self) -> &Voter {
+        &self.voter
+    }
+}
+
+impl<'a> Deref for VoterRefMut<'a> {
+    type Target = Voter;
+    fn deref(&self) -> &Voter {
+        &self.voter
+    }
+}
+
+impl<'a> DerefMut for VoterRefMut<'a> {
+    fn deref_mut(&mut self) -> &mut Voter {
+        &mut self.voter
+    }
+}
+
+/// Access to all deposit entries of a voter and the vote weight they produce.
+pub trait VoterDeposits: Deref<Target = Voter> {
+    /// The deposit entry slots after the Voter struct in the account data.
+    fn extra_deposits(&self) -> &[DepositEntry];
+
+    /// The number of deposit entry slots, used or not.
+    fn deposits_len(&self) -> usize {
+        self.deref().deposits.len() + self.extra_deposits().len()
+    }
+
+    fn deposits(&self) -> Chain<slice::Iter<'_, DepositEntry>, slice::Iter<'_, DepositEntry>> {
+        self.deref()
+            .deposits
             .iter()
-            .filter(|d| d.is_used)
-            .try_fold(0u64, |sum, d| {
+            .chain(self.extra_deposits().iter())
+    }
+
+    /// Totals of the voter's deposit entries of voting mint `mint_idx`.
+    ///
+    /// Instructions that change deposit entries update the RegistrarStats with
+    /// the difference of these before and after.
+    fn mint_stats(&self, mint_idx: usize) -> VotingMintStats {
+        self.deposits()
+            .filter(|d| d.is_used && d.voting_mint_config_idx as usize == mint_idx)
+            .fold(VotingMintStats::default(), |stats, d| VotingMintStats {
+                deposited_native: stats
+                    .deposited_native
+                    .checked_add(d.amount_deposited_native)
+                    .unwrap(),
+                initially_locked_native: stats
+                    .initially_locked_native
+                    .checked_add(d.amount_initially_locked_native)
+                    .unwrap(),
+                deposit_entries: stats.deposit_entries + 1,
+            })
+    }
+
+    /// The full vote weight available to the voter
+    ///
+    /// The `schedules` must include the ones of all Tranches deposits.
+    fn weight(&self, registrar: &RegistrarRef, schedules: &VestingSchedules) -> Result<u64> {
+        self.weight_at(registrar, registrar.clock_unix_timestamp(), schedules)
+    }
+
+    /// The full vote weight the voter's current deposits give at `curr_ts`
+    fn weight_at(
+        &self,
+        registrar: &RegistrarRef,
+        curr_ts: i64,
+        schedules: &VestingSchedules,
+    ) -> Result<u64> {
+        self.deposits()
+            .enumerate()
+            .filter(|(_, d)| d.is_used)
+            .try_fold(0u64, |sum, (i, d)| {
                 d.voting_power(
-                    &registrar.voting_mints[d.voting_mint_config_idx as usize],
+                    &registrar.vote_weight_config(d.voting_mint_config_idx as usize)?,
                     curr_ts,
+                    schedules.for_deposit(i, d)?,
                 )
                 .map(|vp| sum.checked_add(vp).unwrap())
             })
     }
 
+    /// The vote weight the voter can use itself
+    ///
+    /// That's zero if the voter delegated its vote weight.
+    fn undelegated_weight(
+        &self,
+        registrar: &RegistrarRef,
+        schedules: &VestingSchedules,
+    ) -> Result<u64> {
+        if self.has_delegate() {
+            return Ok(0);
+        }
+        self.weight(registrar, schedules)
+    }
+
     /// The vote weight available to the voter when ignoring any lockup effects
-    pub fn weight_baseline(&self, registrar: &Registrar) -> Result<u64> {
-        self.deposits
-            .iter()
+    fn weight_baseline(&self, registrar: &RegistrarRef) -> Result<u64> {
+        self.deposits()
             .filter(|d| d.is_used)
             .try_fold(0u64, |sum, d| {
-                registrar.voting_mints[d.voting_mint_config_idx as usize]
+                registrar
+                    .vote_weight_config(d.voting_mint_config_idx as usize)?
                     .baseline_vote_weight(d.amount_deposited_native)
                     .map(|vp| sum.checked_add(vp).unwrap())
             })
@@ -47,18 +303,20 @@ impl Voter {
 
     /// The extra lockup vote weight that the user is guaranteed to have at `at_ts`, assuming
     /// they withdraw and unlock as much as possible starting from `curr_ts`.
-    pub fn weight_locked_guaranteed(
+    fn weight_locked_guaranteed(
         &self,
-        registrar: &Registrar,
+        registrar: &RegistrarRef,
         curr_ts: i64,
         at_ts: i64,
+        schedules: &VestingSchedules,
     ) -> Result<u64> {
         require_gte!(at_ts, curr_ts, VsrError::InvalidTimestampArguments);
-        self.deposits
-            .iter()
-            .filter(|d| d.is_used)
-            .try_fold(0u64, |sum, d| {
-                let mint_config = &registrar.voting_mints[d.voting_mint_config_idx as usize];
+        self.deposits()
+            .enumerate()
+            .filter(|(_, d)| d.is_used)
+            .try_fold(0u64, |sum, (i, d)| {
+                let mint_config =
+                    registrar.vote_weight_config(d.voting_mint_config_idx as usize)?;
                 let max_locked_vote_weight =
                     mint_config.max_extra_lockup_vote_weight(d.amount_initially_locked_native)?;
                 let amount = d.voting_power_locked_guaranteed(
@@ -66,40 +324,46 @@ impl Voter {
                     at_ts,
                     max_locked_vote_weight,
                     mint_config.lockup_saturation_secs,
+                    schedules.for_deposit(i, d)?,
                 )?;
                 Ok(sum.checked_add(amount).unwrap())
             })
     }
+}
 
-    pub fn active_deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
+impl<'a> VoterDeposits for VoterRef<'a> {
+    fn extra_deposits(&self) -> &[DepositEntry] {
+        &self.extra_deposits
+    }
+}
+
+impl<'a> VoterDeposits for VoterRefMut<'a> {
+    fn extra_deposits(&self) -> &[DepositEntry] {
+        &self.extra_deposits
+    }
+}
+
+impl<'a> VoterRefMut<'a> {
+    /// The deposit entry in slot `index`, used or not.
+    pub fn deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
         let index = index as usize;
         require_gt!(
-            self.deposits.len(),
+            self.deposits_len(),
             index,
             VsrError::OutOfBoundsDepositEntryIndex
         );
-        let d = &mut self.deposits[index];
-        require!(d.is_used, VsrError::UnusedDepositEntryIndex);
-        Ok(d)
+        let inline_len = self.voter.deposits.len();
+        Ok(if index < inline_len {
+            &mut self.voter.deposits[index]
+        } else {
+            &mut self.extra_deposits[index - inline_len]
+        })
     }
 
-    pub fn load_token_owner_record(
-        &self,
-        account_info: &AccountInfo,
-        registrar: &Registrar,
-    ) -> Result<token_owner_record::TokenOwnerRecordV2> {
-        let record = token_owner_record::get_token_owner_record_data_for_realm_and_governing_mint(
-            &registrar.governance_program_id,
-            account_info,
-            &registrar.realm,
-            &registrar.realm_governing_token_mint,
-        )?;
-        require_keys_eq!(
-            record.governing_token_owner,
-            self.voter_authority,
-            VsrError::InvalidTokenOwnerRecord
-        );
-        Ok(record)
+    pub fn active_deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
+        let d = self.deposit_mut(index)?;
+        require!(d.is_used, VsrError::UnusedDepositEntryIndex);
+        Ok(d)
     }
 }
 
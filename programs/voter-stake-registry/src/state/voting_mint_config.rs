@@ -1,9 +1,10 @@
 use crate::error::*;
+use crate::state::lockup::{Lockup, LockupKind};
 use anchor_lang::__private::bytemuck::Zeroable;
 use anchor_lang::prelude::*;
 use std::convert::TryFrom;
 
-const SCALED_FACTOR_BASE: u64 = 1_000_000_000;
+pub const SCALED_FACTOR_BASE: u64 = 1_000_000_000;
 
 /// Exchange rate for an asset that can be used to mint voting rights.
 ///
@@ -38,11 +39,57 @@ pub struct VotingMintConfig {
     /// Number of digits to shift native amounts, applying a 10^digit_shift factor.
     pub digit_shift: i8,
 
+    /// Set by retire_voting_mint. Retired mints take no new deposits and
+    /// have no vote weight.
+    pub retired: bool,
+
+    /// Set by configure_voting_mint_rate. The vote weight of priced mints
+    /// depends on the rate in their VotingMintRate account, see
+    /// RegistrarRef::vote_weight_config().
+    pub priced: bool,
+
     // Empty bytes for future upgrades.
-    pub reserved1: [u8; 7],
-    pub reserved2: [u64; 7], // split because `Default` does not support [u8; 63]
+    pub reserved1: [u8; 5],
+
+    /// Maximum share of the locked tokens that is forfeited on an early unlock.
+    ///
+    /// This is the penalty for tokens with lockup_saturation_secs or more
+    /// left on their lockup. Tokens that unlock sooner pay only a fraction
+    /// of it, based on lockup_time divided by lockup_saturation_secs.
+    ///
+    /// In 1/SCALED_FACTOR_BASE units. Zero disables early unlocks.
+    pub early_unlock_penalty_scaled_factor: u64,
+
+    /// Minimum number of native tokens a deposit, grant or internal transfer
+    /// must add to a deposit entry. Zero for no minimum.
+    ///
+    /// See configure_deposit_limits, like the other limits.
+    pub min_deposit_native: u64,
+
+    /// Maximum number of native tokens deposited by all voters of the
+    /// registrar together, as tracked by RegistrarStats. Zero for no limit.
+    /// Only registrars with a RegistrarStats can set it.
+    pub max_deposited_native: u64,
+
+    /// Maximum number of native tokens deposited by a single voter.
+    /// Zero for no limit.
+    pub max_voter_deposited_native: u64,
+
+    /// Minimum number of seconds left on new lockups, other than LockupKind::None.
+    ///
+    /// See configure_lockup_limits, like the other lockup limits.
+    pub min_lockup_secs: u64,
+
+    /// Maximum number of seconds left on new lockups. Zero for no limit.
+    pub max_lockup_secs: u64,
+
+    /// Bitmask of the lockup kinds new lockups may have, bit `1 << kind`
+    /// for each allowed LockupKind. Zero allows all kinds.
+    pub allowed_lockup_kinds: u32,
+
+    pub reserved2: [u8; 4],
 }
-const_assert!(std::mem::size_of::<VotingMintConfig>() == 2 * 32 + 3 * 8 + 1 + 63);
+const_assert!(std::mem::size_of::<VotingMintConfig>() == 2 * 32 + 9 * 8 + 4 + 1 + 1 + 1 + 9);
 const_assert!(std::mem::size_of::<VotingMintConfig>() % 8 == 0);
 
 impl VotingMintConfig {
@@ -94,6 +141,97 @@ impl VotingMintConfig {
         )
     }
 
+    /// This config with both vote weight factors scaled by `rate`, in
+    /// 1/SCALED_FACTOR_BASE units. Used for priced voting mints.
+    pub fn with_rate(&self, rate: u64) -> Result<VotingMintConfig> {
+        let mut config = *self;
+        config.baseline_vote_weight_scaled_factor =
+            Self::apply_factor(self.baseline_vote_weight_scaled_factor, rate)?;
+        config.max_extra_lockup_vote_weight_scaled_factor =
+            Self::apply_factor(self.max_extra_lockup_vote_weight_scaled_factor, rate)?;
+        Ok(config)
+    }
+
+    /// The penalty for unlocking a number of native tokens early, if all
+    /// of them had lockup_saturation_secs or more left on their lockup.
+    /// Will be multiplied with a factor between 0 and 1 for the lockup duration.
+    pub fn max_early_unlock_penalty(&self, amount_native: u64) -> Result<u64> {
+        Self::apply_factor(amount_native, self.early_unlock_penalty_scaled_factor)
+    }
+
+    /// Checks that adding `amount_native` tokens to a deposit entry is
+    /// allowed by min_deposit_native.
+    pub fn check_min_deposit(&self, amount_native: u64) -> Result<()> {
+        require_gte!(
+            amount_native,
+            self.min_deposit_native,
+            VsrError::DepositBelowMinimum
+        );
+        Ok(())
+    }
+
+    /// Checks the deposited native tokens of a voter and of the whole
+    /// registrar against the maximums, after tokens were added.
+    ///
+    /// The registrar's total is None for registrars without RegistrarStats.
+    pub fn check_max_deposited(
+        &self,
+        voter_deposited_native: u64,
+        registrar_deposited_native: Option<u64>,
+    ) -> Result<()> {
+        if self.max_voter_deposited_native > 0 {
+            require_gte!(
+                self.max_voter_deposited_native,
+                voter_deposited_native,
+                VsrError::VoterDepositLimitExceeded
+            );
+        }
+        if self.max_deposited_native > 0 {
+            let registrar_deposited_native = registrar_deposited_native
+                .ok_or_else(|| error!(VsrError::MissingRegistrarStats))?;
+            require_gte!(
+                self.max_deposited_native,
+                registrar_deposited_native,
+                VsrError::MintDepositLimitExceeded
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that tokens of this mint may be locked with `lockup`, by
+    /// allowed_lockup_kinds, min_lockup_secs and max_lockup_secs.
+    pub fn check_lockup(&self, lockup: &Lockup, curr_ts: i64) -> Result<()> {
+        if self.allowed_lockup_kinds != 0 {
+            require!(
+                self.allowed_lockup_kinds & (1 << lockup.kind as u32) != 0,
+                VsrError::LockupKindNotAllowed
+            );
+        }
+        if lockup.kind == LockupKind::None {
+            return Ok(());
+        }
+        let secs = lockup.seconds_left(curr_ts);
+        require_gte!(
+            secs,
+            self.min_lockup_secs,
+            VsrError::LockupDurationOutOfBounds
+        );
+        if self.max_lockup_secs > 0 {
+            require_gte!(
+                self.max_lockup_secs,
+                secs,
+                VsrError::LockupDurationOutOfBounds
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that new tokens may be deposited, see retired.
+    pub fn check_not_retired(&self) -> Result<()> {
+        require!(!self.retired, VsrError::VotingMintRetired);
+        Ok(())
+    }
+
     /// Whether this voting mint is configured.
     pub fn in_use(&self) -> bool {
         self.mint != Pubkey::default()
@@ -0,0 +1,90 @@
+use crate::state::reward_distribution::is_program_account;
+use anchor_lang::prelude::*;
+
+/// Exchange rate of a priced voting mint, for tokens like LP tokens or
+/// staked derivatives whose value changes over time.
+///
+/// Created by configure_voting_mint_rate, at the address derived from the
+/// registrar and the mint, and updated by its authority with
+/// set_voting_mint_rate. The vote weight factors of the voting mint are
+/// scaled by the current rate, see VotingMintConfig::with_rate().
+#[account(zero_copy)]
+#[derive(Default)]
+pub struct VotingMintRate {
+    pub registrar: Pubkey,
+    pub mint: Pubkey,
+
+    /// The key that can update the rate.
+    pub authority: Pubkey,
+
+    /// Vote weight per native token, before the voting mint's factors.
+    ///
+    /// In 1/SCALED_FACTOR_BASE units.
+    pub rate: u64,
+
+    /// Time of the last rate update.
+    pub updated_ts: i64,
+
+    /// Number of seconds after an update the rate stays valid. Until the
+    /// next update, the mint's deposits have no vote weight.
+    pub max_staleness_secs: u64,
+
+    /// Maximum rate the authority can set. Zero for no limit.
+    pub max_rate: u64,
+
+    pub bump: u8,
+    pub reserved: [u8; 31],
+}
+const_assert!(std::mem::size_of::<VotingMintRate>() == 3 * 32 + 4 * 8 + 1 + 31);
+const_assert!(std::mem::size_of::<VotingMintRate>() % 8 == 0);
+
+impl VotingMintRate {
+    /// The rate at `curr_ts`: zero if it wasn't updated in the last
+    /// max_staleness_secs.
+    pub fn current_rate(&self, curr_ts: i64) -> u64 {
+        let age = curr_ts.saturating_sub(self.updated_ts);
+        if age < 0 || age as u64 > self.max_staleness_secs {
+            return 0;
+        }
+        self.rate
+    }
+}
+
+/// The VotingMintRates of a registrar among `accounts`.
+pub fn load_voting_mint_rates(
+    registrar_address: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<Vec<VotingMintRate>> {
+    let mut rates = Vec::new();
+    for account in accounts {
+        if !is_program_account::<VotingMintRate>(account) {
+            continue;
+        }
+        let data = account.try_borrow_data()?;
+        let rate: &VotingMintRate = bytemuck::from_bytes(&data[8..]);
+        if rate.registrar == *registrar_address {
+            rates.push(*rate);
+        }
+    }
+    Ok(rates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn voting_mint_rate_staleness() {
+        let rate = VotingMintRate {
+            rate: 2_000_000_000,
+            updated_ts: 1000,
+            max_staleness_secs: 100,
+            ..VotingMintRate::default()
+        };
+        assert_eq!(rate.current_rate(1000), 2_000_000_000);
+        assert_eq!(rate.current_rate(1100), 2_000_000_000);
+        assert_eq!(rate.current_rate(1101), 0);
+        // updated after the registrar's clock
+        assert_eq!(rate.current_rate(999), 0);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::error::*;
+use crate::state::lockup::SECS_PER_DAY;
+use crate::state::registrar::RegistrarRef;
+use crate::state::reward_distribution::is_program_account;
+use crate::state::vesting_schedule::VestingSchedules;
+use crate::state::voter::VoterDeposits;
+use anchor_lang::prelude::*;
+use std::cmp::min;
+
+/// The maximum number of checkpoints kept, older ones are overwritten.
+pub const MAX_WEIGHT_CHECKPOINTS: usize = 64;
+
+/// The vote weight of a voter from the checkpoint's time on.
+///
+/// Lockups keep decaying the weight after unix_ts, see decayed_weight().
+#[zero_copy]
+#[derive(Default)]
+pub struct WeightCheckpoint {
+    pub slot: u64,
+    pub unix_ts: i64,
+    pub weight: u64,
+
+    /// How much the weight decreases in the day after unix_ts.
+    pub daily_decay: u64,
+
+    /// The weight once all lockups that can expire have expired.
+    pub final_weight: u64,
+}
+const_assert!(std::mem::size_of::<WeightCheckpoint>() == 5 * 8);
+
+impl WeightCheckpoint {
+    /// The weight at `unix_ts`, which must not be before the checkpoint.
+    ///
+    /// The weight decreases by daily_decay per day, but not below final_weight.
+    /// That's exact for lockups that already decay at the checkpoint, until the
+    /// first of them expires. Lockups whose extra weight is still saturated at
+    /// the checkpoint only start to decay later, which isn't accounted for.
+    pub fn decayed_weight(&self, unix_ts: i64) -> u64 {
+        let secs = unix_ts.saturating_sub(self.unix_ts).max(0) as u128;
+        let decay = self.daily_decay as u128 * secs / SECS_PER_DAY as u128;
+        let weight = (self.weight as u128).saturating_sub(decay) as u64;
+        weight.max(self.final_weight)
+    }
+}
+
+/// The history of a voter's vote weight.
+///
+/// Created by create_voter_weight_checkpoints, at the address derived from
+/// the voter. From then on, instructions that change the voter's weight must
+/// append a checkpoint, see record_weight_checkpoint(). It allows writing the
+/// voter weight record for a past time with
+/// update_historical_voter_weight_record.
+#[account(zero_copy)]
+pub struct VoterWeightCheckpoints {
+    pub voter: Pubkey,
+
+    /// The index in checkpoints the next checkpoint is written to.
+    pub head: u8,
+
+    /// The number of used checkpoints.
+    pub len: u8,
+
+    pub bump: u8,
+    pub reserved: [u8; 29],
+
+    /// Ring buffer of checkpoints. The last len ones before head are used.
+    pub checkpoints: [WeightCheckpoint; MAX_WEIGHT_CHECKPOINTS],
+}
+const_assert!(
+    std::mem::size_of::<VoterWeightCheckpoints>() == 32 + 3 + 29 + MAX_WEIGHT_CHECKPOINTS * 40
+);
+const_assert!(std::mem::size_of::<VoterWeightCheckpoints>() % 8 == 0);
+
+impl VoterWeightCheckpoints {
+    /// The used checkpoints, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &WeightCheckpoint> {
+        let start = (self.head as usize + MAX_WEIGHT_CHECKPOINTS - self.len as usize)
+            % MAX_WEIGHT_CHECKPOINTS;
+        (0..self.len as usize).map(move |i| &self.checkpoints[(start + i) % MAX_WEIGHT_CHECKPOINTS])
+    }
+
+    /// Appends a checkpoint, overwriting the oldest one if all are used.
+    ///
+    /// Weight changes within the same slot only keep the last weight.
+    pub fn push(&mut self, checkpoint: WeightCheckpoint) {
+        if self.len > 0 {
+            let last_index =
+                (self.head as usize + MAX_WEIGHT_CHECKPOINTS - 1) % MAX_WEIGHT_CHECKPOINTS;
+            let last = &mut self.checkpoints[last_index];
+            if last.slot == checkpoint.slot {
+                *last = checkpoint;
+                return;
+            }
+        }
+        self.checkpoints[self.head as usize] = checkpoint;
+        self.head = ((self.head as usize + 1) % MAX_WEIGHT_CHECKPOINTS) as u8;
+        self.len = min(self.len as usize + 1, MAX_WEIGHT_CHECKPOINTS) as u8;
+    }
+
+    /// The vote weight at `unix_ts`: the weight of the newest checkpoint
+    /// at or before it, decayed until `unix_ts`.
+    ///
+    /// Errors if `unix_ts` is before the oldest kept checkpoint.
+    pub fn weight_at(&self, unix_ts: i64) -> Result<u64> {
+        self.iter()
+            .filter(|c| c.unix_ts <= unix_ts)
+            .last()
+            .map(|c| c.decayed_weight(unix_ts))
+            .ok_or_else(|| error!(VsrError::WeightCheckpointNotFound))
+    }
+}
+
+#[macro_export]
+macro_rules! voter_weight_checkpoints_seeds {
+    ( $checkpoints:expr ) => {
+        &[
+            $checkpoints.voter.as_ref(),
+            b"voter-weight-checkpoints".as_ref(),
+            &[$checkpoints.bump],
+        ]
+    };
+}
+
+pub use voter_weight_checkpoints_seeds;
+
+/// The VoterWeightCheckpoints account of a voter among `accounts`, if any.
+pub fn find_weight_checkpoints<'a, 'info>(
+    voter_address: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> Option<&'a AccountInfo<'info>> {
+    accounts.iter().find(|account| {
+        if !is_program_account::<VoterWeightCheckpoints>(account) {
+            return false;
+        }
+        let data = account.data.borrow();
+        let checkpoints: &VoterWeightCheckpoints = bytemuck::from_bytes(&data[8..]);
+        checkpoints.voter == *voter_address
+    })
+}
+
+/// The checkpoint of the weight the voter can use itself now, see
+/// VoterDeposits::undelegated_weight().
+///
+/// The `schedules` must include the ones of all Tranches deposits.
+pub fn voter_weight_checkpoint(
+    voter: &impl VoterDeposits,
+    registrar: &RegistrarRef,
+    schedules: &VestingSchedules,
+) -> Result<WeightCheckpoint> {
+    let unix_ts = registrar.clock_unix_timestamp();
+    let weight_at = |ts: i64| -> Result<u64> {
+        if voter.has_delegate() {
+            return Ok(0);
+        }
+        voter.weight_at(registrar, ts, schedules)
+    };
+    let weight = weight_at(unix_ts)?;
+    Ok(WeightCheckpoint {
+        slot: Clock::get()?.slot,
+        unix_ts,
+        weight,
+        daily_decay: weight.saturating_sub(weight_at(unix_ts + SECS_PER_DAY as i64)?),
+        final_weight: weight_at(i64::MAX)?,
+    })
+}
+
+/// Appends the voter's current weight to its VoterWeightCheckpoints.
+///
+/// Instructions that change the weight of a voter call this afterwards.
+/// Nothing happens for voters without checkpoints, otherwise their
+/// VoterWeightCheckpoints account must be among `accounts`. So must the
+/// VestingSchedules of the voter's Tranches deposits.
+///
+/// The recorded weight is the one the voter can use itself, see
+/// voter_weight_checkpoint().
+pub fn record_weight_checkpoint(
+    voter_address: &Pubkey,
+    voter: &impl VoterDeposits,
+    registrar: &RegistrarRef,
+    accounts: &[AccountInfo],
+) -> Result<()> {
+    if !voter.has_weight_checkpoints {
+        return Ok(());
+    }
+    let account = find_weight_checkpoints(voter_address, accounts)
+        .ok_or_else(|| error!(VsrError::MissingWeightCheckpoints))?;
+
+    let schedules = VestingSchedules::load(voter_address, accounts)?;
+    let checkpoint = voter_weight_checkpoint(voter, registrar, &schedules)?;
+    let mut data = account.try_borrow_mut_data()?;
+    let checkpoints: &mut VoterWeightCheckpoints = bytemuck::from_bytes_mut(&mut data[8..]);
+    checkpoints.push(checkpoint);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(slot: u64, unix_ts: i64, weight: u64) -> WeightCheckpoint {
+        WeightCheckpoint {
+            slot,
+            unix_ts,
+            weight,
+            ..WeightCheckpoint::default()
+        }
+    }
+
+    #[test]
+    pub fn weight_checkpoints() {
+        let mut checkpoints: VoterWeightCheckpoints = bytemuck::Zeroable::zeroed();
+        assert!(checkpoints.weight_at(0).is_err());
+
+        checkpoints.push(checkpoint(10, 100, 1));
+        checkpoints.push(checkpoint(11, 110, 2));
+        // only the last weight in a slot is kept
+        checkpoints.push(checkpoint(11, 110, 3));
+        assert_eq!(checkpoints.len, 2);
+        assert!(checkpoints.weight_at(99).is_err());
+        assert_eq!(checkpoints.weight_at(100).unwrap(), 1);
+        assert_eq!(checkpoints.weight_at(109).unwrap(), 1);
+        assert_eq!(checkpoints.weight_at(110).unwrap(), 3);
+        assert_eq!(checkpoints.weight_at(1000).unwrap(), 3);
+
+        // the oldest checkpoints are overwritten
+        for i in 0..MAX_WEIGHT_CHECKPOINTS as u64 {
+            checkpoints.push(checkpoint(20 + i, 200 + i as i64, 10 + i));
+        }
+        assert_eq!(checkpoints.len as usize, MAX_WEIGHT_CHECKPOINTS);
+        assert_eq!(checkpoints.iter().count(), MAX_WEIGHT_CHECKPOINTS);
+        assert!(checkpoints.weight_at(199).is_err());
+        assert_eq!(checkpoints.weight_at(200).unwrap(), 10);
+        assert_eq!(
+            checkpoints.weight_at(1000).unwrap(),
+            10 + MAX_WEIGHT_CHECKPOINTS as u64 - 1
+        );
+    }
+
+    #[test]
+    pub fn weight_checkpoint_decay() {
+        let day = SECS_PER_DAY as i64;
+        let mut checkpoints: VoterWeightCheckpoints = bytemuck::Zeroable::zeroed();
+        checkpoints.push(WeightCheckpoint {
+            slot: 10,
+            unix_ts: 0,
+            weight: 2000,
+            daily_decay: 100,
+            final_weight: 1000,
+        });
+        assert_eq!(checkpoints.weight_at(0).unwrap(), 2000);
+        assert_eq!(checkpoints.weight_at(day / 2).unwrap(), 1950);
+        assert_eq!(checkpoints.weight_at(4 * day).unwrap(), 1600);
+        // no decay below the final weight
+        assert_eq!(checkpoints.weight_at(10 * day).unwrap(), 1000);
+        assert_eq!(checkpoints.weight_at(100 * day).unwrap(), 1000);
+
+        // a newer checkpoint starts decaying from its own weight
+        checkpoints.push(WeightCheckpoint {
+            slot: 11,
+            unix_ts: 4 * day,
+            weight: 2100,
+            daily_decay: 100,
+            final_weight: 1500,
+        });
+        assert_eq!(checkpoints.weight_at(2 * day).unwrap(), 1800);
+        assert_eq!(checkpoints.weight_at(5 * day).unwrap(), 2000);
+        assert_eq!(checkpoints.weight_at(20 * day).unwrap(), 1500);
+    }
+}
@@ -0,0 +1,192 @@
+//! Helpers for working with both the spl-token and spl-token-2022 programs.
+//!
+//! anchor-spl only supports the original spl-token program: its account types
+//! check the account owner and its CPI helpers hardcode the program id. Voting
+//! mints may belong to either program, so token accounts, mints and transfers
+//! go through the functions here instead.
+
+use crate::error::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use spl_token_2022::extension::transfer_fee::{self, TransferFeeAmount};
+use spl_token_2022::extension::StateWithExtensions;
+
+pub use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+/// Whether `program_id` is one of the supported token programs.
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == anchor_spl::token::ID || *program_id == spl_token_2022::ID
+}
+
+/// The address of the vault holding `mint` tokens for `voter`.
+///
+/// Vaults are associated token accounts, which depend on the token program
+/// that owns the mint.
+pub fn vault_address(voter: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(voter, mint, token_program)
+}
+
+/// Reads a token account owned by either token program.
+pub fn unpack_token_account(account_info: &AccountInfo) -> Result<spl_token_2022::state::Account> {
+    require!(
+        is_token_program(account_info.owner),
+        VsrError::InvalidTokenProgram
+    );
+    let data = account_info.try_borrow_data()?;
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(account.base)
+}
+
+/// Reads a mint owned by either token program.
+pub fn unpack_mint(account_info: &AccountInfo) -> Result<spl_token_2022::state::Mint> {
+    require!(
+        is_token_program(account_info.owner),
+        VsrError::InvalidTokenProgram
+    );
+    let data = account_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint.base)
+}
+
+/// Creates the vault for `voter` and `mint` if it doesn't exist yet.
+pub fn create_vault_if_needed<'info>(
+    payer: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    voter: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if !vault.data_is_empty() {
+        return Ok(());
+    }
+    let ix = spl_associated_token_account::instruction::create_associated_token_account(
+        payer.key,
+        voter.key,
+        mint.key,
+        token_program.key,
+    );
+    invoke(
+        &ix,
+        &[
+            payer.clone(),
+            vault.clone(),
+            voter.clone(),
+            mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TransferChecked<'info> {
+    /// CHECK: Validated by the token program
+    pub from: AccountInfo<'info>,
+    /// CHECK: Validated by the token program
+    pub mint: AccountInfo<'info>,
+    /// CHECK: Validated by the token program
+    pub to: AccountInfo<'info>,
+    /// CHECK: Validated by the token program
+    pub authority: AccountInfo<'info>,
+}
+
+/// Like anchor_spl::token::transfer_checked, but for either token program.
+///
+/// Note that with spl-token-2022 transfer fees, the receiver may get less
+/// than `amount`.
+pub fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let decimals = unpack_mint(&ctx.accounts.mint)?.decimals;
+    let ix = spl_token_2022::instruction::transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.from,
+            ctx.accounts.mint,
+            ctx.accounts.to,
+            ctx.accounts.authority,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseAccount<'info> {
+    /// CHECK: Validated by the token program
+    pub account: AccountInfo<'info>,
+    /// CHECK: Validated by the token program
+    pub destination: AccountInfo<'info>,
+    /// CHECK: Validated by the token program
+    pub authority: AccountInfo<'info>,
+}
+
+/// Like anchor_spl::token::close_account, but for either token program.
+pub fn close_account<'info>(ctx: CpiContext<'_, '_, '_, 'info, CloseAccount<'info>>) -> Result<()> {
+    let ix = spl_token_2022::instruction::close_account(
+        ctx.program.key,
+        ctx.accounts.account.key,
+        ctx.accounts.destination.key,
+        ctx.accounts.authority.key,
+        &[],
+    )?;
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.account,
+            ctx.accounts.destination,
+            ctx.accounts.authority,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// Moves the transfer fees withheld in an spl-token-2022 `account` to its
+/// mint, if there are any. Token accounts with withheld fees can't be closed.
+///
+/// The mint must be writable when there are withheld fees.
+pub fn harvest_withheld_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+) -> Result<()> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let withheld = {
+        let data = account.try_borrow_data()?;
+        let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+        account
+            .get_extension::<TransferFeeAmount>()
+            .map(|fees| u64::from(fees.withheld_amount))
+            .unwrap_or(0)
+    };
+    if withheld == 0 {
+        return Ok(());
+    }
+    let ix = transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+        token_program.key,
+        mint.key,
+        &[account.key],
+    )?;
+    invoke(&ix, &[mint.clone(), account.clone(), token_program.clone()])?;
+    Ok(())
+}
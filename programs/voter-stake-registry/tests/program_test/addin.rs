@@ -18,7 +18,9 @@ pub struct AddinCookie {
 pub struct RegistrarCookie {
     pub address: Pubkey,
     pub authority: Pubkey,
+    pub realm: Pubkey,
     pub mint: MintCookie,
+    pub has_stats: bool,
 }
 
 #[derive(Clone)]
@@ -26,6 +28,11 @@ pub struct VotingMintConfigCookie {
     pub mint: MintCookie,
 }
 
+pub struct RewardDistributionCookie {
+    pub address: Pubkey,
+    pub mint: MintCookie,
+}
+
 pub struct VoterCookie {
     pub address: Pubkey,
     pub authority: Pubkey,
@@ -39,6 +46,21 @@ impl AddinCookie {
         realm: &GovernanceRealmCookie,
         authority: &Keypair,
         payer: &Keypair,
+    ) -> RegistrarCookie {
+        let mut registrar = self
+            .create_registrar_without_stats(realm, authority, payer)
+            .await;
+        self.create_registrar_stats(&mut registrar, authority, payer)
+            .await
+            .unwrap();
+        registrar
+    }
+
+    pub async fn create_registrar_without_stats(
+        &self,
+        realm: &GovernanceRealmCookie,
+        authority: &Keypair,
+        payer: &Keypair,
     ) -> RegistrarCookie {
         let community_token_mint = realm.community_token_mint.pubkey.unwrap();
 
@@ -87,10 +109,50 @@ impl AddinCookie {
         RegistrarCookie {
             address: registrar,
             authority: realm.authority,
+            realm: realm.realm,
             mint: realm.community_token_mint.clone(),
+            has_stats: false,
         }
     }
 
+    pub async fn create_registrar_stats(
+        &self,
+        registrar: &mut RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::CreateRegistrarStats {},
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CreateRegistrarStats {
+                registrar: registrar.address,
+                realm_authority: authority.pubkey(),
+                registrar_stats: RegistrarCookie::stats_address_for(&registrar.address),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the user secret
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await?;
+        registrar.has_stats = true;
+        Ok(())
+    }
+
     pub async fn configure_voting_mint(
         &self,
         registrar: &RegistrarCookie,
@@ -104,6 +166,40 @@ impl AddinCookie {
         lockup_saturation_secs: u64,
         grant_authority: Option<Pubkey>,
         other_mints: Option<&[Pubkey]>,
+    ) -> VotingMintConfigCookie {
+        self.configure_voting_mint_with_penalty(
+            registrar,
+            authority,
+            _payer,
+            index,
+            mint,
+            digit_shift,
+            baseline_vote_weight_scaled_factor,
+            max_extra_lockup_vote_weight_scaled_factor,
+            lockup_saturation_secs,
+            grant_authority,
+            other_mints,
+            0.0,
+        )
+        .await
+    }
+
+    /// Like configure_voting_mint, but also sets the early unlock penalty
+    #[allow(dead_code)]
+    pub async fn configure_voting_mint_with_penalty(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        _payer: &Keypair,
+        index: u16,
+        mint: &MintCookie,
+        digit_shift: i8,
+        baseline_vote_weight_scaled_factor: f64,
+        max_extra_lockup_vote_weight_scaled_factor: f64,
+        lockup_saturation_secs: u64,
+        grant_authority: Option<Pubkey>,
+        other_mints: Option<&[Pubkey]>,
+        early_unlock_penalty_scaled_factor: f64,
     ) -> VotingMintConfigCookie {
         let deposit_mint = mint.pubkey.unwrap();
 
@@ -117,6 +213,8 @@ impl AddinCookie {
                     (max_extra_lockup_vote_weight_scaled_factor * 1e9) as u64,
                 lockup_saturation_secs,
                 grant_authority,
+                early_unlock_penalty_scaled_factor: (early_unlock_penalty_scaled_factor * 1e9)
+                    as u64,
             },
         );
 
@@ -156,6 +254,44 @@ impl AddinCookie {
         VotingMintConfigCookie { mint: mint.clone() }
     }
 
+    #[allow(dead_code)]
+    pub async fn grow_registrar(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        voting_mint_slots: u8,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::GrowRegistrar {
+                voting_mint_slots,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::GrowRegistrar {
+                registrar: registrar.address,
+                realm_authority: authority.pubkey(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&authority.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&payer.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
     pub async fn create_voter(
         &self,
         registrar: &RegistrarCookie,
@@ -223,6 +359,41 @@ impl AddinCookie {
         }
     }
 
+    #[allow(dead_code)]
+    pub async fn grow_voter(
+        &self,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+        deposit_entry_slots: u8,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::GrowVoter {
+                deposit_entry_slots,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::GrowVoter {
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
     pub async fn create_deposit_entry(
         &self,
         registrar: &RegistrarCookie,
@@ -234,6 +405,35 @@ impl AddinCookie {
         start_ts: Option<u64>,
         periods: u32,
         allow_clawback: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        self.create_deposit_entry_with_cliff(
+            registrar,
+            voter,
+            voter_authority,
+            voting_mint,
+            deposit_entry_index,
+            lockup_kind,
+            start_ts,
+            periods,
+            0,
+            allow_clawback,
+        )
+        .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_deposit_entry_with_cliff(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+        voting_mint: &VotingMintConfigCookie,
+        deposit_entry_index: u8,
+        lockup_kind: voter_stake_registry::state::LockupKind,
+        start_ts: Option<u64>,
+        periods: u32,
+        cliff_periods: u32,
+        allow_clawback: bool,
     ) -> std::result::Result<(), BanksClientError> {
         let vault = voter.vault_address(&voting_mint);
 
@@ -243,6 +443,7 @@ impl AddinCookie {
                 kind: lockup_kind,
                 start_ts,
                 periods,
+                cliff_periods,
                 allow_clawback,
             },
         );
@@ -251,12 +452,13 @@ impl AddinCookie {
             &voter_stake_registry::accounts::CreateDepositEntry {
                 vault,
                 registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
                 voter: voter.address,
                 voter_authority: voter_authority.pubkey(),
                 payer: voter_authority.pubkey(),
                 deposit_mint: voting_mint.mint.pubkey.unwrap(),
                 system_program: solana_sdk::system_program::id(),
-                token_program: spl_token::id(),
+                token_program: voting_mint.mint.token_program,
                 associated_token_program: spl_associated_token_account::id(),
                 rent: solana_program::sysvar::rent::id(),
             },
@@ -287,6 +489,85 @@ impl AddinCookie {
         token_address: Pubkey,
         deposit_entry_index: u8,
         amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        self.deposit_impl(
+            registrar,
+            voter,
+            voting_mint,
+            authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            vec![],
+        )
+        .await
+    }
+
+    /// Deposit, passing the voter's reward positions in the given distributions
+    #[allow(dead_code)]
+    pub async fn deposit_with_rewards(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+        distributions: &[&RewardDistributionCookie],
+    ) -> std::result::Result<(), BanksClientError> {
+        self.deposit_impl(
+            registrar,
+            voter,
+            voting_mint,
+            authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            distributions
+                .iter()
+                .flat_map(|d| d.reward_account_metas(voter))
+                .collect(),
+        )
+        .await
+    }
+
+    /// Deposit, passing the voter's weight checkpoints
+    #[allow(dead_code)]
+    pub async fn deposit_with_checkpoints(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        self.deposit_impl(
+            registrar,
+            voter,
+            voting_mint,
+            authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            vec![voter.weight_checkpoints_account_meta()],
+        )
+        .await
+    }
+
+    #[allow(dead_code)]
+    async fn deposit_impl(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+        remaining_accounts: Vec<anchor_lang::prelude::AccountMeta>,
     ) -> std::result::Result<(), BanksClientError> {
         let vault = voter.vault_address(&voting_mint);
 
@@ -296,17 +577,20 @@ impl AddinCookie {
                 amount,
             });
 
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &voter_stake_registry::accounts::Deposit {
                 registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
                 voter: voter.address,
                 vault: vault,
                 deposit_token: token_address,
                 deposit_authority: authority.pubkey(),
-                token_program: spl_token::id(),
+                deposit_mint: voting_mint.mint.pubkey.unwrap(),
+                token_program: voting_mint.mint.token_program,
             },
             None,
         );
+        accounts.extend(remaining_accounts);
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -336,6 +620,114 @@ impl AddinCookie {
         deposit_token: Pubkey,
         token_authority: &Keypair,
         grant_authority: &Keypair,
+    ) -> std::result::Result<VoterCookie, BanksClientError> {
+        self.grant_impl(
+            registrar,
+            voter_authority,
+            voting_mint,
+            lockup_kind,
+            start_ts,
+            periods,
+            allow_clawback,
+            amount,
+            vec![],
+            None,
+            deposit_token,
+            token_authority,
+            grant_authority,
+            vec![],
+        )
+        .await
+    }
+
+    /// Grant, passing the voter's reward positions in `distributions`
+    #[allow(dead_code)]
+    pub async fn grant_with_rewards(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        lockup_kind: voter_stake_registry::state::LockupKind,
+        periods: u32,
+        amount: u64,
+        deposit_token: Pubkey,
+        token_authority: &Keypair,
+        grant_authority: &Keypair,
+        distributions: &[&RewardDistributionCookie],
+    ) -> std::result::Result<VoterCookie, BanksClientError> {
+        self.grant_impl(
+            registrar,
+            voter.authority,
+            voting_mint,
+            lockup_kind,
+            None,
+            periods,
+            false,
+            amount,
+            vec![],
+            None,
+            deposit_token,
+            token_authority,
+            grant_authority,
+            distributions
+                .iter()
+                .flat_map(|d| d.reward_account_metas(voter))
+                .collect(),
+        )
+        .await
+    }
+
+    /// Grant with LockupKind::Tranches, the new deposit entry must be at `deposit_entry_index`
+    #[allow(dead_code)]
+    pub async fn grant_tranches(
+        &self,
+        registrar: &RegistrarCookie,
+        voter_authority: Pubkey,
+        voting_mint: &VotingMintConfigCookie,
+        tranches: Vec<voter_stake_registry::state::Tranche>,
+        allow_clawback: bool,
+        deposit_entry_index: u8,
+        deposit_token: Pubkey,
+        token_authority: &Keypair,
+        grant_authority: &Keypair,
+    ) -> std::result::Result<VoterCookie, BanksClientError> {
+        let amount = tranches.iter().map(|t| t.amount).sum();
+        self.grant_impl(
+            registrar,
+            voter_authority,
+            voting_mint,
+            voter_stake_registry::state::LockupKind::Tranches,
+            None,
+            0,
+            allow_clawback,
+            amount,
+            tranches,
+            Some(deposit_entry_index),
+            deposit_token,
+            token_authority,
+            grant_authority,
+            vec![],
+        )
+        .await
+    }
+
+    #[allow(dead_code)]
+    async fn grant_impl(
+        &self,
+        registrar: &RegistrarCookie,
+        voter_authority: Pubkey,
+        voting_mint: &VotingMintConfigCookie,
+        lockup_kind: voter_stake_registry::state::LockupKind,
+        start_ts: Option<u64>,
+        periods: u32,
+        allow_clawback: bool,
+        amount: u64,
+        tranches: Vec<voter_stake_registry::state::Tranche>,
+        vesting_schedule_index: Option<u8>,
+        deposit_token: Pubkey,
+        token_authority: &Keypair,
+        grant_authority: &Keypair,
+        remaining_accounts: Vec<anchor_lang::prelude::AccountMeta>,
     ) -> std::result::Result<VoterCookie, BanksClientError> {
         let (voter, voter_bump) = Pubkey::find_program_address(
             &[
@@ -367,13 +759,16 @@ impl AddinCookie {
             kind: lockup_kind,
             start_ts,
             periods,
+            cliff_periods: 0,
             allow_clawback,
             amount,
+            tranches,
         });
 
-        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
             &voter_stake_registry::accounts::Grant {
                 registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
                 voter,
                 voter_authority,
                 voter_weight_record,
@@ -384,12 +779,15 @@ impl AddinCookie {
                 payer: token_authority.pubkey(),
                 deposit_mint: voting_mint.mint.pubkey.unwrap(),
                 system_program: solana_sdk::system_program::id(),
-                token_program: spl_token::id(),
+                token_program: voting_mint.mint.token_program,
                 associated_token_program: spl_associated_token_account::id(),
                 rent: solana_program::sysvar::rent::id(),
+                vesting_schedule: vesting_schedule_index
+                    .map(|index| voter_cookie.vesting_schedule_address(index)),
             },
             None,
         );
+        accounts.extend(remaining_accounts);
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -417,22 +815,1821 @@ impl AddinCookie {
         realm_authority: &Keypair,
         token_address: Pubkey,
         deposit_entry_index: u8,
+        amount: Option<u64>,
     ) -> std::result::Result<(), BanksClientError> {
-        let vault = voter.vault_address(&voting_mint);
+        self.clawback_impl(
+            registrar,
+            voter,
+            voting_mint,
+            realm_authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            false,
+        )
+        .await
+    }
 
-        let data =
-            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::Clawback {
-                deposit_entry_index,
-            });
+    #[allow(dead_code)]
+    pub async fn clawback_tranches(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        realm_authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: Option<u64>,
+    ) -> std::result::Result<(), BanksClientError> {
+        self.clawback_impl(
+            registrar,
+            voter,
+            voting_mint,
+            realm_authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            true,
+        )
+        .await
+    }
+
+    #[allow(dead_code)]
+    async fn clawback_impl(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        realm_authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: Option<u64>,
+        with_vesting_schedule: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(&voting_mint);
+
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::Clawback {
+                deposit_entry_index,
+                amount,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::Clawback {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                vault,
+                destination: token_address,
+                realm_authority: realm_authority.pubkey(),
+                deposit_mint: voting_mint.mint.pubkey.unwrap(),
+                token_program: voting_mint.mint.token_program,
+                vesting_schedule: with_vesting_schedule
+                    .then(|| voter.vesting_schedule_address(deposit_entry_index)),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&realm_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn withdraw(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        self.withdraw_impl(
+            registrar,
+            voter,
+            voting_mint,
+            authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            None,
+            &[],
+            vec![],
+        )
+        .await
+    }
+
+    /// Withdraw, passing the vesting schedules of the given deposit entries
+    #[allow(dead_code)]
+    pub async fn withdraw_with_schedules(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+        schedule_indexes: &[u8],
+    ) -> std::result::Result<(), BanksClientError> {
+        self.withdraw_impl(
+            registrar,
+            voter,
+            voting_mint,
+            authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            None,
+            schedule_indexes,
+            vec![],
+        )
+        .await
+    }
+
+    /// Withdraw, passing the voter's weight checkpoints
+    #[allow(dead_code)]
+    pub async fn withdraw_with_checkpoints(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        self.withdraw_impl(
+            registrar,
+            voter,
+            voting_mint,
+            authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            None,
+            &[],
+            vec![voter.weight_checkpoints_account_meta()],
+        )
+        .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn withdraw_delegated(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+        delegate: &VoterCookie,
+    ) -> std::result::Result<(), BanksClientError> {
+        self.withdraw_impl(
+            registrar,
+            voter,
+            voting_mint,
+            authority,
+            token_address,
+            deposit_entry_index,
+            amount,
+            Some(delegate),
+            &[],
+            vec![],
+        )
+        .await
+    }
+
+    #[allow(dead_code)]
+    async fn withdraw_impl(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+        delegate: Option<&VoterCookie>,
+        schedule_indexes: &[u8],
+        remaining_accounts: Vec<anchor_lang::prelude::AccountMeta>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(&voting_mint);
+
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::Withdraw {
+                deposit_entry_index,
+                amount,
+            });
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::Withdraw {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                token_owner_record: voter.token_owner_record,
+                voter_weight_record: voter.voter_weight_record,
+                vault,
+                destination: token_address,
+                voter_authority: authority.pubkey(),
+                deposit_mint: voting_mint.mint.pubkey.unwrap(),
+                token_program: voting_mint.mint.token_program,
+                delegate_token_owner_record: delegate.map(|d| d.token_owner_record),
+                delegate_voter_weight_record: delegate.map(|d| d.voter_weight_record),
+            },
+            None,
+        );
+        for index in schedule_indexes {
+            accounts.push(anchor_lang::prelude::AccountMeta::new_readonly(
+                voter.vesting_schedule_address(*index),
+                false,
+            ));
+        }
+        accounts.extend(remaining_accounts);
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn early_unlock(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        treasury: Pubkey,
+        deposit_entry_index: u8,
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(&voting_mint);
+
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::EarlyUnlock {
+                deposit_entry_index,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::EarlyUnlock {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                token_owner_record: voter.token_owner_record,
+                voter_weight_record: voter.voter_weight_record,
+                vault,
+                treasury,
+                voter_authority: authority.pubkey(),
+                deposit_mint: voting_mint.mint.pubkey.unwrap(),
+                token_program: voting_mint.mint.token_program,
+                delegate_token_owner_record: None,
+                delegate_voter_weight_record: None,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn close_voter(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        voter_authority: &Keypair,
+        schedule_indexes: &[u8],
+    ) -> std::result::Result<(), BanksClientError> {
+        let vault = voter.vault_address(&voting_mint);
+
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::CloseVoter {});
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CloseVoter {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                sol_destination: voter_authority.pubkey(),
+                token_program: voting_mint.mint.token_program,
+            },
+            None,
+        );
+        accounts.push(anchor_lang::prelude::AccountMeta::new(vault, false));
+        for index in schedule_indexes {
+            accounts.push(anchor_lang::prelude::AccountMeta::new(
+                voter.vesting_schedule_address(*index),
+                false,
+            ));
+        }
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn propose_voter_authority(
+        &self,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+        new_authority: Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::ProposeVoterAuthority { new_authority },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::ProposeVoterAuthority {
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn accept_voter_authority(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mints: &[&VotingMintConfigCookie],
+        schedule_indexes: &[u8],
+        new_authority: &Keypair,
+        new_token_owner_record: &TokenOwnerRecordCookie,
+    ) -> std::result::Result<VoterCookie, BanksClientError> {
+        let new_voter = Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"voter".as_ref(),
+                &new_authority.pubkey().to_bytes(),
+            ],
+            &self.program_id,
+        )
+        .0;
+        let new_voter_weight_record = Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"voter-weight-record".as_ref(),
+                &new_authority.pubkey().to_bytes(),
+            ],
+            &self.program_id,
+        )
+        .0;
+        let new_voter_cookie = VoterCookie {
+            address: new_voter,
+            authority: new_authority.pubkey(),
+            voter_weight_record: new_voter_weight_record,
+            token_owner_record: new_token_owner_record.address,
+        };
+
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::AcceptVoterAuthority {},
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::AcceptVoterAuthority {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: voter.authority,
+                token_owner_record: voter.token_owner_record,
+                voter_weight_record: voter.voter_weight_record,
+                new_voter,
+                new_voter_authority: new_authority.pubkey(),
+                new_voter_weight_record,
+                payer: new_authority.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                associated_token_program: spl_associated_token_account::id(),
+                instructions: solana_program::sysvar::instructions::id(),
+            },
+            None,
+        );
+        for voting_mint in voting_mints {
+            // writable to receive withheld transfer fees
+            accounts.push(anchor_lang::prelude::AccountMeta::new(
+                voting_mint.mint.pubkey.unwrap(),
+                false,
+            ));
+            accounts.push(anchor_lang::prelude::AccountMeta::new(
+                voter.vault_address(voting_mint),
+                false,
+            ));
+            accounts.push(anchor_lang::prelude::AccountMeta::new(
+                new_voter_cookie.vault_address(voting_mint),
+                false,
+            ));
+            accounts.push(anchor_lang::prelude::AccountMeta::new_readonly(
+                voting_mint.mint.token_program,
+                false,
+            ));
+        }
+
+        for &index in schedule_indexes {
+            accounts.push(anchor_lang::prelude::AccountMeta::new(
+                voter.vesting_schedule_address(index),
+                false,
+            ));
+            accounts.push(anchor_lang::prelude::AccountMeta::new(
+                new_voter_cookie.vesting_schedule_address(index),
+                false,
+            ));
+        }
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&new_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await?;
+
+        Ok(new_voter_cookie)
+    }
+
+    pub fn update_voter_weight_record_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> Instruction {
+        self.update_voter_weight_record_for_action_instruction(registrar, voter, None, None)
+    }
+
+    pub fn update_voter_weight_record_for_action_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        weight_action: Option<voter_stake_registry::state::VoterWeightAction>,
+        weight_action_target: Option<Pubkey>,
+    ) -> Instruction {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::UpdateVoterWeightRecord {
+                weight_action,
+                weight_action_target,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::UpdateVoterWeightRecord {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_weight_record: voter.voter_weight_record,
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let instructions = vec![self.update_voter_weight_record_instruction(registrar, voter)];
+
+        self.solana.process_transaction(&instructions, None).await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    /// Update the voter weight record, scoping it to an action and target
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_for_action(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        weight_action: Option<voter_stake_registry::state::VoterWeightAction>,
+        weight_action_target: Option<Pubkey>,
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let instructions = vec![self.update_voter_weight_record_for_action_instruction(
+            registrar,
+            voter,
+            weight_action,
+            weight_action_target,
+        )];
+
+        self.solana.process_transaction(&instructions, None).await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    #[allow(dead_code)]
+    pub async fn update_max_vote_weight(
+        &self,
+        registrar: &RegistrarCookie,
+        payer: &Keypair,
+        mints: &[Pubkey],
+    ) -> std::result::Result<voter_stake_registry::state::MaxVoterWeightRecord, BanksClientError>
+    {
+        let (max_voter_weight_record, _) = Pubkey::find_program_address(
+            &[
+                b"max-voter-weight-record".as_ref(),
+                &registrar.realm.to_bytes(),
+                &registrar.mint.pubkey.unwrap().to_bytes(),
+            ],
+            &self.program_id,
+        );
+
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::UpdateMaxVoteWeight {},
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::UpdateMaxVoteWeight {
+                registrar: registrar.address,
+                max_voter_weight_record,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+        for mint in mints {
+            accounts.push(anchor_lang::prelude::AccountMeta::new_readonly(
+                *mint, false,
+            ));
+        }
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&payer.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::MaxVoterWeightRecord>(
+                max_voter_weight_record,
+            )
+            .await)
+    }
+
+    /// Update the voter weight record, passing the vesting schedules of the given deposit entries
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_with_schedules(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        schedule_indexes: &[u8],
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let mut instruction = self.update_voter_weight_record_instruction(registrar, voter);
+        for index in schedule_indexes {
+            instruction
+                .accounts
+                .push(anchor_lang::prelude::AccountMeta::new_readonly(
+                    voter.vesting_schedule_address(*index),
+                    false,
+                ));
+        }
+
+        self.solana
+            .process_transaction(&[instruction], None)
+            .await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    /// Update the voter weight record, passing the rates of the given priced voting mints
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_with_rates(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mints: &[&VotingMintConfigCookie],
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let mut instruction = self.update_voter_weight_record_instruction(registrar, voter);
+        for voting_mint in voting_mints {
+            instruction
+                .accounts
+                .push(anchor_lang::prelude::AccountMeta::new_readonly(
+                    voting_mint.rate_address(registrar),
+                    false,
+                ));
+        }
+
+        self.solana
+            .process_transaction(&[instruction], None)
+            .await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    /// Update the voter weight record, passing the predecessor plugin's record
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_with_predecessor(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        predecessor_record: Pubkey,
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let mut instruction = self.update_voter_weight_record_instruction(registrar, voter);
+        instruction
+            .accounts
+            .push(anchor_lang::prelude::AccountMeta::new_readonly(
+                predecessor_record,
+                false,
+            ));
+
+        self.solana
+            .process_transaction(&[instruction], None)
+            .await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_with_delegators(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegators: &[&VoterCookie],
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let mut instruction = self.update_voter_weight_record_instruction(registrar, voter);
+        for delegator in delegators {
+            instruction
+                .accounts
+                .push(anchor_lang::prelude::AccountMeta::new_readonly(
+                    delegator.address,
+                    false,
+                ));
+        }
+
+        self.solana
+            .process_transaction(&[instruction], None)
+            .await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    #[allow(dead_code)]
+    pub async fn set_delegate(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        new_delegate: Option<&VoterCookie>,
+        previous_delegate: Option<&VoterCookie>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::SetDelegate {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::SetDelegate {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+                token_owner_record: voter.token_owner_record,
+                voter_weight_record: voter.voter_weight_record,
+                new_delegate_voter: new_delegate.map(|d| d.address),
+                previous_delegate_voter: previous_delegate.map(|d| d.address),
+                previous_delegate_token_owner_record: previous_delegate
+                    .map(|d| d.token_owner_record),
+                previous_delegate_voter_weight_record: previous_delegate
+                    .map(|d| d.voter_weight_record),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn close_deposit_entry(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+        with_vesting_schedule: bool,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::CloseDepositEntry {
+                deposit_entry_index,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CloseDepositEntry {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+                vesting_schedule: with_vesting_schedule
+                    .then(|| voter.vesting_schedule_address(deposit_entry_index)),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn reset_lockup(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+        kind: voter_stake_registry::state::LockupKind,
+        periods: u32,
+    ) -> Result<(), BanksClientError> {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::ResetLockup {
+                deposit_entry_index,
+                kind,
+                periods,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::ResetLockup {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn relock_deposit_entry(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+        kind: voter_stake_registry::state::LockupKind,
+        periods: u32,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::RelockDepositEntry {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+                amount,
+                kind,
+                periods,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::RelockDepositEntry {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn internal_transfer_locked(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::InternalTransferLocked {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+                amount,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::InternalTransferLocked {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn external_transfer_locked(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voting_mint: &VotingMintConfigCookie,
+        authority: &Keypair,
+        target_voter: &VoterCookie,
+        target_authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::ExternalTransferLocked {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+                amount,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::ExternalTransferLocked {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+                token_owner_record: voter.token_owner_record,
+                voter_weight_record: voter.voter_weight_record,
+                delegate_token_owner_record: None,
+                delegate_voter_weight_record: None,
+                vault: voter.vault_address(&voting_mint),
+                target_voter: target_voter.address,
+                target_voter_authority: target_authority.pubkey(),
+                target_vault: target_voter.vault_address(&voting_mint),
+                deposit_mint: voting_mint.mint.pubkey.unwrap(),
+                token_program: voting_mint.mint.token_program,
+                associated_token_program: spl_associated_token_account::id(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&authority.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&target_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn internal_transfer_unlocked(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::InternalTransferUnlocked {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+                amount,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::InternalTransferUnlocked {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn split_deposit_entry(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::SplitDepositEntry {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+                amount,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::SplitDepositEntry {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn merge_deposit_entries(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::MergeDepositEntries {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::MergeDepositEntries {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn start_unlocking(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::StartUnlocking {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+                amount,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::StartUnlocking {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn stop_unlocking(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::StopUnlocking {
+                source_deposit_entry_index,
+                target_deposit_entry_index,
+                amount,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::StopUnlocking {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address(),
+                voter: voter.address,
+                voter_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn log_voter_info(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        deposit_entry_begin: u8,
+    ) {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::LogVoterInfo {
+                deposit_entry_begin,
+                deposit_entry_count: 8,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::LogVoterInfo {
+                registrar: registrar.address,
+                voter: voter.address,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, None)
+            .await
+            .unwrap();
+    }
+
+    #[allow(dead_code)]
+    pub async fn log_registrar_stats(&self, registrar: &RegistrarCookie) {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::LogRegistrarStats {},
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::LogRegistrarStats {
+                registrar: registrar.address,
+                registrar_stats: registrar.stats_address().unwrap(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, None)
+            .await
+            .unwrap();
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_reward_distribution(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        mint: &MintCookie,
+        duration_secs: u64,
+    ) -> std::result::Result<RewardDistributionCookie, BanksClientError> {
+        let reward_mint = mint.pubkey.unwrap();
+        let distribution = Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"reward-distribution".as_ref(),
+                &reward_mint.to_bytes(),
+            ],
+            &self.program_id,
+        )
+        .0;
+
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::CreateRewardDistribution { duration_secs },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CreateRewardDistribution {
+                registrar: registrar.address,
+                realm_authority: authority.pubkey(),
+                distribution,
+                vault: spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &distribution,
+                    &reward_mint,
+                    &mint.token_program,
+                ),
+                reward_mint,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                token_program: mint.token_program,
+                associated_token_program: spl_associated_token_account::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&authority.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&payer.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await?;
+
+        Ok(RewardDistributionCookie {
+            address: distribution,
+            mint: mint.clone(),
+        })
+    }
+
+    #[allow(dead_code)]
+    pub async fn fund_reward_distribution(
+        &self,
+        registrar: &RegistrarCookie,
+        distribution: &RewardDistributionCookie,
+        funder: &Keypair,
+        token_address: Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::FundRewardDistribution { amount },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::FundRewardDistribution {
+                registrar: registrar.address,
+                distribution: distribution.address,
+                vault: distribution.vault_address(),
+                funding_token: token_address,
+                funder: funder.pubkey(),
+                reward_mint: distribution.mint.pubkey.unwrap(),
+                token_program: distribution.mint.token_program,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&funder.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_voter_reward(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+        distribution: &RewardDistributionCookie,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::CreateVoterReward {},
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CreateVoterReward {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                distribution: distribution.address,
+                voter_reward: distribution.voter_reward_address(voter),
+                payer: voter_authority.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn update_voter_rewards(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        distributions: &[&RewardDistributionCookie],
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::UpdateVoterRewards {},
+        );
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::UpdateVoterRewards {
+                registrar: registrar.address,
+                voter: voter.address,
+            },
+            None,
+        );
+        for distribution in distributions {
+            accounts.extend(distribution.reward_account_metas(voter));
+        }
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    #[allow(dead_code)]
+    pub async fn claim_reward(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+        distribution: &RewardDistributionCookie,
+        token_address: Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::ClaimReward {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::ClaimReward {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                distribution: distribution.address,
+                voter_reward: distribution.voter_reward_address(voter),
+                vault: distribution.vault_address(),
+                destination: token_address,
+                reward_mint: distribution.mint.pubkey.unwrap(),
+                token_program: distribution.mint.token_program,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn close_voter_reward(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+        distribution: &RewardDistributionCookie,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::CloseVoterReward {},
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CloseVoterReward {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                distribution: distribution.address,
+                voter_reward: distribution.voter_reward_address(voter),
+                sol_destination: voter_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_voter_weight_checkpoints(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::CreateVoterWeightCheckpoints {},
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CreateVoterWeightCheckpoints {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                checkpoints: voter.weight_checkpoints_address(),
+                payer: voter_authority.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn update_historical_voter_weight_record(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        unix_ts: i64,
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        self.update_historical_voter_weight_record_for_action(registrar, voter, unix_ts, None, None)
+            .await
+    }
+
+    pub fn update_historical_voter_weight_record_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        unix_ts: i64,
+        weight_action: Option<voter_stake_registry::state::VoterWeightAction>,
+        weight_action_target: Option<Pubkey>,
+    ) -> Instruction {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::UpdateHistoricalVoterWeightRecord {
+                unix_ts,
+                weight_action,
+                weight_action_target,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::UpdateHistoricalVoterWeightRecord {
+                registrar: registrar.address,
+                voter: voter.address,
+                checkpoints: voter.weight_checkpoints_address(),
+                voter_weight_record: voter.voter_weight_record,
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    /// Update the voter weight record with the weight at `unix_ts`, scoping
+    /// it to an action and target
+    #[allow(dead_code)]
+    pub async fn update_historical_voter_weight_record_for_action(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        unix_ts: i64,
+        weight_action: Option<voter_stake_registry::state::VoterWeightAction>,
+        weight_action_target: Option<Pubkey>,
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let instructions = vec![self.update_historical_voter_weight_record_instruction(
+            registrar,
+            voter,
+            unix_ts,
+            weight_action,
+            weight_action_target,
+        )];
+
+        self.solana.process_transaction(&instructions, None).await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    /// Update the voter weight record with the weight at `unix_ts`, passing
+    /// the predecessor plugin's record
+    #[allow(dead_code)]
+    pub async fn update_historical_voter_weight_record_with_predecessor(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        unix_ts: i64,
+        predecessor_record: Pubkey,
+    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
+        let mut instruction = self.update_historical_voter_weight_record_instruction(
+            registrar, voter, unix_ts, None, None,
+        );
+        instruction
+            .accounts
+            .push(anchor_lang::prelude::AccountMeta::new_readonly(
+                predecessor_record,
+                false,
+            ));
+
+        self.solana
+            .process_transaction(&[instruction], None)
+            .await?;
+
+        Ok(self
+            .solana
+            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
+                voter.voter_weight_record,
+            )
+            .await)
+    }
+
+    #[allow(dead_code)]
+    pub async fn close_voter_weight_checkpoints(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        voter_authority: &Keypair,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::CloseVoterWeightCheckpoints {},
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::CloseVoterWeightCheckpoints {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_authority: voter_authority.pubkey(),
+                checkpoints: voter.weight_checkpoints_address(),
+                sol_destination: voter_authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn set_time_offset(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        time_offset: i64,
+    ) {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::SetTimeOffset {
+                time_offset,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::SetTimeOffset {
+                registrar: registrar.address,
+                realm_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+            .unwrap();
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_deposit_limits(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        index: u16,
+        min_deposit_native: u64,
+        max_deposited_native: u64,
+        max_voter_deposited_native: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::ConfigureDepositLimits {
+                idx: index,
+                min_deposit_native,
+                max_deposited_native,
+                max_voter_deposited_native,
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::ConfigureDepositLimits {
+                registrar: registrar.address,
+                realm_authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_lockup_limits(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        index: u16,
+        allowed_lockup_kinds: &[voter_stake_registry::state::LockupKind],
+        min_lockup_secs: u64,
+        max_lockup_secs: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::ConfigureLockupLimits {
+                idx: index,
+                allowed_lockup_kinds: allowed_lockup_kinds
+                    .iter()
+                    .fold(0, |mask, kind| mask | 1 << *kind as u32),
+                min_lockup_secs,
+                max_lockup_secs,
+            },
+        );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::Clawback {
+            &voter_stake_registry::accounts::ConfigureLockupLimits {
                 registrar: registrar.address,
-                voter: voter.address,
-                vault,
-                destination: token_address,
-                realm_authority: realm_authority.pubkey(),
-                token_program: spl_token::id(),
+                realm_authority: authority.pubkey(),
             },
             None,
         );
@@ -444,7 +2641,7 @@ impl AddinCookie {
         }];
 
         // clone the secrets
-        let signer = Keypair::from_base58_string(&realm_authority.to_base58_string());
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
 
         self.solana
             .process_transaction(&instructions, Some(&[&signer]))
@@ -452,34 +2649,20 @@ impl AddinCookie {
     }
 
     #[allow(dead_code)]
-    pub async fn withdraw(
+    pub async fn retire_voting_mint(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-        voting_mint: &VotingMintConfigCookie,
         authority: &Keypair,
-        token_address: Pubkey,
-        deposit_entry_index: u8,
-        amount: u64,
+        index: u16,
     ) -> std::result::Result<(), BanksClientError> {
-        let vault = voter.vault_address(&voting_mint);
-
-        let data =
-            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::Withdraw {
-                deposit_entry_index,
-                amount,
-            });
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::RetireVotingMint { idx: index },
+        );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::Withdraw {
+            &voter_stake_registry::accounts::RetireVotingMint {
                 registrar: registrar.address,
-                voter: voter.address,
-                token_owner_record: voter.token_owner_record,
-                voter_weight_record: voter.voter_weight_record,
-                vault,
-                destination: token_address,
-                voter_authority: authority.pubkey(),
-                token_program: spl_token::id(),
+                realm_authority: authority.pubkey(),
             },
             None,
         );
@@ -499,29 +2682,24 @@ impl AddinCookie {
     }
 
     #[allow(dead_code)]
-    pub async fn close_voter(
+    pub async fn clear_voting_mint(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-        voting_mint: &VotingMintConfigCookie,
-        voter_authority: &Keypair,
+        authority: &Keypair,
+        index: u16,
     ) -> std::result::Result<(), BanksClientError> {
-        let vault = voter.vault_address(&voting_mint);
-
-        let data =
-            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::CloseVoter {});
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::ClearVotingMint { idx: index },
+        );
 
-        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::CloseVoter {
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &voter_stake_registry::accounts::ClearVotingMint {
                 registrar: registrar.address,
-                voter: voter.address,
-                voter_authority: voter_authority.pubkey(),
-                sol_destination: voter_authority.pubkey(),
-                token_program: spl_token::id(),
+                realm_authority: authority.pubkey(),
+                registrar_stats: registrar.stats_address().unwrap(),
             },
             None,
         );
-        accounts.push(anchor_lang::prelude::AccountMeta::new(vault, false));
 
         let instructions = vec![Instruction {
             program_id: self.program_id,
@@ -530,74 +2708,76 @@ impl AddinCookie {
         }];
 
         // clone the secrets
-        let signer = Keypair::from_base58_string(&voter_authority.to_base58_string());
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
 
         self.solana
             .process_transaction(&instructions, Some(&[&signer]))
             .await
     }
 
-    pub fn update_voter_weight_record_instruction(
+    #[allow(dead_code)]
+    pub async fn configure_voting_mint_rate(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-    ) -> Instruction {
+        authority: &Keypair,
+        payer: &Keypair,
+        voting_mint: &VotingMintConfigCookie,
+        rate_authority: Pubkey,
+        max_rate: u64,
+        max_staleness_secs: u64,
+    ) -> std::result::Result<(), BanksClientError> {
         let data = anchor_lang::InstructionData::data(
-            &voter_stake_registry::instruction::UpdateVoterWeightRecord {},
+            &voter_stake_registry::instruction::ConfigureVotingMintRate {
+                authority: rate_authority,
+                max_rate,
+                max_staleness_secs,
+            },
         );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::UpdateVoterWeightRecord {
+            &voter_stake_registry::accounts::ConfigureVotingMintRate {
                 registrar: registrar.address,
-                voter: voter.address,
-                voter_weight_record: voter.voter_weight_record,
+                realm_authority: authority.pubkey(),
+                voting_mint_rate: voting_mint.rate_address(registrar),
+                mint: voting_mint.mint.pubkey.unwrap(),
+                payer: payer.pubkey(),
                 system_program: solana_sdk::system_program::id(),
             },
             None,
         );
 
-        Instruction {
+        let instructions = vec![Instruction {
             program_id: self.program_id,
             accounts,
             data,
-        }
-    }
-
-    #[allow(dead_code)]
-    pub async fn update_voter_weight_record(
-        &self,
-        registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-    ) -> std::result::Result<voter_stake_registry::state::VoterWeightRecord, BanksClientError> {
-        let instructions = vec![self.update_voter_weight_record_instruction(registrar, voter)];
+        }];
 
-        self.solana.process_transaction(&instructions, None).await?;
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&authority.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&payer.to_base58_string());
 
-        Ok(self
-            .solana
-            .get_account::<voter_stake_registry::state::VoterWeightRecord>(
-                voter.voter_weight_record,
-            )
-            .await)
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
     }
 
     #[allow(dead_code)]
-    pub async fn close_deposit_entry(
+    pub async fn set_voting_mint_rate(
         &self,
-        voter: &VoterCookie,
+        registrar: &RegistrarCookie,
+        voting_mint: &VotingMintConfigCookie,
         authority: &Keypair,
-        deposit_entry_index: u8,
-    ) -> Result<(), BanksClientError> {
+        rate: u64,
+    ) -> std::result::Result<(), BanksClientError> {
         let data = anchor_lang::InstructionData::data(
-            &voter_stake_registry::instruction::CloseDepositEntry {
-                deposit_entry_index,
-            },
+            &voter_stake_registry::instruction::SetVotingMintRate { rate },
         );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::CloseDepositEntry {
-                voter: voter.address,
-                voter_authority: authority.pubkey(),
+            &voter_stake_registry::accounts::SetVotingMintRate {
+                registrar: registrar.address,
+                voting_mint_rate: voting_mint.rate_address(registrar),
+                authority: authority.pubkey(),
             },
             None,
         );
@@ -617,27 +2797,20 @@ impl AddinCookie {
     }
 
     #[allow(dead_code)]
-    pub async fn reset_lockup(
+    pub async fn set_pause_guardian(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
         authority: &Keypair,
-        deposit_entry_index: u8,
-        kind: voter_stake_registry::state::LockupKind,
-        periods: u32,
-    ) -> Result<(), BanksClientError> {
-        let data =
-            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::ResetLockup {
-                deposit_entry_index,
-                kind,
-                periods,
-            });
+        pause_guardian: Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::SetPauseGuardian { pause_guardian },
+        );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::ResetLockup {
+            &voter_stake_registry::accounts::SetPauseGuardian {
                 registrar: registrar.address,
-                voter: voter.address,
-                voter_authority: authority.pubkey(),
+                realm_authority: authority.pubkey(),
             },
             None,
         );
@@ -657,28 +2830,21 @@ impl AddinCookie {
     }
 
     #[allow(dead_code)]
-    pub async fn internal_transfer_locked(
+    pub async fn set_paused(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
         authority: &Keypair,
-        source_deposit_entry_index: u8,
-        target_deposit_entry_index: u8,
-        amount: u64,
-    ) -> Result<(), BanksClientError> {
-        let data = anchor_lang::InstructionData::data(
-            &voter_stake_registry::instruction::InternalTransferLocked {
-                source_deposit_entry_index,
-                target_deposit_entry_index,
-                amount,
-            },
-        );
+        paused: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data =
+            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::SetPaused {
+                paused,
+            });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::InternalTransferLocked {
+            &voter_stake_registry::accounts::SetPaused {
                 registrar: registrar.address,
-                voter: voter.address,
-                voter_authority: authority.pubkey(),
+                authority: authority.pubkey(),
             },
             None,
         );
@@ -698,28 +2864,24 @@ impl AddinCookie {
     }
 
     #[allow(dead_code)]
-    pub async fn internal_transfer_unlocked(
+    pub async fn configure_predecessor_plugin(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
         authority: &Keypair,
-        source_deposit_entry_index: u8,
-        target_deposit_entry_index: u8,
-        amount: u64,
-    ) -> Result<(), BanksClientError> {
+        predecessor_program_id: Pubkey,
+        predecessor_weight_mode: voter_stake_registry::state::PredecessorWeightMode,
+    ) -> std::result::Result<(), BanksClientError> {
         let data = anchor_lang::InstructionData::data(
-            &voter_stake_registry::instruction::InternalTransferUnlocked {
-                source_deposit_entry_index,
-                target_deposit_entry_index,
-                amount,
+            &voter_stake_registry::instruction::ConfigurePredecessorPlugin {
+                predecessor_program_id,
+                predecessor_weight_mode,
             },
         );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::InternalTransferUnlocked {
+            &voter_stake_registry::accounts::ConfigurePredecessorPlugin {
                 registrar: registrar.address,
-                voter: voter.address,
-                voter_authority: authority.pubkey(),
+                realm_authority: authority.pubkey(),
             },
             None,
         );
@@ -739,22 +2901,22 @@ impl AddinCookie {
     }
 
     #[allow(dead_code)]
-    pub async fn log_voter_info(
+    pub async fn set_early_unlock_treasury(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-        deposit_entry_begin: u8,
-    ) {
-        let data =
-            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::LogVoterInfo {
-                deposit_entry_begin,
-                deposit_entry_count: 8,
-            });
+        authority: &Keypair,
+        early_unlock_treasury: Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::SetEarlyUnlockTreasury {
+                early_unlock_treasury,
+            },
+        );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::LogVoterInfo {
+            &voter_stake_registry::accounts::SetEarlyUnlockTreasury {
                 registrar: registrar.address,
-                voter: voter.address,
+                realm_authority: authority.pubkey(),
             },
             None,
         );
@@ -765,26 +2927,29 @@ impl AddinCookie {
             data,
         }];
 
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
         self.solana
-            .process_transaction(&instructions, None)
+            .process_transaction(&instructions, Some(&[&signer]))
             .await
-            .unwrap();
     }
 
     #[allow(dead_code)]
-    pub async fn set_time_offset(
+    pub async fn set_historical_voter_weight(
         &self,
         registrar: &RegistrarCookie,
         authority: &Keypair,
-        time_offset: i64,
-    ) {
-        let data =
-            anchor_lang::InstructionData::data(&voter_stake_registry::instruction::SetTimeOffset {
-                time_offset,
-            });
+        historical_voter_weight: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        let data = anchor_lang::InstructionData::data(
+            &voter_stake_registry::instruction::SetHistoricalVoterWeight {
+                historical_voter_weight,
+            },
+        );
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &voter_stake_registry::accounts::SetTimeOffset {
+            &voter_stake_registry::accounts::SetHistoricalVoterWeight {
                 registrar: registrar.address,
                 realm_authority: authority.pubkey(),
             },
@@ -803,7 +2968,6 @@ impl AddinCookie {
         self.solana
             .process_transaction(&instructions, Some(&[&signer]))
             .await
-            .unwrap();
     }
 
     #[allow(dead_code)]
@@ -850,28 +3014,132 @@ impl AddinCookie {
     }
 }
 
+impl RegistrarCookie {
+    pub fn stats_address_for(registrar: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[&registrar.to_bytes(), b"registrar-stats".as_ref()],
+            &voter_stake_registry::id(),
+        )
+        .0
+    }
+
+    /// The RegistrarStats address, None for registrars without one.
+    pub fn stats_address(&self) -> Option<Pubkey> {
+        self.has_stats
+            .then(|| Self::stats_address_for(&self.address))
+    }
+}
+
 impl VotingMintConfigCookie {
+    #[allow(dead_code)]
+    pub fn rate_address(&self, registrar: &RegistrarCookie) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                &registrar.address.to_bytes(),
+                b"voting-mint-rate".as_ref(),
+                &self.mint.pubkey.unwrap().to_bytes(),
+            ],
+            &voter_stake_registry::id(),
+        )
+        .0
+    }
+
     #[allow(dead_code)]
     pub async fn vault_balance(&self, solana: &SolanaCookie, voter: &VoterCookie) -> u64 {
         let vault = voter.vault_address(&self);
-        solana.get_account::<TokenAccount>(vault).await.amount
+        solana.token_account_balance(vault).await
     }
 }
 
 impl VoterCookie {
     #[allow(dead_code)]
     pub async fn deposit_amount(&self, solana: &SolanaCookie, deposit_id: u8) -> u64 {
-        solana
-            .get_account::<voter_stake_registry::state::Voter>(self.address)
-            .await
-            .deposits[deposit_id as usize]
-            .amount_deposited_native
+        use voter_stake_registry::state::{DepositEntry, Voter};
+        let voter = solana.get_account::<Voter>(self.address).await;
+        let inline_len = voter.deposits.len();
+        if (deposit_id as usize) < inline_len {
+            return voter.deposits[deposit_id as usize].amount_deposited_native;
+        }
+
+        // entries added by grow_voter follow the Voter struct
+        let entry_size = std::mem::size_of::<DepositEntry>();
+        let offset =
+            8 + std::mem::size_of::<Voter>() + (deposit_id as usize - inline_len) * entry_size;
+        let data = solana.get_account_data(self.address).await;
+        let entry: DepositEntry = bytemuck::pod_read_unaligned(&data[offset..offset + entry_size]);
+        entry.amount_deposited_native
     }
 
     pub fn vault_address(&self, mint: &VotingMintConfigCookie) -> Pubkey {
-        spl_associated_token_account::get_associated_token_address(
+        spl_associated_token_account::get_associated_token_address_with_program_id(
             &self.address,
             &mint.mint.pubkey.unwrap(),
+            &mint.mint.token_program,
+        )
+    }
+
+    pub fn vesting_schedule_address(&self, deposit_entry_index: u8) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                &self.address.to_bytes(),
+                b"vesting-schedule".as_ref(),
+                &[deposit_entry_index],
+            ],
+            &voter_stake_registry::id(),
+        )
+        .0
+    }
+
+    #[allow(dead_code)]
+    pub fn weight_checkpoints_address(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                &self.address.to_bytes(),
+                b"voter-weight-checkpoints".as_ref(),
+            ],
+            &voter_stake_registry::id(),
+        )
+        .0
+    }
+
+    #[allow(dead_code)]
+    pub fn weight_checkpoints_account_meta(&self) -> anchor_lang::prelude::AccountMeta {
+        anchor_lang::prelude::AccountMeta::new(self.weight_checkpoints_address(), false)
+    }
+}
+
+impl RewardDistributionCookie {
+    #[allow(dead_code)]
+    pub fn vault_address(&self) -> Pubkey {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &self.address,
+            &self.mint.pubkey.unwrap(),
+            &self.mint.token_program,
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn voter_reward_address(&self, voter: &VoterCookie) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                &self.address.to_bytes(),
+                b"voter-reward".as_ref(),
+                &voter.address.to_bytes(),
+            ],
+            &voter_stake_registry::id(),
         )
+        .0
+    }
+
+    /// The accounts that instructions changing the voter's deposits need
+    #[allow(dead_code)]
+    pub fn reward_account_metas(
+        &self,
+        voter: &VoterCookie,
+    ) -> Vec<anchor_lang::prelude::AccountMeta> {
+        vec![
+            anchor_lang::prelude::AccountMeta::new(self.address, false),
+            anchor_lang::prelude::AccountMeta::new(self.voter_reward_address(voter), false),
+        ]
     }
 }
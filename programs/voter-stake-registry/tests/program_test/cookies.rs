@@ -10,6 +10,7 @@ pub struct MintCookie {
     pub quote_lot: f64,
     pub pubkey: Option<Pubkey>,
     pub authority: Keypair,
+    pub token_program: Pubkey,
 }
 
 impl Clone for MintCookie {
@@ -22,6 +23,7 @@ impl Clone for MintCookie {
             quote_lot: self.quote_lot,
             pubkey: self.pubkey.clone(),
             authority: clone_keypair(&self.authority),
+            token_program: self.token_program,
         }
     }
 }
@@ -135,6 +135,7 @@ impl TestContext {
                 quote_lot: 10 as f64,
                 pubkey: None, //Some(mngo_token::ID),
                 authority: Keypair::new(),
+                token_program: spl_token::id(),
             }, // symbol: "MNGO".to_string()
             MintCookie {
                 index: 1,
@@ -144,6 +145,7 @@ impl TestContext {
                 quote_lot: 0 as f64,
                 pubkey: None,
                 authority: Keypair::new(),
+                token_program: spl_token::id(),
             }, // symbol: "USDC".to_string()
         ];
         // Add mints in loop
@@ -2,7 +2,6 @@ use std::cell::RefCell;
 use std::sync::{Arc, RwLock};
 
 use anchor_lang::AccountDeserialize;
-use anchor_spl::token::TokenAccount;
 use solana_program::{program_pack::Pack, rent::*, system_instruction};
 use solana_program_test::*;
 use solana_sdk::{
@@ -13,6 +12,9 @@ use solana_sdk::{
     transaction::Transaction,
 };
 use spl_token::*;
+use spl_token_2022::extension::{transfer_fee, ExtensionType, StateWithExtensions};
+
+use super::{clone_keypair, MintCookie};
 
 pub struct SolanaCookie {
     pub context: RefCell<ProgramTestContext>,
@@ -97,6 +99,116 @@ impl SolanaCookie {
         return keypair.pubkey();
     }
 
+    /// Creates a spl-token-2022 mint with the TransferFee extension. Transfers
+    /// pay `transfer_fee_basis_points` of their amount, at most `maximum_fee`.
+    #[allow(dead_code)]
+    pub async fn create_transfer_fee_mint(
+        &self,
+        authority: &Keypair,
+        decimals: u8,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    ) -> MintCookie {
+        let keypair = Keypair::new();
+        let space = ExtensionType::get_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ]);
+        let rent = self.rent.minimum_balance(space);
+
+        let instructions = [
+            system_instruction::create_account(
+                &self.context.borrow().payer.pubkey(),
+                &keypair.pubkey(),
+                rent,
+                space as u64,
+                &spl_token_2022::id(),
+            ),
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::id(),
+                &keypair.pubkey(),
+                Some(&authority.pubkey()),
+                Some(&authority.pubkey()),
+                transfer_fee_basis_points,
+                maximum_fee,
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint(
+                &spl_token_2022::id(),
+                &keypair.pubkey(),
+                &authority.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ];
+
+        self.process_transaction(&instructions, Some(&[&keypair]))
+            .await
+            .unwrap();
+        MintCookie {
+            index: 0,
+            decimals,
+            unit: 10u64.pow(decimals as u32) as f64,
+            base_lot: 0 as f64,
+            quote_lot: 0 as f64,
+            pubkey: Some(keypair.pubkey()),
+            authority: clone_keypair(authority),
+            token_program: spl_token_2022::id(),
+        }
+    }
+
+    /// Creates the associated token account of `owner` for `mint` and mints
+    /// `amount` tokens to it.
+    #[allow(dead_code)]
+    pub async fn create_funded_token_account(
+        &self,
+        owner: &Pubkey,
+        mint: &MintCookie,
+        amount: u64,
+    ) -> Pubkey {
+        let mint_pk = mint.pubkey.unwrap();
+        let address = spl_associated_token_account::get_associated_token_address_with_program_id(
+            owner,
+            &mint_pk,
+            &mint.token_program,
+        );
+        let instructions = [
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &self.context.borrow().payer.pubkey(),
+                owner,
+                &mint_pk,
+                &mint.token_program,
+            ),
+            spl_token_2022::instruction::mint_to(
+                &mint.token_program,
+                &mint_pk,
+                &address,
+                &mint.authority.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        ];
+
+        self.process_transaction(&instructions, Some(&[&mint.authority]))
+            .await
+            .unwrap();
+        address
+    }
+
+    /// Moves the transfer fees withheld in `accounts` to the mint, which
+    /// is necessary before they can be closed.
+    #[allow(dead_code)]
+    pub async fn harvest_withheld_fees(&self, mint: &MintCookie, accounts: &[Pubkey]) {
+        let instructions = [transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+            &mint.token_program,
+            &mint.pubkey.unwrap(),
+            &accounts.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()];
+        self.process_transaction(&instructions, None).await.unwrap();
+    }
+
     #[allow(dead_code)]
     pub async fn get_account_data(&self, address: Pubkey) -> Vec<u8> {
         self.context
@@ -110,6 +222,32 @@ impl SolanaCookie {
             .to_vec()
     }
 
+    #[allow(dead_code)]
+    pub async fn account_exists(&self, address: Pubkey) -> bool {
+        self.context
+            .borrow_mut()
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    /// Overwrites an account, for example to fake accounts of other programs
+    #[allow(dead_code)]
+    pub fn set_account_data(&self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let account = solana_sdk::account::Account {
+            lamports: self.rent.minimum_balance(data.len()),
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context
+            .borrow_mut()
+            .set_account(&address, &account.into());
+    }
+
     #[allow(dead_code)]
     pub async fn get_account<T: AccountDeserialize>(&self, address: Pubkey) -> T {
         let data = self.get_account_data(address).await;
@@ -119,7 +257,12 @@ impl SolanaCookie {
 
     #[allow(dead_code)]
     pub async fn token_account_balance(&self, address: Pubkey) -> u64 {
-        self.get_account::<TokenAccount>(address).await.amount
+        // Works for accounts of both token programs, including extensions.
+        let data = self.get_account_data(address).await;
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)
+            .unwrap()
+            .base
+            .amount
     }
 
     #[allow(dead_code)]
@@ -64,6 +64,6 @@ pub async fn get_lockup_data(
         duration,
         amount_initially_locked_native: d.amount_initially_locked_native,
         amount_deposited_native: d.amount_deposited_native,
-        amount_unlocked: d.amount_unlocked(now),
+        amount_unlocked: d.amount_unlocked(now, None),
     }
 }
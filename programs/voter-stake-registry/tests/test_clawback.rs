@@ -166,6 +166,7 @@ async fn test_clawback() -> Result<(), TransportError> {
             &voter_authority,
             realm_authority_ata,
             0,
+            None,
         )
         .await
         .expect_err("fails because realm_authority is invalid");
@@ -180,6 +181,7 @@ async fn test_clawback() -> Result<(), TransportError> {
             &realm_authority,
             realm_authority_ata,
             0,
+            None,
         )
         .await?;
 
@@ -216,3 +218,201 @@ async fn test_clawback() -> Result<(), TransportError> {
 
     Ok(())
 }
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_clawback_partial() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let community_token_mint = &context.mints[0];
+
+    let realm_authority = &context.users[0].key;
+    let realm_authority_ata = context.users[0].token_accounts[0];
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            community_token_mint,
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = context
+        .addin
+        .create_registrar(&realm, realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint = context
+        .addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            community_token_mint,
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = context
+        .addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    let realm_ata_initial = context
+        .solana
+        .token_account_balance(realm_authority_ata)
+        .await;
+    let voter_ata_initial = context
+        .solana
+        .token_account_balance(voter_authority_ata)
+        .await;
+
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            voter_authority,
+            &mngo_voting_mint,
+            0,
+            voter_stake_registry::state::LockupKind::Daily,
+            None,
+            10,
+            true,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            10000,
+        )
+        .await?;
+
+    // Advance almost three days: 2000 vested, 8000 locked over 8 more days
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, (3 * 24 - 1) * 60 * 60)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    println!("clawback too much");
+    context
+        .addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            Some(8001),
+        )
+        .await
+        .expect_err("fails because only 8000 are locked");
+
+    println!("clawback partial");
+    context
+        .addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            Some(4000),
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana, 0).await, 6000);
+
+    // The remaining 4000 locked tokens vest at 500 per day
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, (4 * 24 - 1) * 60 * 60)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            2501,
+        )
+        .await
+        .expect_err("fails because only 2500 are vested");
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            2500,
+        )
+        .await?;
+
+    println!("clawback rest");
+    context
+        .addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            None,
+        )
+        .await?;
+
+    let realm_after_clawback = context
+        .solana
+        .token_account_balance(realm_authority_ata)
+        .await;
+    assert_eq!(realm_ata_initial - 2500, realm_after_clawback);
+    let voter_after_withdraw = context
+        .solana
+        .token_account_balance(voter_authority_ata)
+        .await;
+    assert_eq!(voter_after_withdraw, voter_ata_initial + 2500);
+    let vault_after_clawback = mngo_voting_mint
+        .vault_balance(&context.solana, &voter)
+        .await;
+    assert_eq!(vault_after_clawback, 0);
+    assert_eq!(voter.deposit_amount(&context.solana, 0).await, 0);
+
+    Ok(())
+}
@@ -0,0 +1,372 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+use program_test::*;
+use voter_stake_registry::state::{LockupKind, Voter};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_delegation() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+    let delegate_authority = &context.users[2].key;
+    let delegate_mngo = context.users[2].token_accounts[0];
+    let delegate_token_owner_record = realm
+        .create_token_owner_record(delegate_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+    let delegate = addin
+        .create_voter(
+            &registrar,
+            &delegate_token_owner_record,
+            &delegate_authority,
+            &payer,
+        )
+        .await;
+
+    for (voter, authority, token_account, amount) in [
+        (&voter, voter_authority, voter_mngo, 1000),
+        (&delegate, delegate_authority, delegate_mngo, 500),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                voter,
+                authority,
+                &mngo_voting_mint,
+                0,
+                LockupKind::None,
+                None,
+                0,
+                false,
+            )
+            .await
+            .unwrap();
+        addin
+            .deposit(
+                &registrar,
+                voter,
+                &mngo_voting_mint,
+                authority,
+                token_account,
+                0,
+                amount,
+            )
+            .await
+            .unwrap();
+    }
+
+    addin
+        .set_delegate(&registrar, &voter, voter_authority, Some(&delegate), None)
+        .await
+        .unwrap();
+    context.solana.advance_clock_by_slots(2).await;
+
+    // the voter's weight moved to the delegate
+    let vwr = addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .unwrap();
+    assert_eq!(vwr.voter_weight, 0);
+    let vwr = addin
+        .update_voter_weight_record(&registrar, &delegate)
+        .await
+        .unwrap();
+    assert_eq!(vwr.voter_weight, 500);
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_delegators(&registrar, &delegate, &[&voter])
+        .await
+        .unwrap();
+    assert_eq!(vwr.voter_weight, 1500);
+
+    // delegators can't be counted twice, and must have delegated to the voter
+    addin
+        .update_voter_weight_record_with_delegators(&registrar, &delegate, &[&voter, &voter])
+        .await
+        .expect_err("duplicate delegator");
+    addin
+        .update_voter_weight_record_with_delegators(&registrar, &voter, &[&delegate])
+        .await
+        .expect_err("not a delegator");
+
+    // withdrawing needs the delegate's records and resets the delegate's weight
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            400,
+        )
+        .await
+        .expect_err("delegate accounts missing");
+    addin
+        .withdraw_delegated(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            400,
+            &delegate,
+        )
+        .await
+        .unwrap();
+    let vwr = context
+        .solana
+        .get_account::<voter_stake_registry::state::VoterWeightRecord>(delegate.voter_weight_record)
+        .await;
+    assert_eq!(vwr.voter_weight, 0);
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_delegators(&registrar, &delegate, &[&voter])
+        .await
+        .unwrap();
+    assert_eq!(vwr.voter_weight, 1100);
+
+    // revoking needs the previous delegate's records
+    addin
+        .set_delegate(&registrar, &voter, voter_authority, None, None)
+        .await
+        .expect_err("previous delegate accounts missing");
+    addin
+        .set_delegate(&registrar, &voter, voter_authority, None, Some(&delegate))
+        .await
+        .unwrap();
+    context.solana.advance_clock_by_slots(2).await;
+
+    let vwr = addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .unwrap();
+    assert_eq!(vwr.voter_weight, 600);
+    addin
+        .update_voter_weight_record_with_delegators(&registrar, &delegate, &[&voter])
+        .await
+        .expect_err("no longer a delegator");
+
+    // can't delegate to oneself
+    addin
+        .set_delegate(&registrar, &voter, voter_authority, Some(&voter), None)
+        .await
+        .expect_err("delegate to self");
+
+    Ok(())
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_delegate_transfer() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+    let delegate_authority = &context.users[2].key;
+    let delegate_token_owner_record = realm
+        .create_token_owner_record(delegate_authority.pubkey(), &payer)
+        .await;
+    let new_authority = &context.users[3].key;
+    let new_token_owner_record = realm
+        .create_token_owner_record(new_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+    let delegate = addin
+        .create_voter(
+            &registrar,
+            &delegate_token_owner_record,
+            &delegate_authority,
+            &payer,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            1000,
+        )
+        .await?;
+
+    addin
+        .set_delegate(&registrar, &voter, voter_authority, Some(&delegate), None)
+        .await?;
+    let delegate_voter = context.solana.get_account::<Voter>(delegate.address).await;
+    assert_eq!(delegate_voter.delegators, 1);
+
+    // delegates can't be transferred or closed while they have delegators
+    addin
+        .propose_voter_authority(&delegate, delegate_authority, new_authority.pubkey())
+        .await?;
+    addin
+        .accept_voter_authority(
+            &registrar,
+            &delegate,
+            &[],
+            &[],
+            new_authority,
+            &new_token_owner_record,
+        )
+        .await
+        .expect_err("the delegate has delegators");
+    addin
+        .close_voter(
+            &registrar,
+            &delegate,
+            &mngo_voting_mint,
+            delegate_authority,
+            &[],
+        )
+        .await
+        .expect_err("the delegate has delegators");
+
+    // revoking the delegation allows the transfer
+    addin
+        .set_delegate(&registrar, &voter, voter_authority, None, Some(&delegate))
+        .await?;
+    let delegate_voter = context.solana.get_account::<Voter>(delegate.address).await;
+    assert_eq!(delegate_voter.delegators, 0);
+    let new_delegate = addin
+        .accept_voter_authority(
+            &registrar,
+            &delegate,
+            &[],
+            &[],
+            new_authority,
+            &new_token_owner_record,
+        )
+        .await?;
+
+    // the former delegator withdraws without any delegate accounts
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            0,
+            1000,
+        )
+        .await?;
+
+    // and can delegate to the new authority
+    addin
+        .set_delegate(
+            &registrar,
+            &voter,
+            voter_authority,
+            Some(&new_delegate),
+            None,
+        )
+        .await?;
+    let new_delegate_voter = context
+        .solana
+        .get_account::<Voter>(new_delegate.address)
+        .await;
+    assert_eq!(new_delegate_voter.delegators, 1);
+
+    Ok(())
+}
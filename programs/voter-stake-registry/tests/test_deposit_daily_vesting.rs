@@ -258,7 +258,7 @@ async fn test_deposit_daily_vesting() -> Result<(), TransportError> {
     assert_eq!(after_withdraw.deposit, 0);
 
     addin
-        .close_deposit_entry(&voter, &voter_authority, 0)
+        .close_deposit_entry(&registrar, &voter, &voter_authority, 0, false)
         .await
         .unwrap();
 
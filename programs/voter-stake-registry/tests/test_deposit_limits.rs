@@ -0,0 +1,244 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_deposit_limits() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let voter2_authority = &context.users[2].key;
+    let voter2_authority_ata = context.users[2].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let token_owner_record2 = realm
+        .create_token_owner_record(voter2_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    addin
+        .configure_deposit_limits(&registrar, &voter_authority, 0, 100, 2000, 1500)
+        .await
+        .expect_err("only the realm authority sets limits");
+    addin
+        .configure_deposit_limits(&registrar, &realm_authority, 1, 100, 2000, 1500)
+        .await
+        .expect_err("voting mint not configured");
+    addin
+        .configure_deposit_limits(&registrar, &realm_authority, 0, 100, 2000, 1500)
+        .await?;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    let voter2 = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record2,
+            &voter2_authority,
+            &realm_authority,
+        )
+        .await;
+
+    for index in 0..2 {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter_authority,
+                &mngo_voting_mint,
+                index,
+                LockupKind::None,
+                None,
+                0,
+                false,
+            )
+            .await?;
+    }
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter2,
+            &voter2_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            50,
+        )
+        .await
+        .expect_err("below the minimum deposit");
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            1,
+            600,
+        )
+        .await
+        .expect_err("above the voter's maximum");
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            1,
+            500,
+        )
+        .await?;
+
+    addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            600,
+        )
+        .await
+        .expect_err("above the registrar's maximum");
+    addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            500,
+        )
+        .await?;
+
+    addin
+        .internal_transfer_unlocked(&registrar, &voter, &voter_authority, 0, 1, 50)
+        .await
+        .expect_err("below the minimum deposit");
+    addin
+        .internal_transfer_unlocked(&registrar, &voter, &voter_authority, 0, 1, 100)
+        .await?;
+
+    // limits can be lifted again and survive reconfiguring the mint
+    addin
+        .configure_deposit_limits(&registrar, &realm_authority, 0, 0, 0, 0)
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            10,
+        )
+        .await?;
+    addin
+        .configure_deposit_limits(&registrar, &realm_authority, 0, 100, 0, 0)
+        .await?;
+    addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+    addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            20,
+        )
+        .await
+        .expect_err("below the minimum deposit");
+
+    Ok(())
+}
@@ -236,7 +236,7 @@ async fn test_deposit_monthly_vesting() -> Result<(), TransportError> {
 
     // cannot close yet, has funds
     addin
-        .close_deposit_entry(&voter, &voter_authority, 0)
+        .close_deposit_entry(&registrar, &voter, &voter_authority, 0, false)
         .await
         .expect_err("deposit not empty");
 
@@ -249,7 +249,7 @@ async fn test_deposit_monthly_vesting() -> Result<(), TransportError> {
     assert_eq!(after_withdraw.deposit, 0);
 
     addin
-        .close_deposit_entry(&voter, &voter_authority, 0)
+        .close_deposit_entry(&registrar, &voter, &voter_authority, 0, false)
         .await
         .unwrap();
 
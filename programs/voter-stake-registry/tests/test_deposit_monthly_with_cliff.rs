@@ -0,0 +1,235 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transport::TransportError};
+
+mod program_test;
+
+struct Balances {
+    token: u64,
+    vault: u64,
+    deposit: u64,
+    voter_weight: u64,
+}
+
+async fn balances(
+    context: &TestContext,
+    registrar: &RegistrarCookie,
+    address: Pubkey,
+    voter: &VoterCookie,
+    rate: &VotingMintConfigCookie,
+    deposit_id: u8,
+) -> Balances {
+    // Advance slots to avoid caching of the UpdateVoterWeightRecord call
+    // TODO: Is this something that could be an issue on a live node?
+    context.solana.advance_clock_by_slots(2).await;
+
+    let token = context.solana.token_account_balance(address).await;
+    let vault = rate.vault_balance(&context.solana, &voter).await;
+    let deposit = voter.deposit_amount(&context.solana, deposit_id).await;
+    let vwr = context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .unwrap();
+    Balances {
+        token,
+        vault,
+        deposit,
+        voter_weight: vwr.voter_weight,
+    }
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_deposit_monthly_with_cliff() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+    let get_balances = |depot_id| {
+        balances(
+            &context,
+            &registrar,
+            reference_account,
+            &voter,
+            &mngo_voting_mint,
+            depot_id,
+        )
+    };
+    let withdraw = |amount: u64| {
+        addin.withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            reference_account,
+            0,
+            amount,
+        )
+    };
+    let deposit = |amount: u64| {
+        addin.deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            reference_account,
+            0,
+            amount,
+        )
+    };
+
+    let token = context
+        .solana
+        .token_account_balance(reference_account)
+        .await;
+
+    // the cliff can't be longer than the lockup
+    addin
+        .create_deposit_entry_with_cliff(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            voter_stake_registry::state::LockupKind::MonthlyWithCliff,
+            None,
+            4,
+            5,
+            false,
+        )
+        .await
+        .expect_err("cliff too long");
+    // only MonthlyWithCliff lockups have a cliff
+    addin
+        .create_deposit_entry_with_cliff(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            voter_stake_registry::state::LockupKind::Monthly,
+            None,
+            4,
+            2,
+            false,
+        )
+        .await
+        .expect_err("cliff on monthly lockup");
+
+    addin
+        .create_deposit_entry_with_cliff(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            voter_stake_registry::state::LockupKind::MonthlyWithCliff,
+            None,
+            4,
+            2,
+            false,
+        )
+        .await
+        .unwrap();
+    deposit(8000).await.unwrap();
+
+    let after_deposit = get_balances(0).await;
+    assert_eq!(token, after_deposit.token + after_deposit.vault);
+    assert_eq!(after_deposit.voter_weight, after_deposit.vault);
+    assert_eq!(after_deposit.vault, 8000);
+    assert_eq!(after_deposit.deposit, 8000);
+
+    // cannot withdraw yet, nothing is vested
+    withdraw(1).await.expect_err("nothing vested yet");
+
+    // advance past the first month, still within the cliff
+    addin
+        .set_time_offset(&registrar, &realm_authority, 32 * 24 * 60 * 60)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    withdraw(1)
+        .await
+        .expect_err("nothing vested during the cliff");
+
+    // advance past the cliff, two months vest at once
+    addin
+        .set_time_offset(&registrar, &realm_authority, 61 * 24 * 60 * 60)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    withdraw(4001).await.expect_err("withdrew too much");
+    withdraw(4000).await.unwrap();
+
+    let after_withdraw = get_balances(0).await;
+    assert_eq!(token, after_withdraw.token + after_withdraw.vault);
+    assert_eq!(after_withdraw.voter_weight, after_withdraw.vault);
+    assert_eq!(after_withdraw.vault, 4000);
+    assert_eq!(after_withdraw.deposit, 4000);
+
+    // then one month vests at a time
+    addin
+        .set_time_offset(&registrar, &realm_authority, 92 * 24 * 60 * 60)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    withdraw(2001).await.expect_err("withdrew too much");
+    withdraw(2000).await.unwrap();
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 122 * 24 * 60 * 60)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    withdraw(2000).await.unwrap();
+
+    let after_withdraw = get_balances(0).await;
+    assert_eq!(token, after_withdraw.token + after_withdraw.vault);
+    assert_eq!(after_withdraw.voter_weight, after_withdraw.vault);
+    assert_eq!(after_withdraw.vault, 0);
+    assert_eq!(after_withdraw.deposit, 0);
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_early_unlock() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let treasury = context.users[0].token_accounts[0];
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    // a lockup of the full saturation time costs half of the locked tokens
+    let day = 24 * 60 * 60;
+    let mngo_voting_mint = addin
+        .configure_voting_mint_with_penalty(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            1.0,
+            100 * day as u64,
+            None,
+            None,
+            0.5,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    let early_unlock = |treasury, index: u8| {
+        addin.early_unlock(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            treasury,
+            index,
+        )
+    };
+    let deposit = |index: u8, amount: u64| {
+        addin.deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            index,
+            amount,
+        )
+    };
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::Cliff,
+            None,
+            50,
+            false,
+        )
+        .await?;
+    deposit(0, 10000).await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Cliff,
+            None,
+            50,
+            true,
+        )
+        .await?;
+    deposit(1, 1000).await?;
+
+    early_unlock(treasury, 0)
+        .await
+        .expect_err("no treasury configured");
+
+    addin
+        .set_early_unlock_treasury(&registrar, &voter_authority, voter_authority.pubkey())
+        .await
+        .expect_err("only the realm authority can set the treasury");
+    addin
+        .set_early_unlock_treasury(&registrar, &realm_authority, realm_authority.pubkey())
+        .await?;
+
+    early_unlock(voter_authority_ata, 0)
+        .await
+        .expect_err("not owned by the treasury");
+    early_unlock(treasury, 1)
+        .await
+        .expect_err("deposit allows clawback");
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 10 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    let treasury_initial = context.solana.token_account_balance(treasury).await;
+
+    // 40 days left: 10000 * 0.5 * 40 / 100
+    early_unlock(treasury, 0).await?;
+    assert_eq!(
+        context.solana.token_account_balance(treasury).await,
+        treasury_initial + 2000
+    );
+
+    let voter_data = context
+        .solana
+        .get_account::<voter_stake_registry::state::Voter>(voter.address)
+        .await;
+    let deposit = &voter_data.deposits[0];
+    assert_eq!(deposit.lockup.kind, LockupKind::None);
+    assert_eq!(deposit.amount_deposited_native, 8000);
+
+    // the unlocked deposit only has baseline weight left
+    let vwr = context
+        .solana
+        .get_account::<voter_stake_registry::state::VoterWeightRecord>(voter.voter_weight_record)
+        .await;
+    assert_eq!(vwr.voter_weight, 8000 + 1000 + 400);
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            8000,
+        )
+        .await?;
+    assert_eq!(
+        mngo_voting_mint
+            .vault_balance(&context.solana, &voter)
+            .await,
+        1000
+    );
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::{LockupKind, Voter};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_external_transfer_locked() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let target_authority = &context.users[2].key;
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let target_token_owner_record = realm
+        .create_token_owner_record(target_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    let target_voter = addin
+        .create_voter(
+            &registrar,
+            &target_token_owner_record,
+            &target_authority,
+            &realm_authority,
+        )
+        .await;
+
+    let create_deposit_entry = |index: u8, allow_clawback: bool| {
+        addin.create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            index,
+            LockupKind::Cliff,
+            None,
+            10,
+            allow_clawback,
+        )
+    };
+    let deposit = |index: u8, amount: u64| {
+        addin.deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            index,
+            amount,
+        )
+    };
+    create_deposit_entry(0, false).await?;
+    deposit(0, 1000).await?;
+    create_deposit_entry(1, true).await?;
+    deposit(1, 1000).await?;
+
+    addin
+        .external_transfer_locked(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            &target_voter,
+            &target_authority,
+            1,
+            0,
+            300,
+        )
+        .await
+        .expect_err("can't transfer from clawback deposits");
+
+    // a fresh target entry gets the source's lockup
+    addin
+        .external_transfer_locked(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            &target_voter,
+            &target_authority,
+            0,
+            0,
+            300,
+        )
+        .await?;
+
+    let voter_data = context.solana.get_account::<Voter>(voter.address).await;
+    let target_data = context
+        .solana
+        .get_account::<Voter>(target_voter.address)
+        .await;
+    let source = &voter_data.deposits[0];
+    let target = &target_data.deposits[0];
+    assert_eq!(source.amount_deposited_native, 700);
+    assert_eq!(target.amount_deposited_native, 300);
+    assert_eq!(target.amount_initially_locked_native, 300);
+    assert_eq!(target.lockup.kind, LockupKind::Cliff);
+    assert_eq!(target.lockup.seconds_left(0), source.lockup.seconds_left(0));
+    assert!(!target.allow_clawback);
+    assert_eq!(
+        mngo_voting_mint
+            .vault_balance(&context.solana, &target_voter)
+            .await,
+        300
+    );
+
+    // existing target entries must be locked at least as long
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &target_voter,
+            &target_authority,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Cliff,
+            None,
+            5,
+            false,
+        )
+        .await?;
+    addin
+        .external_transfer_locked(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            &target_voter,
+            &target_authority,
+            0,
+            1,
+            200,
+        )
+        .await
+        .expect_err("target lockup too short");
+    addin
+        .external_transfer_locked(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            &target_voter,
+            &target_authority,
+            0,
+            0,
+            200,
+        )
+        .await?;
+
+    let target_data = context
+        .solana
+        .get_account::<Voter>(target_voter.address)
+        .await;
+    assert_eq!(target_data.deposits[0].amount_deposited_native, 500);
+    assert_eq!(
+        mngo_voting_mint
+            .vault_balance(&context.solana, &voter)
+            .await,
+        1500
+    );
+
+    Ok(())
+}
@@ -0,0 +1,345 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::{LockupKind, Tranche, VestingSchedule};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_grant_tranches() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let realm_authority_ata = context.users[0].token_accounts[0];
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let day = 24 * 60 * 60;
+    let year = 365 * day;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            1.0,
+            year as u64,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    let realm_ata_initial = context
+        .solana
+        .token_account_balance(realm_authority_ata)
+        .await;
+
+    // 10% after a day, 20% after 100 days and the rest after two years
+    let now = context.solana.get_clock().await.unix_timestamp;
+    let tranches = vec![
+        Tranche {
+            unlock_ts: now + day,
+            amount: 1000,
+        },
+        Tranche {
+            unlock_ts: now + 100 * day,
+            amount: 2000,
+        },
+        Tranche {
+            unlock_ts: now + 2 * year,
+            amount: 7000,
+        },
+    ];
+
+    let mut unsorted = tranches.clone();
+    unsorted.swap(0, 1);
+    addin
+        .grant_tranches(
+            &registrar,
+            voter_authority.pubkey(),
+            &mngo_voting_mint,
+            unsorted,
+            true,
+            0,
+            realm_authority_ata,
+            &realm_authority,
+            &realm_authority,
+        )
+        .await
+        .expect_err("tranches must be sorted");
+
+    addin
+        .grant_tranches(
+            &registrar,
+            voter_authority.pubkey(),
+            &mngo_voting_mint,
+            tranches,
+            true,
+            0,
+            realm_authority_ata,
+            &realm_authority,
+            &realm_authority,
+        )
+        .await?;
+
+    let voter_data = context
+        .solana
+        .get_account::<voter_stake_registry::state::Voter>(voter.address)
+        .await;
+    let deposit = &voter_data.deposits[0];
+    assert_eq!(deposit.lockup.kind, LockupKind::Tranches);
+    assert_eq!(deposit.amount_deposited_native, 10000);
+    assert_eq!(deposit.amount_initially_locked_native, 10000);
+    let schedule = context
+        .solana
+        .get_account::<VestingSchedule>(voter.vesting_schedule_address(0))
+        .await;
+    assert_eq!(schedule.voter, voter.address);
+    assert_eq!(schedule.tranches().len(), 3);
+
+    addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .expect_err("vesting schedule missing");
+    let vwr = addin
+        .update_voter_weight_record_with_schedules(&registrar, &voter, &[0])
+        .await?;
+    // baseline plus each tranche's share of the lockup bonus:
+    // 10000 * (1000 * 1/365 + 2000 * 100/365 + 7000 * 1) / 10000
+    assert_eq!(vwr.voter_weight, 10000 + 7550);
+
+    addin
+        .withdraw_with_schedules(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1,
+            &[0],
+        )
+        .await
+        .expect_err("nothing vested yet");
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await
+        .expect_err("vesting schedule missing");
+    addin
+        .withdraw_with_schedules(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1001,
+            &[0],
+        )
+        .await
+        .expect_err("only the first tranche vested");
+    addin
+        .withdraw_with_schedules(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+            &[0],
+        )
+        .await?;
+
+    // claw back the last tranche and half of the second one
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            Some(8000),
+        )
+        .await
+        .expect_err("vesting schedule missing");
+    addin
+        .clawback_tranches(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            Some(8000),
+        )
+        .await?;
+
+    let schedule = context
+        .solana
+        .get_account::<VestingSchedule>(voter.vesting_schedule_address(0))
+        .await;
+    assert_eq!(schedule.tranches().len(), 2);
+    assert_eq!(schedule.tranches()[1].amount, 1000);
+    let voter_data = context
+        .solana
+        .get_account::<voter_stake_registry::state::Voter>(voter.address)
+        .await;
+    let deposit = &voter_data.deposits[0];
+    assert_eq!(deposit.lockup.kind, LockupKind::Tranches);
+    assert_eq!(deposit.amount_deposited_native, 1000);
+    assert_eq!(deposit.amount_initially_locked_native, 2000);
+
+    // claw back the rest
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .clawback_tranches(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            None,
+        )
+        .await?;
+
+    let voter_data = context
+        .solana
+        .get_account::<voter_stake_registry::state::Voter>(voter.address)
+        .await;
+    let deposit = &voter_data.deposits[0];
+    assert_eq!(deposit.lockup.kind, LockupKind::None);
+    assert_eq!(deposit.amount_deposited_native, 0);
+    assert_eq!(deposit.allow_clawback, false);
+
+    let realm_ata_after = context
+        .solana
+        .token_account_balance(realm_authority_ata)
+        .await;
+    assert_eq!(realm_ata_initial - 1000, realm_ata_after);
+    assert_eq!(
+        mngo_voting_mint
+            .vault_balance(&context.solana, &voter)
+            .await,
+        0
+    );
+
+    // closing the deposit entry closes its vesting schedule
+    addin
+        .close_deposit_entry(&registrar, &voter, &voter_authority, 0, true)
+        .await?;
+    assert!(
+        !context
+            .solana
+            .account_exists(voter.vesting_schedule_address(0))
+            .await
+    );
+
+    // a new grant creates the schedule again, closing the voter closes it
+    let now = context.solana.get_clock().await.unix_timestamp;
+    addin
+        .grant_tranches(
+            &registrar,
+            voter_authority.pubkey(),
+            &mngo_voting_mint,
+            vec![Tranche {
+                unlock_ts: now + 100 * day,
+                amount: 1000,
+            }],
+            true,
+            0,
+            realm_authority_ata,
+            &realm_authority,
+            &realm_authority,
+        )
+        .await?;
+    assert!(
+        context
+            .solana
+            .account_exists(voter.vesting_schedule_address(0))
+            .await
+    );
+    addin
+        .close_deposit_entry(&registrar, &voter, &voter_authority, 0, false)
+        .await
+        .expect_err("vesting schedule missing");
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .clawback_tranches(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            0,
+            None,
+        )
+        .await?;
+    addin
+        .close_voter(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            &[0],
+        )
+        .await?;
+    assert!(
+        !context
+            .solana
+            .account_exists(voter.vesting_schedule_address(0))
+            .await
+    );
+
+    Ok(())
+}
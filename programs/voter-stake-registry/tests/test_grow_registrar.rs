@@ -0,0 +1,133 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+use program_test::*;
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_grow_registrar() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let registrar_size = context
+        .solana
+        .get_account_data(registrar.address)
+        .await
+        .len();
+
+    // only the realm authority can grow the registrar
+    addin
+        .grow_registrar(&registrar, &payer, &payer, 2)
+        .await
+        .expect_err("not the realm authority");
+
+    addin
+        .grow_registrar(&registrar, &realm_authority, &payer, 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        context
+            .solana
+            .get_account_data(registrar.address)
+            .await
+            .len(),
+        registrar_size + 2 * std::mem::size_of::<voter_stake_registry::state::VotingMintConfig>()
+    );
+
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+    // the last of the new slots
+    let usdc_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            5,
+            &context.mints[1],
+            0,
+            2.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+
+    for (deposit_id, voting_mint) in [(0, &mngo_voting_mint), (1, &usdc_voting_mint)] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter_authority,
+                voting_mint,
+                deposit_id,
+                LockupKind::None,
+                None,
+                0,
+                false,
+            )
+            .await
+            .unwrap();
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                voting_mint,
+                &voter_authority,
+                context.users[1].token_accounts[voting_mint.mint.index],
+                deposit_id,
+                1000,
+            )
+            .await
+            .unwrap();
+    }
+
+    let vwr = addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .unwrap();
+    assert_eq!(vwr.voter_weight, 1000 + 2 * 1000);
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+use program_test::*;
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_grow_voter() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_mngo = context.users[1].token_accounts[0];
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+    let voter_size = context.solana.get_account_data(voter.address).await.len();
+
+    let create_deposit_entry = |index: u8| {
+        addin.create_deposit_entry(
+            &registrar,
+            &voter,
+            voter_authority,
+            &mngo_voting_mint,
+            index,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+    };
+
+    create_deposit_entry(32)
+        .await
+        .expect_err("deposit entry index out of bounds");
+
+    // only the voter authority can grow the voter
+    addin
+        .grow_voter(&voter, &payer, 2)
+        .await
+        .expect_err("not the voter authority");
+
+    addin.grow_voter(&voter, voter_authority, 2).await.unwrap();
+    assert_eq!(
+        context.solana.get_account_data(voter.address).await.len(),
+        voter_size + 2 * std::mem::size_of::<voter_stake_registry::state::DepositEntry>()
+    );
+
+    for index in [0, 33] {
+        create_deposit_entry(index).await.unwrap();
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                &mngo_voting_mint,
+                voter_authority,
+                voter_mngo,
+                index,
+                1000,
+            )
+            .await
+            .unwrap();
+    }
+    create_deposit_entry(34)
+        .await
+        .expect_err("deposit entry index out of bounds");
+
+    assert_eq!(voter.deposit_amount(&context.solana, 33).await, 1000);
+    let vwr = addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .unwrap();
+    assert_eq!(vwr.voter_weight, 2000);
+
+    // the new entries can be withdrawn from and closed like the initial ones
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            voter_authority,
+            voter_mngo,
+            33,
+            1000,
+        )
+        .await
+        .unwrap();
+    addin
+        .close_deposit_entry(&registrar, &voter, voter_authority, 33, false)
+        .await
+        .unwrap();
+    assert_eq!(voter.deposit_amount(&context.solana, 33).await, 0);
+
+    Ok(())
+}
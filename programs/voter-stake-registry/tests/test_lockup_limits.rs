@@ -0,0 +1,182 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_lockup_limits() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    // only cliff and constant lockups between 30 days and 4 years
+    let day = 24 * 60 * 60;
+    addin
+        .configure_lockup_limits(
+            &registrar,
+            &realm_authority,
+            0,
+            &[LockupKind::Cliff, LockupKind::Constant],
+            4 * 365 * day,
+            30 * day,
+        )
+        .await
+        .expect_err("minimum above maximum");
+    addin
+        .configure_lockup_limits(
+            &registrar,
+            &realm_authority,
+            0,
+            &[LockupKind::Cliff, LockupKind::Constant],
+            30 * day,
+            4 * 365 * day,
+        )
+        .await?;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    for (kind, periods) in [
+        (LockupKind::None, 0),
+        (LockupKind::Daily, 100),
+        (LockupKind::Cliff, 10),
+        (LockupKind::Cliff, 5 * 365),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter_authority,
+                &mngo_voting_mint,
+                0,
+                kind,
+                None,
+                periods,
+                false,
+            )
+            .await
+            .expect_err("lockup not allowed");
+    }
+    for (index, kind, periods) in [
+        (0, LockupKind::Cliff, 100),
+        (1, LockupKind::Constant, 60),
+        (2, LockupKind::Cliff, 300),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter_authority,
+                &mngo_voting_mint,
+                index,
+                kind,
+                None,
+                periods,
+                false,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                &mngo_voting_mint,
+                &voter_authority,
+                voter_authority_ata,
+                index,
+                1000,
+            )
+            .await?;
+    }
+
+    addin
+        .reset_lockup(
+            &registrar,
+            &voter,
+            &voter_authority,
+            0,
+            LockupKind::Cliff,
+            5 * 365,
+        )
+        .await
+        .expect_err("lockup too long");
+    addin
+        .reset_lockup(
+            &registrar,
+            &voter,
+            &voter_authority,
+            0,
+            LockupKind::Cliff,
+            200,
+        )
+        .await?;
+
+    addin
+        .internal_transfer_locked(&registrar, &voter, &voter_authority, 0, 2, 100)
+        .await?;
+
+    // the target's lockup is checked against the current limits
+    addin
+        .configure_lockup_limits(
+            &registrar,
+            &realm_authority,
+            0,
+            &[LockupKind::Cliff, LockupKind::Constant],
+            30 * day,
+            250 * day,
+        )
+        .await?;
+    addin
+        .internal_transfer_locked(&registrar, &voter, &voter_authority, 0, 2, 200)
+        .await
+        .expect_err("target lockup too long");
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+use program_test::*;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_max_vote_weight() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            1.0,
+            365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+    let usdc_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            1,
+            &context.mints[1],
+            0,
+            0.5,
+            0.0,
+            365 * 24 * 60 * 60,
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+    let mints = [
+        mngo_voting_mint.mint.pubkey.unwrap(),
+        usdc_voting_mint.mint.pubkey.unwrap(),
+    ];
+
+    // The test token accounts are created directly, the mint supply starts at 0
+    let record = addin
+        .update_max_vote_weight(&registrar, payer, &mints)
+        .await?;
+    assert_eq!(record.realm, realm.realm);
+    assert_eq!(record.governing_token_mint, mints[0]);
+    assert_eq!(record.max_voter_weight, 0);
+    assert_eq!(
+        record.max_voter_weight_expiry,
+        Some(context.solana.get_clock().await.slot)
+    );
+
+    // Mint tokens to increase supply
+    for (mint, amount) in [(&context.mints[0], 1000), (&context.mints[1], 400)] {
+        let instructions = vec![spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &mint.pubkey.unwrap(),
+            &context.users[1].token_accounts[mint.index],
+            &mint.authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()];
+        context
+            .solana
+            .process_transaction(&instructions, Some(&[&mint.authority]))
+            .await?;
+    }
+
+    context.solana.advance_clock_by_slots(2).await;
+
+    // The existing record is updated
+    let record = addin
+        .update_max_vote_weight(&registrar, payer, &mints)
+        .await?;
+    assert_eq!(record.max_voter_weight, 1000 + 1000 + 200);
+    assert_eq!(
+        record.max_voter_weight_expiry,
+        Some(context.solana.get_clock().await.slot)
+    );
+
+    // Fails if a voting mint is missing
+    addin
+        .update_max_vote_weight(&registrar, payer, &mints[..1])
+        .await
+        .expect_err("usdc mint missing");
+
+    Ok(())
+}
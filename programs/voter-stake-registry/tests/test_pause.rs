@@ -0,0 +1,158 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_pause() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let guardian = &context.users[2].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+
+    addin
+        .set_paused(&registrar, &guardian, true)
+        .await
+        .expect_err("not the pause guardian yet");
+    addin
+        .set_pause_guardian(&registrar, &guardian, guardian.pubkey())
+        .await
+        .expect_err("only the realm authority sets the guardian");
+    addin
+        .set_pause_guardian(&registrar, &realm_authority, guardian.pubkey())
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    addin.set_paused(&registrar, &guardian, true).await?;
+
+    // no new deposits or lockup changes while paused
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            500,
+        )
+        .await
+        .expect_err("registrar is paused");
+    addin
+        .reset_lockup(
+            &registrar,
+            &voter,
+            &voter_authority,
+            0,
+            LockupKind::Cliff,
+            10,
+        )
+        .await
+        .expect_err("registrar is paused");
+
+    // unlocked tokens can still be withdrawn
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            400,
+        )
+        .await?;
+
+    addin
+        .set_paused(&registrar, &realm_authority, false)
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            600,
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use anchor_lang::AnchorSerialize;
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{pubkey::Pubkey, signer::Signer, transport::TransportError};
+use spl_governance_addin_api::voter_weight::VoterWeightRecord as SplVoterWeightRecord;
+use voter_stake_registry::state::{LockupKind, PredecessorWeightMode};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_predecessor_plugin() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+
+    // a record of another plugin, faked since only its data matters
+    let predecessor_program_id = Pubkey::new_unique();
+    let predecessor_record = Pubkey::new_unique();
+    let set_predecessor_record = |owner: Pubkey, voter_weight: u64, expiry: Option<u64>| {
+        let record = SplVoterWeightRecord {
+            account_discriminator: SplVoterWeightRecord::ACCOUNT_DISCRIMINATOR,
+            realm: realm.realm,
+            governing_token_mint: context.mints[0].pubkey.unwrap(),
+            governing_token_owner: owner,
+            voter_weight,
+            voter_weight_expiry: expiry,
+            weight_action: None,
+            weight_action_target: None,
+            reserved: [0; 8],
+        };
+        context.solana.set_account_data(
+            predecessor_record,
+            predecessor_program_id,
+            record.try_to_vec().unwrap(),
+        );
+    };
+    set_predecessor_record(voter_authority.pubkey(), 500, None);
+
+    addin
+        .configure_predecessor_plugin(
+            &registrar,
+            &voter_authority,
+            predecessor_program_id,
+            PredecessorWeightMode::Add,
+        )
+        .await
+        .expect_err("only the realm authority can chain plugins");
+    addin
+        .configure_predecessor_plugin(
+            &registrar,
+            &realm_authority,
+            predecessor_program_id,
+            PredecessorWeightMode::Add,
+        )
+        .await?;
+
+    addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .expect_err("predecessor record missing");
+    let vwr = addin
+        .update_voter_weight_record_with_predecessor(&registrar, &voter, predecessor_record)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1500);
+
+    // a gating plugin: no weight without a pass
+    addin
+        .configure_predecessor_plugin(
+            &registrar,
+            &realm_authority,
+            predecessor_program_id,
+            PredecessorWeightMode::Multiply,
+        )
+        .await?;
+    set_predecessor_record(voter_authority.pubkey(), 1, None);
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_predecessor(&registrar, &voter, predecessor_record)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000);
+
+    set_predecessor_record(voter_authority.pubkey(), 0, None);
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_predecessor(&registrar, &voter, predecessor_record)
+        .await?;
+    assert_eq!(vwr.voter_weight, 0);
+
+    // the predecessor record must be current and belong to the voter
+    set_predecessor_record(voter_authority.pubkey(), 1, Some(0));
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .update_voter_weight_record_with_predecessor(&registrar, &voter, predecessor_record)
+        .await
+        .expect_err("predecessor record expired");
+    set_predecessor_record(realm_authority.pubkey(), 1, None);
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .update_voter_weight_record_with_predecessor(&registrar, &voter, predecessor_record)
+        .await
+        .expect_err("predecessor record of another voter");
+
+    // without a predecessor, the registrar's weight is used alone
+    addin
+        .configure_predecessor_plugin(
+            &registrar,
+            &realm_authority,
+            Pubkey::default(),
+            PredecessorWeightMode::Add,
+        )
+        .await?;
+    let vwr = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(vwr.voter_weight, 1000);
+
+    // historical voter weights are combined with the predecessor's too
+    addin
+        .configure_predecessor_plugin(
+            &registrar,
+            &realm_authority,
+            predecessor_program_id,
+            PredecessorWeightMode::Add,
+        )
+        .await?;
+    set_predecessor_record(voter_authority.pubkey(), 500, None);
+    let now = context.solana.get_clock().await.unix_timestamp;
+    addin
+        .create_voter_weight_checkpoints(&registrar, &voter, &voter_authority)
+        .await?;
+    addin
+        .set_historical_voter_weight(&registrar, &realm_authority, true)
+        .await?;
+    addin
+        .update_historical_voter_weight_record(&registrar, &voter, now)
+        .await
+        .expect_err("predecessor record missing");
+    let vwr = addin
+        .update_historical_voter_weight_record_with_predecessor(
+            &registrar,
+            &voter,
+            now,
+            predecessor_record,
+        )
+        .await?;
+    assert_eq!(vwr.voter_weight, 1500);
+
+    Ok(())
+}
@@ -0,0 +1,328 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::{LockupKind, RegistrarStats, VotingMintStats};
+
+mod program_test;
+
+async fn mint_stats(context: &TestContext, registrar: &RegistrarCookie) -> VotingMintStats {
+    let stats = context
+        .solana
+        .get_account::<RegistrarStats>(registrar.stats_address().unwrap())
+        .await;
+    assert_eq!(stats.registrar, registrar.address);
+    stats.voting_mints[0]
+}
+
+fn stats(
+    deposited_native: u64,
+    initially_locked_native: u64,
+    deposit_entries: u64,
+) -> VotingMintStats {
+    VotingMintStats {
+        deposited_native,
+        initially_locked_native,
+        deposit_entries,
+    }
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_registrar_stats() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let voter2_authority = &context.users[2].key;
+    let voter2_authority_ata = context.users[2].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let token_owner_record2 = realm
+        .create_token_owner_record(voter2_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    let voter2 = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record2,
+            &voter2_authority,
+            &realm_authority,
+        )
+        .await;
+
+    assert_eq!(mint_stats(&context, &registrar).await, stats(0, 0, 0));
+
+    // an unlocked and a locked deposit for the first voter, an unlocked one for the second
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            1,
+            LockupKind::Cliff,
+            None,
+            10,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            1,
+            500,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter2,
+            &voter2_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            300,
+        )
+        .await?;
+    assert_eq!(mint_stats(&context, &registrar).await, stats(1800, 500, 3));
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+    assert_eq!(mint_stats(&context, &registrar).await, stats(800, 500, 3));
+
+    addin
+        .close_deposit_entry(&registrar, &voter, &voter_authority, 0, false)
+        .await?;
+    assert_eq!(mint_stats(&context, &registrar).await, stats(800, 500, 2));
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            300,
+        )
+        .await?;
+    addin
+        .close_voter(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            &[],
+        )
+        .await?;
+    assert_eq!(mint_stats(&context, &registrar).await, stats(500, 500, 1));
+
+    addin.log_registrar_stats(&registrar).await;
+
+    // registrars that have stats need them in every change
+    let registrar_without_stats = RegistrarCookie {
+        address: registrar.address,
+        authority: registrar.authority,
+        realm: registrar.realm,
+        mint: registrar.mint.clone(),
+        has_stats: false,
+    };
+    addin
+        .deposit(
+            &registrar_without_stats,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            1,
+            100,
+        )
+        .await
+        .expect_err("registrar stats missing");
+
+    // registrars without stats keep working, but can't get them anymore
+    // once a voting mint is configured
+    let realm2 = context
+        .governance
+        .create_realm(
+            "testrealm2",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+    let token_owner_record3 = realm2
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let mut registrar2 = addin
+        .create_registrar_without_stats(&realm2, &realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint2 = addin
+        .configure_voting_mint(
+            &registrar2,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+    addin
+        .create_registrar_stats(&mut registrar2, &realm_authority, realm_authority)
+        .await
+        .expect_err("voting mint already configured");
+    addin
+        .configure_deposit_limits(&registrar2, &realm_authority, 0, 0, 1000, 0)
+        .await
+        .expect_err("no registrar stats to track the total");
+    addin
+        .configure_deposit_limits(&registrar2, &realm_authority, 0, 0, 0, 1000)
+        .await?;
+
+    let voter3 = addin
+        .create_voter(
+            &registrar2,
+            &token_owner_record3,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    addin
+        .create_deposit_entry(
+            &registrar2,
+            &voter3,
+            &voter_authority,
+            &mngo_voting_mint2,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar2,
+            &voter3,
+            &mngo_voting_mint2,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            200,
+        )
+        .await?;
+    addin
+        .withdraw(
+            &registrar2,
+            &voter3,
+            &mngo_voting_mint2,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            200,
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use std::cell::RefCell;
+use std::sync::Arc;
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_relock_deposit_entry() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+    let create_deposit_entry = |index: u8, periods: u32| {
+        addin.create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            index,
+            LockupKind::Daily,
+            None,
+            periods,
+            false,
+        )
+    };
+    let deposit = |index: u8, amount: u64| {
+        addin.deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            reference_account,
+            index,
+            amount,
+        )
+    };
+    let relock = |source: u8, target: u8, amount: u64, periods: u32| {
+        addin.relock_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            source,
+            target,
+            amount,
+            LockupKind::Daily,
+            periods,
+        )
+    };
+    let time_offset = Arc::new(RefCell::new(0i64));
+    let advance_time = |extra: u64| {
+        *time_offset.borrow_mut() += extra as i64;
+        addin.set_time_offset(&registrar, &realm_authority, *time_offset.borrow())
+    };
+    let lockup_status =
+        |index: u8| get_lockup_data(&context.solana, voter.address, index, *time_offset.borrow());
+
+    let day = 24 * 60 * 60;
+    let hour = 60 * 60;
+
+    create_deposit_entry(0, 3).await?;
+    deposit(0, 90).await?;
+    advance_time(day + hour).await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // the relocked tokens can't end up locked for less time than before
+    relock(0, 1, 30, 1).await.expect_err("shorter lockup");
+    relock(0, 0, 30, 5).await.expect_err("same entry");
+    relock(0, 1, 100, 5).await.expect_err("more than deposited");
+
+    // after a day, 60 are locked: a third of them and of the unlocked
+    // tokens move, and all of the moved tokens are locked again
+    relock(0, 1, 30, 5).await?;
+    assert_eq!(
+        lockup_status(0).await,
+        LockupData {
+            time_passed: hour,
+            duration: 2 * day,
+            amount_initially_locked_native: 40,
+            amount_deposited_native: 60,
+            amount_unlocked: 20,
+        }
+    );
+    assert_eq!(
+        lockup_status(1).await,
+        LockupData {
+            time_passed: 0,
+            duration: 5 * day,
+            amount_initially_locked_native: 30,
+            amount_deposited_native: 30,
+            amount_unlocked: 0,
+        }
+    );
+
+    // existing target entries must be locked at least as long
+    relock(0, 1, 10, 7)
+        .await
+        .expect_err("target lockup too short");
+    create_deposit_entry(2, 7).await?;
+    deposit(2, 10).await?;
+    relock(0, 2, 10, 5).await?;
+    assert_eq!(
+        lockup_status(2).await,
+        LockupData {
+            time_passed: 0,
+            duration: 7 * day,
+            amount_initially_locked_native: 20,
+            amount_deposited_native: 20,
+            amount_unlocked: 0,
+        }
+    );
+    assert_eq!(lockup_status(0).await.amount_deposited_native, 50);
+
+    Ok(())
+}
@@ -89,6 +89,7 @@ async fn test_reset_lockup() -> Result<(), TransportError> {
     let lockup_status =
         |index: u8| get_lockup_data(&context.solana, voter.address, index, *time_offset.borrow());
 
+    let week = LockupKind::Weekly.period_secs();
     let month = LockupKind::Monthly.period_secs();
     let day = 24 * 60 * 60;
     let hour = 60 * 60;
@@ -284,6 +285,21 @@ async fn test_reset_lockup() -> Result<(), TransportError> {
         }
     );
 
+    reset_lockup(7, 1, LockupKind::Weekly)
+        .await
+        .expect_err("period shortnend");
+    reset_lockup(7, 2, LockupKind::Weekly).await.unwrap();
+    assert_eq!(
+        lockup_status(7).await,
+        LockupData {
+            time_passed: 0,
+            duration: 2 * week,
+            amount_initially_locked_native: 70,
+            amount_deposited_native: 70,
+            amount_unlocked: 0
+        }
+    );
+
     reset_lockup(7, 1, LockupKind::Monthly).await.unwrap();
     assert_eq!(
         lockup_status(7).await,
@@ -299,6 +315,9 @@ async fn test_reset_lockup() -> Result<(), TransportError> {
     reset_lockup(7, 31, LockupKind::Daily)
         .await
         .expect_err("decreasing strictness");
+    reset_lockup(7, 5, LockupKind::Weekly)
+        .await
+        .expect_err("decreasing strictness");
     reset_lockup(7, 31, LockupKind::None)
         .await
         .expect_err("decreasing strictness");
@@ -0,0 +1,203 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_retire_voting_mint() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+    let voter_authority_usdc_ata = context.users[1].token_accounts[1];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+    let usdc_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            1,
+            &context.mints[1],
+            0,
+            1.0,
+            0.0,
+            365 * 24 * 60 * 60,
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+    let mints = [
+        mngo_voting_mint.mint.pubkey.unwrap(),
+        usdc_voting_mint.mint.pubkey.unwrap(),
+    ];
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    for (index, voting_mint, ata, amount) in [
+        (0, &mngo_voting_mint, voter_authority_ata, 1000),
+        (1, &usdc_voting_mint, voter_authority_usdc_ata, 500),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter_authority,
+                voting_mint,
+                index,
+                LockupKind::None,
+                None,
+                0,
+                false,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                voting_mint,
+                &voter_authority,
+                ata,
+                index,
+                amount,
+            )
+            .await?;
+    }
+    let vwr = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(vwr.voter_weight, 1500);
+
+    addin
+        .clear_voting_mint(&registrar, &realm_authority, 1)
+        .await
+        .expect_err("not retired");
+    addin
+        .retire_voting_mint(&registrar, &voter_authority, 1)
+        .await
+        .expect_err("only the realm authority can retire");
+    addin
+        .retire_voting_mint(&registrar, &realm_authority, 1)
+        .await?;
+
+    // no new deposits, no vote weight
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &usdc_voting_mint,
+            &voter_authority,
+            voter_authority_usdc_ata,
+            1,
+            100,
+        )
+        .await
+        .expect_err("voting mint retired");
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &usdc_voting_mint,
+            2,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await
+        .expect_err("voting mint retired");
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(vwr.voter_weight, 1000);
+    addin
+        .update_max_vote_weight(&registrar, payer, &mints[..1])
+        .await?;
+
+    addin
+        .clear_voting_mint(&registrar, &realm_authority, 1)
+        .await
+        .expect_err("deposit entry still uses the mint");
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &usdc_voting_mint,
+            &voter_authority,
+            voter_authority_usdc_ata,
+            1,
+            500,
+        )
+        .await?;
+    addin
+        .close_deposit_entry(&registrar, &voter, &voter_authority, 1, false)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .clear_voting_mint(&registrar, &realm_authority, 1)
+        .await?;
+
+    // the slot can be used again
+    addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            1,
+            &context.mints[1],
+            0,
+            1.0,
+            0.0,
+            365 * 24 * 60 * 60,
+            None,
+            Some(&[context.mints[0].pubkey.unwrap()]),
+        )
+        .await;
+
+    Ok(())
+}
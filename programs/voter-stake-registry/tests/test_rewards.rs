@@ -0,0 +1,595 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::{LockupKind, RewardDistribution, VoterReward};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_rewards() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let realm_authority_ata = context.users[0].token_accounts[0];
+    let funder_ata = context.users[0].token_accounts[1];
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+    let voter_authority_reward_ata = context.users[1].token_accounts[1];
+
+    let voter2_authority = &context.users[2].key;
+    let voter2_authority_ata = context.users[2].token_accounts[0];
+    let voter2_authority_reward_ata = context.users[2].token_accounts[1];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let token_owner_record2 = realm
+        .create_token_owner_record(voter2_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let day = 24 * 60 * 60;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            1.0,
+            100 * day as u64,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    let voter2 = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record2,
+            &voter2_authority,
+            &realm_authority,
+        )
+        .await;
+
+    // locked vote weight of 10000 and 5000
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::Constant,
+            None,
+            100,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            10000,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter2,
+            &voter2_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::Constant,
+            None,
+            50,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            10000,
+        )
+        .await?;
+
+    assert!(
+        addin
+            .create_reward_distribution(
+                &registrar,
+                &voter_authority,
+                &voter_authority,
+                &context.mints[1],
+                10 * day as u64,
+            )
+            .await
+            .is_err(),
+        "only the realm authority can create distributions"
+    );
+    let distribution = addin
+        .create_reward_distribution(
+            &registrar,
+            &realm_authority,
+            &realm_authority,
+            &context.mints[1],
+            10 * day as u64,
+        )
+        .await?;
+
+    addin
+        .create_voter_reward(&registrar, &voter, &voter_authority, &distribution)
+        .await?;
+    addin
+        .create_voter_reward(&registrar, &voter2, &voter2_authority, &distribution)
+        .await?;
+    let position = context
+        .solana
+        .get_account::<VoterReward>(distribution.voter_reward_address(&voter))
+        .await;
+    assert_eq!(position.weight, 10000);
+
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            5000,
+        )
+        .await
+        .expect_err("reward position missing");
+
+    // paid out over ten days, shared 2:1
+    addin
+        .fund_reward_distribution(
+            &registrar,
+            &distribution,
+            &realm_authority,
+            funder_ata,
+            8640,
+        )
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(distribution.vault_address())
+            .await,
+        8640
+    );
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 11 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    let reward_initial = context
+        .solana
+        .token_account_balance(voter_authority_reward_ata)
+        .await;
+    let reward2_initial = context
+        .solana
+        .token_account_balance(voter2_authority_reward_ata)
+        .await;
+
+    addin
+        .claim_reward(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &distribution,
+            voter_authority_reward_ata,
+        )
+        .await?;
+    addin
+        .claim_reward(
+            &registrar,
+            &voter2,
+            &voter2_authority,
+            &distribution,
+            voter2_authority_reward_ata,
+        )
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter_authority_reward_ata)
+            .await,
+        reward_initial + 5760
+    );
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter2_authority_reward_ata)
+            .await,
+        reward2_initial + 2880
+    );
+
+    // raise the first voter's locked vote weight to 15000, now shared 3:1
+    addin
+        .deposit_with_rewards(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            5000,
+            &[&distribution],
+        )
+        .await?;
+    let position = context
+        .solana
+        .get_account::<VoterReward>(distribution.voter_reward_address(&voter))
+        .await;
+    assert_eq!(position.weight, 15000);
+
+    addin
+        .fund_reward_distribution(
+            &registrar,
+            &distribution,
+            &realm_authority,
+            funder_ata,
+            8640,
+        )
+        .await?;
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 22 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // anyone can bring a position up to date
+    addin
+        .update_voter_rewards(&registrar, &voter2, &[&distribution])
+        .await?;
+    let position2 = context
+        .solana
+        .get_account::<VoterReward>(distribution.voter_reward_address(&voter2))
+        .await;
+    assert_eq!(position2.weight, 5000);
+    assert_eq!(position2.rewards_owed, 2160);
+
+    addin
+        .close_voter_reward(&registrar, &voter2, &voter2_authority, &distribution)
+        .await
+        .expect_err("rewards not claimed");
+
+    addin
+        .claim_reward(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &distribution,
+            voter_authority_reward_ata,
+        )
+        .await?;
+    addin
+        .claim_reward(
+            &registrar,
+            &voter2,
+            &voter2_authority,
+            &distribution,
+            voter2_authority_reward_ata,
+        )
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter_authority_reward_ata)
+            .await,
+        reward_initial + 5760 + 6480
+    );
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter2_authority_reward_ata)
+            .await,
+        reward2_initial + 2880 + 2160
+    );
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(distribution.vault_address())
+            .await,
+        0
+    );
+
+    // without reward positions, deposits don't need the reward accounts anymore
+    context.solana.advance_clock_by_slots(2).await;
+    addin
+        .close_voter_reward(&registrar, &voter2, &voter2_authority, &distribution)
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &mngo_voting_mint,
+            &voter2_authority,
+            voter2_authority_ata,
+            0,
+            5000,
+        )
+        .await?;
+
+    // grants raise the locked vote weight of reward positions too
+    addin
+        .grant(
+            &registrar,
+            voter_authority.pubkey(),
+            &mngo_voting_mint,
+            LockupKind::Constant,
+            None,
+            100,
+            false,
+            5000,
+            realm_authority_ata,
+            &realm_authority,
+            &realm_authority,
+        )
+        .await
+        .expect_err("reward position missing");
+    addin
+        .grant_with_rewards(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            LockupKind::Constant,
+            100,
+            5000,
+            realm_authority_ata,
+            &realm_authority,
+            &realm_authority,
+            &[&distribution],
+        )
+        .await?;
+    let position = context
+        .solana
+        .get_account::<VoterReward>(distribution.voter_reward_address(&voter))
+        .await;
+    assert_eq!(position.weight, 20000);
+
+    Ok(())
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_rewards_expired_lockup() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let funder_ata = context.users[0].token_accounts[1];
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+    let voter_authority_reward_ata = context.users[1].token_accounts[1];
+
+    let voter2_authority = &context.users[2].key;
+    let voter2_authority_ata = context.users[2].token_accounts[0];
+    let voter2_authority_reward_ata = context.users[2].token_accounts[1];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let token_owner_record2 = realm
+        .create_token_owner_record(voter2_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let day = 24 * 60 * 60;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            1.0,
+            100 * day as u64,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    let voter2 = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record2,
+            &voter2_authority,
+            &realm_authority,
+        )
+        .await;
+
+    // both start with a locked vote weight of 1000, but only the cliff
+    // lockup decays, until it expires after ten days
+    for (voter, authority, ata, lockup_kind) in [
+        (
+            &voter,
+            voter_authority,
+            voter_authority_ata,
+            LockupKind::Cliff,
+        ),
+        (
+            &voter2,
+            voter2_authority,
+            voter2_authority_ata,
+            LockupKind::Constant,
+        ),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                voter,
+                authority,
+                &mngo_voting_mint,
+                0,
+                lockup_kind,
+                None,
+                10,
+                false,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                voter,
+                &mngo_voting_mint,
+                authority,
+                ata,
+                0,
+                10000,
+            )
+            .await?;
+    }
+
+    let distribution = addin
+        .create_reward_distribution(
+            &registrar,
+            &realm_authority,
+            &realm_authority,
+            &context.mints[1],
+            10 * day as u64,
+        )
+        .await?;
+    addin
+        .create_voter_reward(&registrar, &voter, &voter_authority, &distribution)
+        .await?;
+    addin
+        .create_voter_reward(&registrar, &voter2, &voter2_authority, &distribution)
+        .await?;
+    addin
+        .fund_reward_distribution(
+            &registrar,
+            &distribution,
+            &realm_authority,
+            funder_ata,
+            8640,
+        )
+        .await?;
+
+    // claim long after the cliff lockup expired
+    addin
+        .set_time_offset(&registrar, &realm_authority, 20 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    let reward_initial = context
+        .solana
+        .token_account_balance(voter_authority_reward_ata)
+        .await;
+    let reward2_initial = context
+        .solana
+        .token_account_balance(voter2_authority_reward_ata)
+        .await;
+
+    addin
+        .claim_reward(
+            &registrar,
+            &voter2,
+            &voter2_authority,
+            &distribution,
+            voter2_authority_reward_ata,
+        )
+        .await?;
+    addin
+        .claim_reward(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &distribution,
+            voter_authority_reward_ata,
+        )
+        .await?;
+
+    // the expired position isn't paid at its stale weight, its share is kept
+    // for the next funding
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter2_authority_reward_ata)
+            .await,
+        reward2_initial + 4320
+    );
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter_authority_reward_ata)
+            .await,
+        reward_initial
+    );
+    let position = context
+        .solana
+        .get_account::<VoterReward>(distribution.voter_reward_address(&voter))
+        .await;
+    assert_eq!(position.weight, 0);
+    let distribution_data = context
+        .solana
+        .get_account::<RewardDistribution>(distribution.address)
+        .await;
+    assert_eq!(distribution_data.total_weight, 1000);
+    assert_eq!(distribution_data.undistributed, 4320);
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use std::cell::RefCell;
+use std::sync::Arc;
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_split_merge() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+    let create_deposit_entry = |index: u8, periods: u32| {
+        addin.create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            index,
+            LockupKind::Daily,
+            None,
+            periods,
+            false,
+        )
+    };
+    let deposit = |index: u8, amount: u64| {
+        addin.deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            reference_account,
+            index,
+            amount,
+        )
+    };
+    let split = |source: u8, target: u8, amount: u64| {
+        addin.split_deposit_entry(&registrar, &voter, &voter_authority, source, target, amount)
+    };
+    let merge = |source: u8, target: u8| {
+        addin.merge_deposit_entries(&registrar, &voter, &voter_authority, source, target)
+    };
+    let time_offset = Arc::new(RefCell::new(0i64));
+    let advance_time = |extra: u64| {
+        *time_offset.borrow_mut() += extra as i64;
+        addin.set_time_offset(&registrar, &realm_authority, *time_offset.borrow())
+    };
+    let lockup_status =
+        |index: u8| get_lockup_data(&context.solana, voter.address, index, *time_offset.borrow());
+
+    let day = 24 * 60 * 60;
+    let hour = 60 * 60;
+
+    create_deposit_entry(0, 3).await?;
+    deposit(0, 90).await?;
+    advance_time(day + hour).await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // after a day, 60 are locked: a third of them moves with the split
+    split(0, 1, 100).await.expect_err("more than deposited");
+    split(0, 1, 30).await?;
+    assert_eq!(
+        lockup_status(0).await,
+        LockupData {
+            time_passed: hour,
+            duration: 2 * day,
+            amount_initially_locked_native: 40,
+            amount_deposited_native: 60,
+            amount_unlocked: 20,
+        }
+    );
+    assert_eq!(
+        lockup_status(1).await,
+        LockupData {
+            time_passed: hour,
+            duration: 2 * day,
+            amount_initially_locked_native: 20,
+            amount_deposited_native: 30,
+            amount_unlocked: 10,
+        }
+    );
+    split(0, 1, 10).await.expect_err("target entry in use");
+
+    // only entries with matching lockups can be merged
+    create_deposit_entry(2, 5).await?;
+    deposit(2, 10).await?;
+    merge(2, 0).await.expect_err("different end");
+    merge(0, 0).await.expect_err("same entry");
+
+    merge(1, 0).await?;
+    assert_eq!(
+        lockup_status(0).await,
+        LockupData {
+            time_passed: hour,
+            duration: 2 * day,
+            amount_initially_locked_native: 60,
+            amount_deposited_native: 90,
+            amount_unlocked: 30,
+        }
+    );
+
+    // the freed entry can be reused
+    create_deposit_entry(1, 1).await?;
+
+    Ok(())
+}
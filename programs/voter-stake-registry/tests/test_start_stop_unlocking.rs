@@ -0,0 +1,164 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use std::cell::RefCell;
+use std::sync::Arc;
+use voter_stake_registry::state::{LockupKind, Voter};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_start_stop_unlocking() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, payer)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            payer,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(&registrar, &token_owner_record, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+    let create_deposit_entry = |index: u8, periods: u32| {
+        addin.create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            index,
+            LockupKind::Constant,
+            None,
+            periods,
+            false,
+        )
+    };
+    let deposit = |index: u8, amount: u64| {
+        addin.deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            reference_account,
+            index,
+            amount,
+        )
+    };
+    let start_unlocking = |source: u8, target: u8, amount: u64| {
+        addin.start_unlocking(&registrar, &voter, &voter_authority, source, target, amount)
+    };
+    let stop_unlocking = |source: u8, target: u8, amount: u64| {
+        addin.stop_unlocking(&registrar, &voter, &voter_authority, source, target, amount)
+    };
+    let time_offset = Arc::new(RefCell::new(0i64));
+    let advance_time = |extra: u64| {
+        *time_offset.borrow_mut() += extra as i64;
+        addin.set_time_offset(&registrar, &realm_authority, *time_offset.borrow())
+    };
+    let lockup_status =
+        |index: u8| get_lockup_data(&context.solana, voter.address, index, *time_offset.borrow());
+    let voter_address = voter.address;
+    let solana = &context.solana;
+    let lockup_kind = |index: u8| async move {
+        let voter_data = solana.get_account::<Voter>(voter_address).await;
+        voter_data.deposits[index as usize].lockup.kind
+    };
+
+    let day = 24 * 60 * 60;
+
+    create_deposit_entry(0, 5).await?;
+    deposit(0, 100).await?;
+    advance_time(2 * day).await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // tokens split off a Constant entry unlock after its full lockup duration
+    stop_unlocking(0, 1, 40)
+        .await
+        .expect_err("not a Cliff entry");
+    start_unlocking(0, 1, 40).await?;
+    assert_eq!(lockup_kind(1).await, LockupKind::Cliff);
+    assert_eq!(
+        lockup_status(1).await,
+        LockupData {
+            time_passed: 0,
+            duration: 5 * day,
+            amount_initially_locked_native: 40,
+            amount_deposited_native: 40,
+            amount_unlocked: 0,
+        }
+    );
+    assert_eq!(lockup_kind(0).await, LockupKind::Constant);
+    assert_eq!(lockup_status(0).await.amount_deposited_native, 60);
+
+    // whole entries are converted in place
+    start_unlocking(0, 0, 50)
+        .await
+        .expect_err("must convert the whole entry");
+    start_unlocking(0, 0, 60).await?;
+    assert_eq!(lockup_kind(0).await, LockupKind::Cliff);
+
+    // re-freezing keeps the time that is left
+    advance_time(2 * day).await;
+    context.solana.advance_clock_by_slots(2).await;
+    stop_unlocking(1, 1, 40).await?;
+    advance_time(day).await;
+    context.solana.advance_clock_by_slots(2).await;
+    assert_eq!(lockup_kind(1).await, LockupKind::Constant);
+    assert_eq!(
+        lockup_status(1).await,
+        LockupData {
+            time_passed: 0,
+            duration: 3 * day,
+            amount_initially_locked_native: 40,
+            amount_deposited_native: 40,
+            amount_unlocked: 0,
+        }
+    );
+    assert_eq!(
+        lockup_status(0).await,
+        LockupData {
+            time_passed: 3 * day,
+            duration: 5 * day,
+            amount_initially_locked_native: 60,
+            amount_deposited_native: 60,
+            amount_unlocked: 0,
+        }
+    );
+
+    Ok(())
+}
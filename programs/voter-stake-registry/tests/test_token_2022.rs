@@ -0,0 +1,358 @@
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::LockupKind;
+
+use program_test::*;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_token_2022_transfer_fee() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let voter_authority = &context.users[1].key;
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    // transfers pay a 1% fee
+    let fee_mint = context
+        .solana
+        .create_transfer_fee_mint(&realm_authority, 6, 100, 1_000_000)
+        .await;
+    let realm_authority_ata = context
+        .solana
+        .create_funded_token_account(&realm_authority.pubkey(), &fee_mint, 1_000_000)
+        .await;
+    let voter_authority_ata = context
+        .solana
+        .create_funded_token_account(&voter_authority.pubkey(), &fee_mint, 1_000_000)
+        .await;
+
+    let fee_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &fee_mint,
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &fee_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+
+    // the vault belongs to the spl-token-2022 program
+    let vault = voter.vault_address(&fee_voting_mint);
+    let vault_account = context
+        .solana
+        .context
+        .borrow_mut()
+        .banks_client
+        .get_account(vault)
+        .await?
+        .unwrap();
+    assert_eq!(vault_account.owner, spl_token_2022::id());
+
+    // deposits only credit the amount that arrives in the vault
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &fee_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            10000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana, 0).await, 9900);
+    assert_eq!(
+        fee_voting_mint.vault_balance(&context.solana, &voter).await,
+        9900
+    );
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &fee_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana, 0).await, 8900);
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter_authority_ata)
+            .await,
+        1_000_000 - 10000 + 990
+    );
+
+    // so do grants
+    addin
+        .grant(
+            &registrar,
+            voter_authority.pubkey(),
+            &fee_voting_mint,
+            LockupKind::Cliff,
+            None,
+            10,
+            true,
+            10000,
+            realm_authority_ata,
+            &realm_authority,
+            &realm_authority,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana, 1).await, 9900);
+    assert_eq!(
+        fee_voting_mint.vault_balance(&context.solana, &voter).await,
+        8900 + 9900
+    );
+
+    addin
+        .clawback(
+            &registrar,
+            &voter,
+            &fee_voting_mint,
+            &realm_authority,
+            realm_authority_ata,
+            1,
+            None,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana, 1).await, 0);
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(realm_authority_ata)
+            .await,
+        1_000_000 - 10000 + 9801
+    );
+
+    addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &fee_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            8900,
+        )
+        .await?;
+    assert_eq!(
+        fee_voting_mint.vault_balance(&context.solana, &voter).await,
+        0
+    );
+
+    // the fees withheld in the vault must be harvested before closing it
+    addin
+        .close_voter(&registrar, &voter, &fee_voting_mint, &voter_authority, &[])
+        .await
+        .expect_err("vault has withheld fees");
+    context
+        .solana
+        .harvest_withheld_fees(&fee_mint, &[vault])
+        .await;
+    addin
+        .close_voter(&registrar, &voter, &fee_voting_mint, &voter_authority, &[])
+        .await?;
+
+    Ok(())
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_token_2022_transfer_fee_voter_authority() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let voter_authority = &context.users[1].key;
+    let new_authority = &context.users[2].key;
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let new_token_owner_record = realm
+        .create_token_owner_record(new_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    // transfers pay a 1% fee
+    let fee_mint = context
+        .solana
+        .create_transfer_fee_mint(&realm_authority, 6, 100, 1_000_000)
+        .await;
+    let voter_authority_ata = context
+        .solana
+        .create_funded_token_account(&voter_authority.pubkey(), &fee_mint, 1_000_000)
+        .await;
+
+    let fee_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &fee_mint,
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    for (index, lockup_kind, periods, amount) in [
+        (0, LockupKind::None, 0, 10000),
+        (1, LockupKind::Cliff, 10, 5000),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter_authority,
+                &fee_voting_mint,
+                index,
+                lockup_kind,
+                None,
+                periods,
+                false,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                &fee_voting_mint,
+                &voter_authority,
+                voter_authority_ata,
+                index,
+                amount,
+            )
+            .await?;
+    }
+    assert_eq!(voter.deposit_amount(&context.solana, 0).await, 9900);
+    assert_eq!(voter.deposit_amount(&context.solana, 1).await, 4950);
+
+    addin
+        .propose_voter_authority(&voter, &voter_authority, new_authority.pubkey())
+        .await?;
+    let new_voter = addin
+        .accept_voter_authority(
+            &registrar,
+            &voter,
+            &[&fee_voting_mint],
+            &[],
+            &new_authority,
+            &new_token_owner_record,
+        )
+        .await?;
+
+    // moving the vault costs a fee of 149, which the deposit entries share
+    assert_eq!(
+        fee_voting_mint
+            .vault_balance(&context.solana, &new_voter)
+            .await,
+        14701
+    );
+    assert_eq!(new_voter.deposit_amount(&context.solana, 0).await, 9800);
+    assert_eq!(new_voter.deposit_amount(&context.solana, 1).await, 4901);
+
+    // the old vault was closed even though fees were withheld in it
+    assert!(
+        !context
+            .solana
+            .account_exists(voter.vault_address(&fee_voting_mint))
+            .await
+    );
+
+    addin
+        .withdraw(
+            &registrar,
+            &new_voter,
+            &fee_voting_mint,
+            &new_authority,
+            voter_authority_ata,
+            0,
+            9800,
+        )
+        .await?;
+
+    Ok(())
+}
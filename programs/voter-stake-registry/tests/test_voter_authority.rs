@@ -0,0 +1,437 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::{LockupKind, Tranche, VestingSchedule, Voter};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_voter_authority() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let new_authority = &context.users[2].key;
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let new_token_owner_record = realm
+        .create_token_owner_record(new_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    // a locked deposit that the realm authority may claw back
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::Cliff,
+            None,
+            10,
+            true,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+
+    addin
+        .accept_voter_authority(
+            &registrar,
+            &voter,
+            &[&mngo_voting_mint],
+            &[],
+            &new_authority,
+            &new_token_owner_record,
+        )
+        .await
+        .expect_err("no transfer was proposed");
+    addin
+        .propose_voter_authority(&voter, &new_authority, new_authority.pubkey())
+        .await
+        .expect_err("only the voter authority can propose");
+    addin
+        .propose_voter_authority(&voter, &voter_authority, new_authority.pubkey())
+        .await?;
+    addin
+        .accept_voter_authority(
+            &registrar,
+            &voter,
+            &[],
+            &[],
+            &new_authority,
+            &new_token_owner_record,
+        )
+        .await
+        .expect_err("the vault must move too");
+
+    context.solana.advance_clock_by_slots(2).await;
+    let new_voter = addin
+        .accept_voter_authority(
+            &registrar,
+            &voter,
+            &[&mngo_voting_mint],
+            &[],
+            &new_authority,
+            &new_token_owner_record,
+        )
+        .await?;
+
+    // the deposit and its tokens moved, lockup and clawback included
+    let voter_data = context.solana.get_account::<Voter>(new_voter.address).await;
+    assert_eq!(voter_data.voter_authority, new_authority.pubkey());
+    let deposit = &voter_data.deposits[0];
+    assert_eq!(deposit.amount_deposited_native, 1000);
+    assert_eq!(deposit.lockup.kind, LockupKind::Cliff);
+    assert!(deposit.allow_clawback);
+    assert_eq!(
+        mngo_voting_mint
+            .vault_balance(&context.solana, &new_voter)
+            .await,
+        1000
+    );
+
+    let vwr = addin
+        .update_voter_weight_record(&registrar, &new_voter)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000);
+    addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .expect_err("the old voter is closed");
+
+    Ok(())
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_voter_authority_token_programs() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let new_authority = &context.users[2].key;
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let new_token_owner_record = realm
+        .create_token_owner_record(new_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    // a second voting mint of the spl-token-2022 program
+    let mint_2022 = context
+        .solana
+        .create_transfer_fee_mint(&realm_authority, 6, 0, 0)
+        .await;
+    let voter_authority_ata_2022 = context
+        .solana
+        .create_funded_token_account(&voter_authority.pubkey(), &mint_2022, 1_000_000)
+        .await;
+    let voting_mint_2022 = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            1,
+            &mint_2022,
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    for (index, voting_mint, token_account) in [
+        (0, &mngo_voting_mint, voter_authority_ata),
+        (1, &voting_mint_2022, voter_authority_ata_2022),
+    ] {
+        addin
+            .create_deposit_entry(
+                &registrar,
+                &voter,
+                &voter_authority,
+                voting_mint,
+                index,
+                LockupKind::Cliff,
+                None,
+                10,
+                false,
+            )
+            .await?;
+        addin
+            .deposit(
+                &registrar,
+                &voter,
+                voting_mint,
+                &voter_authority,
+                token_account,
+                index,
+                1000,
+            )
+            .await?;
+    }
+
+    addin
+        .propose_voter_authority(&voter, &voter_authority, new_authority.pubkey())
+        .await?;
+    let new_voter = addin
+        .accept_voter_authority(
+            &registrar,
+            &voter,
+            &[&mngo_voting_mint, &voting_mint_2022],
+            &[],
+            &new_authority,
+            &new_token_owner_record,
+        )
+        .await?;
+
+    // both vaults moved, each with its own token program
+    for (index, voting_mint) in [(0, &mngo_voting_mint), (1, &voting_mint_2022)] {
+        assert_eq!(new_voter.deposit_amount(&context.solana, index).await, 1000);
+        assert_eq!(
+            voting_mint.vault_balance(&context.solana, &new_voter).await,
+            1000
+        );
+    }
+
+    Ok(())
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_voter_authority_tranches() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+    let realm_authority_ata = context.users[0].token_accounts[0];
+
+    let voter_authority = &context.users[1].key;
+
+    let new_authority = &context.users[2].key;
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+    let new_token_owner_record = realm
+        .create_token_owner_record(new_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    let now = context.solana.get_clock().await.unix_timestamp;
+    let tranches = vec![
+        Tranche {
+            unlock_ts: now + 24 * 60 * 60,
+            amount: 1000,
+        },
+        Tranche {
+            unlock_ts: now + 365 * 24 * 60 * 60,
+            amount: 9000,
+        },
+    ];
+    addin
+        .grant_tranches(
+            &registrar,
+            voter_authority.pubkey(),
+            &mngo_voting_mint,
+            tranches,
+            true,
+            0,
+            realm_authority_ata,
+            &realm_authority,
+            &realm_authority,
+        )
+        .await?;
+
+    addin
+        .propose_voter_authority(&voter, &voter_authority, new_authority.pubkey())
+        .await?;
+    addin
+        .accept_voter_authority(
+            &registrar,
+            &voter,
+            &[&mngo_voting_mint],
+            &[],
+            &new_authority,
+            &new_token_owner_record,
+        )
+        .await
+        .expect_err("the vesting schedule must move too");
+
+    context.solana.advance_clock_by_slots(2).await;
+    let new_voter = addin
+        .accept_voter_authority(
+            &registrar,
+            &voter,
+            &[&mngo_voting_mint],
+            &[0],
+            &new_authority,
+            &new_token_owner_record,
+        )
+        .await?;
+
+    // the tranches moved to a vesting schedule of the new voter
+    let voter_data = context.solana.get_account::<Voter>(new_voter.address).await;
+    let deposit = &voter_data.deposits[0];
+    assert_eq!(deposit.lockup.kind, LockupKind::Tranches);
+    assert_eq!(deposit.amount_deposited_native, 10000);
+    let schedule = context
+        .solana
+        .get_account::<VestingSchedule>(new_voter.vesting_schedule_address(0))
+        .await;
+    assert_eq!(schedule.voter, new_voter.address);
+    assert_eq!(schedule.deposit_entry_index, 0);
+    assert_eq!(schedule.tranches().len(), 2);
+    assert_eq!(schedule.tranches()[1].amount, 9000);
+    assert!(
+        !context
+            .solana
+            .account_exists(voter.vesting_schedule_address(0))
+            .await
+    );
+
+    addin
+        .update_voter_weight_record_with_schedules(&registrar, &new_voter, &[0])
+        .await?;
+
+    Ok(())
+}
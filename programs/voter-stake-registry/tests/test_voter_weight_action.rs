@@ -0,0 +1,109 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{pubkey::Pubkey, signer::Signer, transport::TransportError};
+use spl_governance_addin_api::voter_weight::VoterWeightAction as SplVoterWeightAction;
+use voter_stake_registry::state::{LockupKind, VoterWeightAction};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_voter_weight_action() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+
+    // the record is scoped to voting on one proposal
+    let proposal = Pubkey::new_unique();
+    let vwr = addin
+        .update_voter_weight_record_for_action(
+            &registrar,
+            &voter,
+            Some(VoterWeightAction::CastVote),
+            Some(proposal),
+        )
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000);
+    assert_eq!(vwr.weight_action, Some(SplVoterWeightAction::CastVote));
+    assert_eq!(vwr.weight_action_target, Some(proposal));
+
+    // without an action, the record can be used for anything
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(vwr.voter_weight, 1000);
+    assert_eq!(vwr.weight_action, None);
+    assert_eq!(vwr.weight_action_target, None);
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{signer::Signer, transport::TransportError};
+use voter_stake_registry::state::LockupKind;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_voting_mint_rate() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let rate_authority = &context.users[2].key;
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    let day = 24 * 60 * 60;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * day as u64,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+
+    addin
+        .configure_voting_mint_rate(
+            &registrar,
+            &voter_authority,
+            &voter_authority,
+            &mngo_voting_mint,
+            rate_authority.pubkey(),
+            3_000_000_000,
+            day as u64,
+        )
+        .await
+        .expect_err("only the realm authority can price voting mints");
+    addin
+        .configure_voting_mint_rate(
+            &registrar,
+            &realm_authority,
+            &realm_authority,
+            &mngo_voting_mint,
+            rate_authority.pubkey(),
+            3_000_000_000,
+            day as u64,
+        )
+        .await?;
+
+    // the rate account is needed, and there's no weight before the first update
+    addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .expect_err("rate account missing");
+    let vwr = addin
+        .update_voter_weight_record_with_rates(&registrar, &voter, &[&mngo_voting_mint])
+        .await?;
+    assert_eq!(vwr.voter_weight, 0);
+
+    addin
+        .set_voting_mint_rate(
+            &registrar,
+            &mngo_voting_mint,
+            &voter_authority,
+            1_500_000_000,
+        )
+        .await
+        .expect_err("not the rate authority");
+    addin
+        .set_voting_mint_rate(
+            &registrar,
+            &mngo_voting_mint,
+            &rate_authority,
+            4_000_000_000,
+        )
+        .await
+        .expect_err("above the max rate");
+    addin
+        .set_voting_mint_rate(
+            &registrar,
+            &mngo_voting_mint,
+            &rate_authority,
+            1_500_000_000,
+        )
+        .await?;
+
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_rates(&registrar, &voter, &[&mngo_voting_mint])
+        .await?;
+    assert_eq!(vwr.voter_weight, 1500);
+
+    // reconfiguring the voting mint keeps the pricing
+    addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            2.0,
+            0.0,
+            5 * 365 * day as u64,
+            None,
+            Some(&[mngo_voting_mint.rate_address(&registrar)]),
+        )
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_rates(&registrar, &voter, &[&mngo_voting_mint])
+        .await?;
+    assert_eq!(vwr.voter_weight, 3000);
+
+    // stale rates give no weight until the next update
+    addin
+        .set_time_offset(&registrar, &realm_authority, 2 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_rates(&registrar, &voter, &[&mngo_voting_mint])
+        .await?;
+    assert_eq!(vwr.voter_weight, 0);
+
+    addin
+        .set_voting_mint_rate(&registrar, &mngo_voting_mint, &rate_authority, 500_000_000)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_voter_weight_record_with_rates(&registrar, &voter, &[&mngo_voting_mint])
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000);
+
+    Ok(())
+}
@@ -0,0 +1,381 @@
+use program_test::*;
+use solana_program_test::*;
+use solana_sdk::{pubkey::Pubkey, signer::Signer, transport::TransportError};
+use spl_governance_addin_api::voter_weight::VoterWeightAction as SplVoterWeightAction;
+use voter_stake_registry::state::{LockupKind, VoterWeightAction, VoterWeightCheckpoints};
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_weight_checkpoints() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    // no lockup bonus, the weight is the deposited amount
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            0.0,
+            5 * 365 * 24 * 60 * 60,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+
+    let day = 24 * 60 * 60;
+    let start = context.solana.get_clock().await.unix_timestamp;
+    addin
+        .create_voter_weight_checkpoints(&registrar, &voter, &voter_authority)
+        .await?;
+    let checkpoints = context
+        .solana
+        .get_account::<VoterWeightCheckpoints>(voter.weight_checkpoints_address())
+        .await;
+    assert_eq!(checkpoints.voter, voter.address);
+    assert_eq!(checkpoints.len, 1);
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            500,
+        )
+        .await
+        .expect_err("weight checkpoints missing");
+    addin
+        .deposit_with_checkpoints(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            500,
+        )
+        .await?;
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 2 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    addin
+        .withdraw_with_checkpoints(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1200,
+        )
+        .await?;
+
+    let vwr = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(vwr.voter_weight, 300);
+    addin
+        .update_historical_voter_weight_record(&registrar, &voter, start)
+        .await
+        .expect_err("historical voter weight disabled");
+
+    // only one of the two ways to write the record works at a time
+    let voter_authority2 = &context.users[2].key;
+    addin
+        .set_historical_voter_weight(&registrar, &voter_authority2, true)
+        .await
+        .expect_err("not the realm authority");
+    addin
+        .set_historical_voter_weight(&registrar, &realm_authority, true)
+        .await?;
+    addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await
+        .expect_err("historical voter weight enabled");
+
+    // the weight at past times
+    let vwr = addin
+        .update_historical_voter_weight_record(&registrar, &voter, start + day / 2)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000);
+    let vwr = addin
+        .update_historical_voter_weight_record(&registrar, &voter, start + day + day / 2)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1500);
+
+    // the record can be scoped to voting on one proposal
+    context.solana.advance_clock_by_slots(2).await;
+    let proposal = Pubkey::new_unique();
+    let vwr = addin
+        .update_historical_voter_weight_record_for_action(
+            &registrar,
+            &voter,
+            start + day / 2,
+            Some(VoterWeightAction::CastVote),
+            Some(proposal),
+        )
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000);
+    assert_eq!(vwr.weight_action, Some(SplVoterWeightAction::CastVote));
+    assert_eq!(vwr.weight_action_target, Some(proposal));
+
+    // without an action, the record can be used for anything
+    context.solana.advance_clock_by_slots(2).await;
+    let vwr = addin
+        .update_historical_voter_weight_record(&registrar, &voter, start + day / 2)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000);
+    assert_eq!(vwr.weight_action, None);
+    assert_eq!(vwr.weight_action_target, None);
+
+    addin
+        .update_historical_voter_weight_record(&registrar, &voter, start - 1)
+        .await
+        .expect_err("before the first checkpoint");
+    addin
+        .update_historical_voter_weight_record(&registrar, &voter, start + 3 * day)
+        .await
+        .expect_err("in the future");
+
+    addin
+        .close_voter_weight_checkpoints(&registrar, &voter, &voter_authority)
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            500,
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_weight_checkpoints_decay() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let addin = &context.addin;
+
+    let realm_authority = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter_authority_ata = context.users[1].token_accounts[0];
+
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &realm_authority,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &realm_authority)
+        .await;
+
+    let registrar = addin
+        .create_registrar(&realm, &realm_authority, realm_authority)
+        .await;
+
+    // the lockup bonus saturates at 10 days
+    let day = 24 * 60 * 60;
+    let mngo_voting_mint = addin
+        .configure_voting_mint(
+            &registrar,
+            &realm_authority,
+            realm_authority,
+            0,
+            &context.mints[0],
+            0,
+            1.0,
+            1.0,
+            10 * day as u64,
+            None,
+            None,
+        )
+        .await;
+
+    let voter = addin
+        .create_voter(
+            &registrar,
+            &token_owner_record,
+            &voter_authority,
+            &realm_authority,
+        )
+        .await;
+
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            0,
+            LockupKind::Cliff,
+            None,
+            10,
+            false,
+        )
+        .await?;
+    addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &mngo_voting_mint,
+            1,
+            LockupKind::None,
+            None,
+            0,
+            false,
+        )
+        .await?;
+    addin
+        .deposit(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            0,
+            1000,
+        )
+        .await?;
+
+    // the cliff lockup adds 1000 and loses 100 of that per day
+    let start = context.solana.get_clock().await.unix_timestamp;
+    addin
+        .create_voter_weight_checkpoints(&registrar, &voter, &voter_authority)
+        .await?;
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 4 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    addin
+        .deposit_with_checkpoints(
+            &registrar,
+            &voter,
+            &mngo_voting_mint,
+            &voter_authority,
+            voter_authority_ata,
+            1,
+            500,
+        )
+        .await?;
+
+    let vwr = addin.update_voter_weight_record(&registrar, &voter).await?;
+    assert_eq!(vwr.voter_weight, 1000 + 600 + 500);
+    addin
+        .set_historical_voter_weight(&registrar, &realm_authority, true)
+        .await?;
+
+    // the lockup decayed between the two checkpoints
+    let vwr = addin
+        .update_historical_voter_weight_record(&registrar, &voter, start)
+        .await?;
+    assert_eq!(vwr.voter_weight, 2000);
+    let vwr = addin
+        .update_historical_voter_weight_record(&registrar, &voter, start + 2 * day)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000 + 800);
+
+    addin
+        .set_time_offset(&registrar, &realm_authority, 12 * day)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // and after the last one, until the lockup expired
+    let vwr = addin
+        .update_historical_voter_weight_record(&registrar, &voter, start + 5 * day)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000 + 500 + 500);
+    let vwr = addin
+        .update_historical_voter_weight_record(&registrar, &voter, start + 11 * day)
+        .await?;
+    assert_eq!(vwr.voter_weight, 1000 + 500);
+
+    Ok(())
+}